@@ -168,7 +168,7 @@ fn generate_chimera_binding(_: &Path, out_dir: &Path) -> Result<()> {
         .with_context(|| "copy binding file")
 }
 
-#[cfg(all(not(feature = "chimera"), target_pointer_width = "64"))]
+#[cfg(not(feature = "chimera"))]
 fn generate_chimera_binding(_: &Path, _: &Path) -> Result<()> {
     Ok(())
 }
@@ -1,5 +1,9 @@
 //! Hyperscan is a software regular expression matching engine
 //! designed with high performance and flexibility in mind.
+//!
+//! The bundled bindings are generated from the Hyperscan 5.4 headers, including the literal API,
+//! the combination and quiet flags, the Icelake tune families and the stream compression,
+//! enable the `gen` feature to regenerate them from the installed headers with `bindgen`.
 #![no_std]
 #![allow(non_camel_case_types, deref_nullptr)]
 
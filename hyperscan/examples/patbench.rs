@@ -313,7 +313,7 @@ impl Benchmark {
     fn open_streams(&mut self, db: &StreamingDatabase) -> Result<()> {
         self.streams = (0..self.sessions.len())
             .map(|_| db.open_stream())
-            .collect::<Result<Vec<_>>>()?;
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(())
     }
@@ -323,7 +323,7 @@ impl Benchmark {
         let matches = &mut self.matches;
 
         for stream in self.streams.drain(..) {
-            stream.close(&scratch, |_, _, _, _| {
+            let _ = stream.close(&scratch, |_, _, _, _| {
                 *matches += 1;
                 Matching::Continue
             })?;
@@ -339,7 +339,7 @@ impl Benchmark {
         for (i, ref packet) in self.packets.iter().enumerate() {
             let ref stream = self.streams[self.stream_ids[i]];
 
-            stream.scan(&packet, &scratch, |_, _, _, _| {
+            let _ = stream.scan(&packet, &scratch, |_, _, _, _| {
                 *matches += 1;
                 Matching::Continue
             })?;
@@ -354,7 +354,7 @@ impl Benchmark {
         let matches = &mut self.matches;
 
         for packet in &self.packets {
-            db.scan(packet, &scratch, |_, _, _, _| {
+            let _ = db.scan(packet, &scratch, |_, _, _, _| {
                 *matches += 1;
                 Matching::Continue
             })?;
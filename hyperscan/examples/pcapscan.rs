@@ -218,7 +218,7 @@ impl Benchmark {
     fn open_streams(&mut self) -> Result<()> {
         self.streams = iter::repeat_with(|| self.streaming_db.open_stream())
             .take(self.sessions.len())
-            .collect::<Result<Vec<_>>>()?;
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(())
     }
@@ -227,7 +227,7 @@ impl Benchmark {
     fn close_streams(&mut self) -> Result<()> {
         for stream in self.streams.drain(..) {
             let match_count = &self.match_count;
-            stream
+            let _ = stream
                 .close(&self.scratch, |_, _, _, _| {
                     match_count.fetch_add(1, Ordering::Relaxed);
 
@@ -241,7 +241,7 @@ impl Benchmark {
 
     fn reset_streams(&mut self) -> Result<()> {
         for ref stream in &self.streams {
-            stream
+            let _ = stream
                 .reset(&self.scratch, |_, _, _, _| {
                     self.match_count.fetch_add(1, Ordering::Relaxed);
 
@@ -259,7 +259,7 @@ impl Benchmark {
         for (i, ref packet) in self.packets.iter().enumerate() {
             let ref stream = self.streams[self.stream_ids[i]];
 
-            stream
+            let _ = stream
                 .scan(packet.as_ref().as_slice(), &self.scratch, |_, _, _, _| {
                     self.match_count.fetch_add(1, Ordering::Relaxed);
 
@@ -275,7 +275,8 @@ impl Benchmark {
     // through Hyperscan using the block-mode interface.
     fn scan_block(&mut self) -> Result<()> {
         for ref packet in &self.packets {
-            self.block_db
+            let _ = self
+                .block_db
                 .scan(packet.as_ref().as_slice(), &self.scratch, |_, _, _, _| {
                     self.match_count.fetch_add(1, Ordering::Relaxed);
 
@@ -83,17 +83,17 @@ fn main() -> Result<()> {
 
     println!("Scanning {} bytes with Hyperscan", input_data.len());
 
-    database
-        .scan(&input_data, &scratch, |_, from, to, _| {
+    let _ = database
+        .scan_map(&input_data, &scratch, |_, matched| {
             println!(
-                "Match for pattern \"{}\" at offset {}..{}: {}",
+                "Match for pattern \"{}\": {}",
                 pattern.expression,
-                from,
-                to,
-                &input_data[from as usize..to as usize]
+                String::from_utf8_lossy(matched)
             );
 
             Matching::Continue
         })
-        .with_context(|| "scan input buffer")
+        .with_context(|| "scan input buffer")?;
+
+    Ok(())
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "compile")]
+use std::convert::TryFrom;
 use std::ffi::CString;
 use std::mem::MaybeUninit;
 use std::ptr::null;
@@ -19,6 +21,9 @@ use crate::PlatformRef;
 /// match when the match callback is called. Using mode flag `Mode::Groups`
 /// will also fill the `captured' array with the start and end offsets of all
 /// the capturing groups specified by the pattern that has matched.
+///
+/// The capturing groups need a larger scratch space and more work of PCRE to confirm the matches,
+/// `Pattern::capture_groups()` reports the number of the capturing groups of a pattern to choose the mode.
 #[repr(u32)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Mode {
@@ -34,7 +39,40 @@ impl Default for Mode {
     }
 }
 
+/// The default limit on the amount of match function called in PCRE, which is the built-in default of PCRE.
+pub const DEFAULT_MATCH_LIMIT: u64 = 10_000_000;
+
+/// The default limit on the recursion depth of match function in PCRE, which is the built-in default of PCRE.
+pub const DEFAULT_MATCH_LIMIT_RECURSION: u64 = 10_000_000;
+
 /// Define match limits for PCRE runtime.
+///
+/// The limits bound the worst-case cost of PCRE to confirm a match on the hostile inputs,
+/// the error event handler is called with `MatchError::MatchLimit` or `MatchError::RecursionLimit` when a limit is hit.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::chimera::{prelude::*, MatchError, MatchLimit, Mode};
+/// let limit = MatchLimit::default().max_matches(100_000).recursion_depth(1_000);
+/// let db = r"/(\w+)\s+\1/".with_limits(Mode::NoGroups, limit).unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut matches = vec![];
+/// let mut errors: Vec<(MatchError, u32)> = vec![];
+///
+/// db.scan("hello hello world", &s, |_, from, to, _, _| {
+///     matches.push(from..to);
+///     Matching::Continue
+/// }, |error_type, id| {
+///     // the limit was hit, the matches of the pattern may be missed.
+///     errors.push((error_type, id));
+///     Matching::Skip
+/// }).unwrap();
+///
+/// assert_eq!(matches, vec![0..11]);
+/// assert!(errors.is_empty());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct MatchLimit {
     /// A limit from pcre_extra on the amount of match function called in PCRE to limit backtracking that can take place.
     pub max_matches: u64,
@@ -42,6 +80,37 @@ pub struct MatchLimit {
     pub recursion_depth: u64,
 }
 
+impl Default for MatchLimit {
+    fn default() -> Self {
+        MatchLimit {
+            max_matches: DEFAULT_MATCH_LIMIT,
+            recursion_depth: DEFAULT_MATCH_LIMIT_RECURSION,
+        }
+    }
+}
+
+impl MatchLimit {
+    /// Constructs the match limits, the `match_limit` and `match_limit_recursion` of PCRE.
+    pub fn new(max_matches: u64, recursion_depth: u64) -> Self {
+        MatchLimit {
+            max_matches,
+            recursion_depth,
+        }
+    }
+
+    /// Set the limit on the amount of match function called in PCRE.
+    pub fn max_matches(mut self, max_matches: u64) -> Self {
+        self.max_matches = max_matches;
+        self
+    }
+
+    /// Set the limit on the recursion depth of match function in PCRE.
+    pub fn recursion_depth(mut self, recursion_depth: u64) -> Self {
+        self.recursion_depth = recursion_depth;
+        self
+    }
+}
+
 /// Compile an expression into a Chimera database.
 ///
 /// # Examples
@@ -173,6 +242,41 @@ pub trait Builder {
         self.for_platform(Mode::Groups, None, None)
     }
 
+    /// Build an expression is compiled into a Chimera database with the compile mode.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::chimera::{prelude::*, Mode};
+    /// let patterns: Patterns = "/foo(bar)?/i\n/baz/".parse().unwrap();
+    /// let mode = if patterns.capture_groups().iter().any(|&n| n > 0) {
+    ///     Mode::Groups
+    /// } else {
+    ///     Mode::NoGroups
+    /// };
+    ///
+    /// assert_eq!(mode, Mode::Groups);
+    ///
+    /// let db = patterns.with_mode(mode).unwrap();
+    /// let scratch = db.alloc_scratch().unwrap();
+    /// let mut captures = vec![];
+    ///
+    /// db.scan("hello foobar!", &scratch, |_, _, _, _, captured: Option<&[Capture]>| {
+    ///     captures.extend(captured.unwrap_or_default().iter().map(|c| c.range()));
+    ///     Matching::Continue
+    /// }, Matching::Skip).unwrap();
+    ///
+    /// assert_eq!(captures, vec![6..12, 9..12]);
+    /// ```
+    fn with_mode(&self, mode: Mode) -> Result<Database, Self::Err> {
+        self.for_platform(mode, None, None)
+    }
+
+    /// Build an expression is compiled into a Chimera database with the compile mode and the PCRE match limits.
+    fn with_limits(&self, mode: Mode, match_limit: MatchLimit) -> Result<Database, Self::Err> {
+        self.for_platform(mode, Some(match_limit), None)
+    }
+
     /// Build an expression is compiled into a Chimera database for a target platform.
     fn for_platform(
         &self,
@@ -311,6 +415,53 @@ impl Builder for Patterns {
     }
 }
 
+#[cfg(feature = "compile")]
+impl Builder for crate::compile::Pattern {
+    type Err = Error;
+
+    /// Build a Hyperscan pattern into a Chimera database, the pattern is converted with `Pattern::try_from`.
+    fn for_platform(
+        &self,
+        mode: Mode,
+        match_limit: Option<MatchLimit>,
+        platform: Option<&PlatformRef>,
+    ) -> Result<Database, Self::Err> {
+        Pattern::try_from(self)?.for_platform(mode, match_limit, platform)
+    }
+}
+
+#[cfg(feature = "compile")]
+impl Builder for crate::compile::Patterns {
+    type Err = Error;
+
+    /// Build the Hyperscan patterns into a Chimera database, the patterns are converted with `Patterns::try_from`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{chimera::{Builder as _, Capture, Matching, Mode}, prelude::*, ChimeraDatabase};
+    /// let patterns = patterns!(r"(\w+)@example\.com", "foo"; CASELESS);
+    /// let db: ChimeraDatabase = patterns.with_mode(Mode::Groups).unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let mut matches = vec![];
+    ///
+    /// db.scan("mail Bob@Example.com", &s, |id, _, _, _, captured: Option<&[Capture]>| {
+    ///     matches.push((id, captured.unwrap()[1].range()));
+    ///     Matching::Continue
+    /// }, Matching::Skip).unwrap();
+    ///
+    /// assert_eq!(matches, vec![(0, 5..8)]);
+    /// ```
+    fn for_platform(
+        &self,
+        mode: Mode,
+        match_limit: Option<MatchLimit>,
+        platform: Option<&PlatformRef>,
+    ) -> Result<Database, Self::Err> {
+        Patterns::try_from(self)?.for_platform(mode, match_limit, platform)
+    }
+}
+
 impl FromStr for Database {
     type Err = anyhow::Error;
 
@@ -35,7 +35,7 @@ mod runtime;
 pub use crate::ffi::chimera as ffi;
 
 pub use self::common::{version, Database, DatabaseRef};
-pub use self::compile::{compile, Builder, Mode};
+pub use self::compile::{compile, Builder, MatchLimit, Mode, DEFAULT_MATCH_LIMIT, DEFAULT_MATCH_LIMIT_RECURSION};
 pub use self::errors::{CompileError, Error};
 pub use self::pattern::{Flags, Pattern, Patterns};
 pub use self::runtime::{
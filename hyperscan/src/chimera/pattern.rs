@@ -1,3 +1,5 @@
+#[cfg(feature = "compile")]
+use std::convert::TryFrom;
 use std::fmt;
 use std::iter::FromIterator;
 use std::str::FromStr;
@@ -140,6 +142,74 @@ impl Pattern {
         self.flags |= Flags::UCP;
         self
     }
+
+    /// Returns the number of the capturing groups in the expression, not including the whole match.
+    ///
+    /// In `Mode::Groups`, the `captured` array of a match contains the whole match and each of the capturing groups,
+    /// while the scratch space has to track all of them. The groups are counted by the syntax of the expression,
+    /// the named groups are included, while the non-capturing groups, lookarounds and comments are not.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::chimera::prelude::*;
+    /// let pattern: Pattern = r"(?<user>\w+)@(\w+)(?:\.\w+)+[()]\(".parse().unwrap();
+    ///
+    /// assert_eq!(pattern.capture_groups(), 2);
+    /// ```
+    pub fn capture_groups(&self) -> usize {
+        let mut chars = self.expression.chars().peekable();
+        let mut groups = 0;
+        let mut in_class = false;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    // skip the quoted sequence `\Q...\E`
+                    if chars.next() == Some('Q') {
+                        while let Some(c) = chars.next() {
+                            if c == '\\' && chars.next_if_eq(&'E').is_some() {
+                                break;
+                            }
+                        }
+                    }
+                }
+                '[' if in_class => {
+                    // skip the POSIX class, e.g. `[:alpha:]`
+                    if chars.next_if_eq(&':').is_some() {
+                        while chars.next().map_or(false, |c| c != ']') {}
+                    }
+                }
+                '[' => {
+                    in_class = true;
+                    chars.next_if_eq(&'^');
+                    chars.next_if_eq(&']');
+                }
+                ']' => in_class = false,
+                '(' if !in_class => match chars.peek() {
+                    Some('?') => {
+                        chars.next();
+
+                        let named = match chars.next() {
+                            Some('<') => chars.peek().map_or(false, |&c| c != '=' && c != '!'),
+                            Some('P') => chars.peek() == Some(&'<'),
+                            Some('\'') => true,
+                            _ => false,
+                        };
+
+                        if named {
+                            groups += 1;
+                        }
+                    }
+                    Some('*') => {}
+                    _ => groups += 1,
+                },
+                _ => {}
+            }
+        }
+
+        groups
+    }
 }
 
 impl fmt::Display for Pattern {
@@ -195,6 +265,73 @@ impl FromIterator<Pattern> for Patterns {
     }
 }
 
+#[cfg(feature = "compile")]
+impl TryFrom<&crate::compile::Pattern> for Pattern {
+    type Error = Error;
+
+    /// Convert a Hyperscan pattern to a Chimera pattern.
+    ///
+    /// The start of match is always reported by Chimera, so `SOM_LEFTMOST` and the SOM horizon are ignored,
+    /// while the flags and the extended parameters which are not supported by Chimera are rejected.
+    fn try_from(pattern: &crate::compile::Pattern) -> Result<Self, Self::Error> {
+        use crate::compile::Flags as HsFlags;
+
+        let supported = [
+            (HsFlags::CASELESS, Flags::CASELESS),
+            (HsFlags::DOTALL, Flags::DOTALL),
+            (HsFlags::MULTILINE, Flags::MULTILINE),
+            (HsFlags::SINGLEMATCH, Flags::SINGLEMATCH),
+            (HsFlags::UTF8, Flags::UTF8),
+            (HsFlags::UCP, Flags::UCP),
+        ];
+        let unsupported = supported
+            .iter()
+            .fold(pattern.flags - HsFlags::SOM_LEFTMOST, |flags, &(flag, _)| flags - flag);
+
+        if !unsupported.is_empty() {
+            bail!("unsupported pattern flags for Chimera: {}", unsupported);
+        }
+
+        let flags = supported
+            .iter()
+            .filter(|&&(flag, _)| pattern.flags.contains(flag))
+            .fold(Flags::empty(), |flags, &(_, flag)| flags | flag);
+
+        if !pattern.ext.is_empty() {
+            bail!("unsupported extended parameters for Chimera: {}", pattern.ext);
+        }
+
+        Ok(Pattern {
+            expression: pattern.expression.clone(),
+            flags,
+            id: pattern.id,
+        })
+    }
+}
+
+#[cfg(feature = "compile")]
+impl TryFrom<&crate::compile::Patterns> for Patterns {
+    type Error = Error;
+
+    /// Convert the Hyperscan patterns to Chimera patterns, see `Pattern::try_from`.
+    fn try_from(patterns: &crate::compile::Patterns) -> Result<Self, Self::Error> {
+        patterns
+            .iter()
+            .map(Pattern::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+impl Patterns {
+    /// Returns the number of the capturing groups of each pattern, see `Pattern::capture_groups()`.
+    ///
+    /// The patterns without any capturing group could be compiled with `Mode::NoGroups` for a smaller scratch space.
+    pub fn capture_groups(&self) -> Vec<usize> {
+        self.iter().map(Pattern::capture_groups).collect()
+    }
+}
+
 impl FromStr for Patterns {
     type Err = Error;
 
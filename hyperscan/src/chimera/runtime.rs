@@ -1,7 +1,7 @@
 use std::fmt;
 use std::mem::{self, MaybeUninit};
 use std::ops::Range;
-use std::ptr;
+use std::ptr::{self, NonNull};
 use std::slice;
 
 use anyhow::Result;
@@ -9,6 +9,7 @@ use derive_more::{Deref, From, Into};
 use foreign_types::{foreign_type, ForeignType, ForeignTypeRef};
 
 use crate::chimera::{errors::AsResult, ffi, DatabaseRef};
+use crate::closure;
 
 foreign_type! {
     /// A large enough region of scratch space to support a given database.
@@ -58,9 +59,42 @@ impl DatabaseRef {
 
         unsafe { ffi::ch_alloc_scratch(self.as_ptr(), &mut s).map(|_| Scratch::from_ptr(s)) }
     }
+
+    /// Grow a `scratch` space in place to support this database as well,
+    /// like `hyperscan::DatabaseRef::realloc_scratch`.
+    pub fn grow_scratch<'a>(&'a self, s: &'a mut Scratch) -> Result<&'a mut Scratch> {
+        let mut p = s.as_ptr();
+
+        unsafe {
+            ffi::ch_alloc_scratch(self.as_ptr(), &mut p).map(|_| {
+                s.0 = NonNull::new_unchecked(p);
+            })?;
+        }
+
+        Ok(s)
+    }
 }
 
 /// Callback return value used to tell the Chimera matcher what to do after processing this match.
+///
+/// Unlike Hyperscan, the callback can mute an individual pattern for the rest of the scan with `Matching::Skip`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::chimera::prelude::*;
+/// let db: Database = "/a/".parse().unwrap();
+/// let scratch = db.alloc_scratch().unwrap();
+/// let mut matches = vec![];
+///
+/// db.scan("a a a", &scratch, |_id, from, to, _flags, _captured| {
+///     matches.push((from, to));
+///
+///     Matching::Skip
+/// }, |_error_type, _id| Matching::Continue).unwrap();
+///
+/// assert_eq!(matches, vec![(0, 1)]);
+/// ```
 #[repr(u32)]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Matching {
@@ -68,7 +102,7 @@ pub enum Matching {
     Continue = ffi::CH_CALLBACK_CONTINUE,
     /// Terminate matching.
     Terminate = ffi::CH_CALLBACK_TERMINATE,
-    /// Skip remaining matches for this ID and continue.
+    /// Skip remaining matches for this ID and continue matching the other patterns.
     Skip = ffi::CH_CALLBACK_SKIP_PATTERN,
 }
 
@@ -187,18 +221,14 @@ where
     F: FnMut(u32, u64, u64, u32, Option<&'a [Capture]>) -> Matching,
 {
     let &mut (ref mut callback, _) = &mut *(ctx as *mut (&mut F, *mut ()));
-
-    callback(
-        id,
-        from,
-        to,
-        flags,
-        if captured.is_null() || size == 0 {
-            None
-        } else {
-            Some(slice::from_raw_parts(captured as *const _, size as usize))
-        },
-    ) as i32
+    let captured = if captured.is_null() || size == 0 {
+        None
+    } else {
+        Some(slice::from_raw_parts(captured as *const _, size as usize))
+    };
+
+    // the panic is resumed after `ch_scan` returns, instead of unwinding across the FFI boundary
+    closure::catch_unwind(|| callback(id, from, to, flags, captured)) as i32
 }
 
 /// Definition of the Chimera error event callback function type.
@@ -255,8 +285,9 @@ where
     F: FnMut(Error, u32) -> Matching,
 {
     let &mut (_, ref mut callback) = &mut *(ctx as *mut (*mut (), &mut F));
+    let error_type: Error = mem::transmute(error_type);
 
-    callback(mem::transmute(error_type), id) as i32
+    closure::catch_unwind(|| callback(error_type, id)) as i32
 }
 
 impl DatabaseRef {
@@ -330,7 +361,7 @@ impl DatabaseRef {
 
             let mut userdata = (on_match_data, on_error_data);
 
-            ffi::ch_scan(
+            let res = ffi::ch_scan(
                 self.as_ptr(),
                 data.as_ptr() as *const _,
                 data.len() as _,
@@ -339,8 +370,11 @@ impl DatabaseRef {
                 on_match_callback,
                 on_error_callback,
                 &mut userdata as *mut _ as *mut _,
-            )
-            .ok()
+            );
+
+            closure::resume_unwind();
+
+            res.ok()
         }
     }
 }
@@ -0,0 +1,121 @@
+//! Calling the Rust closures from the C callbacks without unwinding across the FFI boundary.
+// the closures are only split by the Hyperscan runtime, Chimera calls `catch_unwind` from its own trampolines
+#![cfg_attr(not(feature = "runtime"), allow(dead_code))]
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+use libc::c_void;
+
+thread_local! {
+    /// The panic caught by a trampoline, which is resumed after the FFI call returns.
+    static PANIC: RefCell<Option<Box<dyn Any + Send>>> = RefCell::new(None);
+}
+
+/// The value returned by a trampoline to stop the scan when the closure panics.
+pub trait Terminate {
+    /// Stop the scan.
+    const TERMINATE: Self;
+}
+
+#[cfg(feature = "runtime")]
+impl Terminate for crate::runtime::Matching {
+    const TERMINATE: Self = crate::runtime::Matching::Terminate;
+}
+
+#[cfg(feature = "chimera")]
+impl Terminate for crate::chimera::Matching {
+    const TERMINATE: Self = crate::chimera::Matching::Terminate;
+}
+
+/// Resume the panic of a closure caught by the trampoline on the current thread, if any.
+///
+/// It should be called after the FFI call which may invoke the trampoline returns.
+pub(crate) fn resume_unwind() {
+    if let Some(payload) = PANIC.with(|p| p.borrow_mut().take()) {
+        panic::resume_unwind(payload)
+    }
+}
+
+/// Call the callback from a trampoline, the panic is caught and stored to be resumed by `resume_unwind`,
+/// and `Terminate::TERMINATE` is returned to stop the scan.
+pub(crate) fn catch_unwind<F, Ret>(callback: F) -> Ret
+where
+    F: FnOnce() -> Ret,
+    Ret: Terminate,
+{
+    // the callback may be called again after it panicked, e.g. for the end of data matches
+    if PANIC.with(|p| p.borrow().is_some()) {
+        return Ret::TERMINATE;
+    }
+
+    match panic::catch_unwind(AssertUnwindSafe(callback)) {
+        Ok(ret) => ret,
+        Err(payload) => {
+            PANIC.with(|p| *p.borrow_mut() = Some(payload));
+
+            Ret::TERMINATE
+        }
+    }
+}
+
+/// Split a closure to a trampoline and its userdata pointer.
+///
+/// The panic of the closure doesn't unwind across the FFI boundary, the trampoline catches it
+/// with `catch_unwind`, and the panic is resumed by `resume_unwind`.
+pub(crate) unsafe fn split_closure<C, Args, Ret>(closure: &mut C) -> (C::Trampoline, *mut c_void)
+where
+    C: Split<Args, Ret>,
+{
+    (C::TRAMPOLINE, closure as *mut _ as *mut _)
+}
+
+pub trait Split<Args, Ret> {
+    type Trampoline: Copy;
+
+    const TRAMPOLINE: Self::Trampoline;
+}
+
+macro_rules! impl_split {
+    ($( $outer:ident ),* ; $( $inner:ident ),*) => {
+        impl<Func, Ret, $($outer),*> Split<($( $outer, )*), Ret> for Func
+        where
+            Func: FnMut($($outer),*) -> Ret,
+            Ret: Terminate,
+        {
+            type Trampoline = unsafe extern "C" fn($($outer,)* *mut c_void) -> Ret;
+
+            const TRAMPOLINE: Self::Trampoline = {
+                #[allow(non_snake_case)]
+                unsafe extern "C" fn trampoline<T, Ret_, $( $inner ),*>($($inner: $inner,)* ptr: *mut c_void) -> Ret_
+                where
+                    T: FnMut($($inner),*) -> Ret_,
+                    Ret_: Terminate,
+                {
+                    let callback = &mut *(ptr as *mut T);
+
+                    catch_unwind(|| callback($($inner),*))
+                }
+
+                trampoline::<Func, Ret, $($outer,)*>
+            };
+        }
+    }
+}
+
+impl_split!(;);
+impl_split!(A; A);
+impl_split!(A, B; A, B);
+impl_split!(A, B, C; A, B, C);
+impl_split!(A, B, C, D; A, B, C, D);
+impl_split!(A, B, C, D, E; A, B, C, D, E);
+impl_split!(A, B, C, D, E, F; A, B, C, D, E, F);
+impl_split!(A, B, C, D, E, F, G; A, B, C, D, E, F, G);
+impl_split!(A, B, C, D, E, F, G, H; A, B, C, D, E, F, G, H);
+impl_split!(A, B, C, D, E, F, G, H, I; A, B, C, D, E, F, G, H, I);
+impl_split!(A, B, C, D, E, F, G, H, I, K; A, B, C, D, E, F, G, H, I, K);
+impl_split!(A, B, C, D, E, F, G, H, I, K, L; A, B, C, D, E, F, G, H, I, K, L);
+impl_split!(A, B, C, D, E, F, G, H, I, K, L, M; A, B, C, D, E, F, G, H, I, K, L, M);
+impl_split!(A, B, C, D, E, F, G, H, I, K, L, M, N; A, B, C, D, E, F, G, H, I, K, L, M, N);
+impl_split!(A, B, C, D, E, F, G, H, I, K, L, M, N, O; A, B, C, D, E, F, G, H, I, K, L, M, N, O);
@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::common::{Database, DatabaseRef, Serialized};
+use crate::errors::Result;
+
+const MAGIC: &[u8; 6] = b"HSFAT\x01";
+
+/// Errors of reading a database bundle.
+#[derive(Debug, Error, PartialEq)]
+pub enum BundleError {
+    /// The data is not a database bundle.
+    #[error("The data is not a database bundle.")]
+    BadMagic,
+
+    /// The bundle is truncated or malformed.
+    #[error("The bundle is truncated or malformed.")]
+    Malformed,
+
+    /// The bundle contains an unknown CPU feature tier.
+    #[error("The bundle contains an unknown CPU feature tier {0}.")]
+    UnknownTier(u8),
+
+    /// The name of the CPU feature tier is unknown.
+    #[error("The CPU feature tier `{0}` is unknown.")]
+    UnknownTierName(String),
+
+    /// The bundle has no variant supported by the current host.
+    #[error("The bundle has no variant supported by the current host.")]
+    Unsupported,
+}
+
+/// The CPU feature level a database was compiled for.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tier {
+    /// Without the optional CPU features.
+    Generic = 0,
+    /// Intel(R) Advanced Vector Extensions 2 (Intel(R) AVX2)
+    Avx2 = 1,
+    /// Intel(R) Advanced Vector Extensions 512 (Intel(R) AVX512)
+    Avx512 = 2,
+}
+
+impl Tier {
+    /// All the tiers from the least to the most capable.
+    pub const ALL: [Tier; 3] = [Tier::Generic, Tier::Avx2, Tier::Avx512];
+
+    /// Detect the most capable tier supported by the current host.
+    pub fn host() -> Tier {
+        cfg_if::cfg_if! {
+            if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
+                if is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512bw") {
+                    Tier::Avx512
+                } else if is_x86_feature_detected!("avx2") {
+                    Tier::Avx2
+                } else {
+                    Tier::Generic
+                }
+            } else {
+                Tier::Generic
+            }
+        }
+    }
+
+    /// Returns the name of the tier.
+    pub fn name(self) -> &'static str {
+        match self {
+            Tier::Generic => "generic",
+            Tier::Avx2 => "avx2",
+            Tier::Avx512 => "avx512",
+        }
+    }
+
+    /// Returns the CPU features of the tier.
+    #[cfg(feature = "compile")]
+    pub fn cpu_features(self) -> crate::compile::CpuFeatures {
+        use crate::compile::CpuFeatures;
+
+        match self {
+            Tier::Generic => CpuFeatures::empty(),
+            Tier::Avx2 => CpuFeatures::AVX2,
+            Tier::Avx512 => CpuFeatures::AVX2 | CpuFeatures::AVX512,
+        }
+    }
+}
+
+impl TryFrom<u8> for Tier {
+    type Error = BundleError;
+
+    fn try_from(n: u8) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(Tier::Generic),
+            1 => Ok(Tier::Avx2),
+            2 => Ok(Tier::Avx512),
+            _ => Err(BundleError::UnknownTier(n)),
+        }
+    }
+}
+
+impl FromStr for Tier {
+    type Err = BundleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Tier::ALL
+            .iter()
+            .copied()
+            .find(|tier| tier.name() == s)
+            .ok_or_else(|| BundleError::UnknownTierName(s.to_owned()))
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A fat bundle of the serialized databases compiled from the same patterns for the CPU feature tiers,
+/// so one artifact serves the hosts with different CPUs.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, BlockMode, Bundle, Serialized, Tier};
+/// let bundle = pattern! {"test"}.build_bundle::<BlockMode>(&Tier::ALL).unwrap();
+/// let bytes = bundle.to_bytes();
+///
+/// let bundle = Bundle::from_bytes(&bytes).unwrap();
+/// assert_eq!(bundle.tiers().collect::<Vec<_>>(), Tier::ALL);
+///
+/// let db: BlockDatabase = bundle.get(Tier::Generic).unwrap().deserialize().unwrap();
+///
+/// // pick the most capable variant supported by the host
+/// let (db, tier) = bundle.load::<BlockMode>().unwrap();
+/// assert!(tier <= Tier::host());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bundle {
+    variants: BTreeMap<Tier, Vec<u8>>,
+}
+
+impl Bundle {
+    /// Constructs an empty bundle.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of the variants.
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// Returns `true` if the bundle has no variant.
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    /// Returns the tiers of the variants from the least to the most capable.
+    pub fn tiers(&self) -> impl Iterator<Item = Tier> + '_ {
+        self.variants.keys().copied()
+    }
+
+    /// Returns the serialized database of the tier.
+    pub fn get(&self, tier: Tier) -> Option<&[u8]> {
+        self.variants.get(&tier).map(Vec::as_slice)
+    }
+
+    /// Serialize the database as the variant of the tier, replaces the previous one.
+    pub fn insert<T>(&mut self, tier: Tier, db: &DatabaseRef<T>) -> Result<&mut Self> {
+        let buf = db.serialize()?;
+
+        Ok(self.insert_serialized(tier, buf.to_vec()))
+    }
+
+    /// Add a serialized database as the variant of the tier, replaces the previous one.
+    pub fn insert_serialized(&mut self, tier: Tier, bytes: Vec<u8>) -> &mut Self {
+        self.variants.insert(tier, bytes);
+        self
+    }
+
+    /// Remove the variant of the tier.
+    pub fn remove(&mut self, tier: Tier) -> Option<Vec<u8>> {
+        self.variants.remove(&tier)
+    }
+
+    /// Load the most capable variant supported by the current host, falls back to the less capable ones,
+    /// returns the database with the chosen tier.
+    pub fn load<T>(&self) -> Result<(Database<T>, Tier)> {
+        let host = Tier::host();
+        let mut last_err = None;
+
+        for (&tier, bytes) in self.variants.iter().rev().filter(|(tier, _)| **tier <= host) {
+            match bytes.deserialize() {
+                Ok(db) => return Ok((db, tier)),
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| BundleError::Unsupported.into()))
+    }
+
+    /// Read the serialized databases from a directory, named by the tiers, e.g. `generic.db` or `avx2.db`.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let mut bundle = Bundle::new();
+
+        for tier in &Tier::ALL {
+            let path = dir.as_ref().join(format!("{}.db", tier));
+
+            if path.is_file() {
+                bundle.insert_serialized(*tier, fs::read(path)?);
+            }
+        }
+
+        Ok(bundle)
+    }
+
+    /// Returns the bundle as bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+
+        self.write_to(&mut buf).expect("write to buffer");
+
+        buf
+    }
+
+    /// Write the bundle to the writer.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&(self.variants.len() as u32).to_le_bytes())?;
+
+        for (&tier, bytes) in &self.variants {
+            w.write_all(&[tier as u8])?;
+            w.write_all(&(bytes.len() as u32).to_le_bytes())?;
+            w.write_all(bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the bundle from bytes.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, BundleError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(BundleError::BadMagic);
+        }
+
+        bytes = &bytes[MAGIC.len()..];
+
+        let n = read_u32(&mut bytes).map_err(|_| BundleError::Malformed)?;
+        let mut variants = BTreeMap::new();
+
+        for _ in 0..n {
+            let mut tier = [0];
+            bytes.read_exact(&mut tier).map_err(|_| BundleError::Malformed)?;
+            let tier = Tier::try_from(tier[0])?;
+            let len = read_u32(&mut bytes).map_err(|_| BundleError::Malformed)? as usize;
+
+            if len > bytes.len() {
+                return Err(BundleError::Malformed);
+            }
+
+            let (data, rest) = bytes.split_at(len);
+            bytes = rest;
+            variants.insert(tier, data.to_vec());
+        }
+
+        if bytes.is_empty() {
+            Ok(Bundle { variants })
+        } else {
+            Err(BundleError::Malformed)
+        }
+    }
+}
+
+fn read_u32(r: &mut &[u8]) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_bytes() {
+        let mut bundle = Bundle::new();
+        bundle
+            .insert_serialized(Tier::Avx2, b"avx2".to_vec())
+            .insert_serialized(Tier::Generic, b"generic".to_vec());
+
+        let bytes = bundle.to_bytes();
+
+        assert_eq!(Bundle::from_bytes(&bytes), Ok(bundle));
+        assert_eq!(Bundle::from_bytes(b"HSENV\x01"), Err(BundleError::BadMagic));
+        assert_eq!(
+            Bundle::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BundleError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_tier_name() {
+        assert_eq!("avx2".parse::<Tier>(), Ok(Tier::Avx2));
+        assert_eq!(
+            "sse2".parse::<Tier>(),
+            Err(BundleError::UnknownTierName("sse2".to_owned()))
+        );
+        assert_eq!(
+            Bundle::new().load::<crate::common::Block>().unwrap_err(),
+            crate::Error::Bundle(BundleError::Unsupported)
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::common::{version, version_str};
+use crate::errors::Error;
+
+/// The API surfaces which are only available in the newer Hyperscan versions.
+///
+/// The bindings are generated from the Hyperscan 5.x headers, but the linked library may be older,
+/// the capability is checked against the runtime version before calling into it,
+/// so an API which the linked library doesn't provide is reported as `Error::Unsupported`,
+/// and the caller could fall back to another one.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{runtime_version, Capability};
+/// if Capability::Literal.is_supported() {
+///     assert!(runtime_version().major >= 5);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// The logical combination and quiet flags of the patterns.
+    Combination,
+    /// The pure literal API, e.g. `hs_compile_lit`.
+    Literal,
+    /// The stream state compression API, e.g. `hs_compress_stream`.
+    CompressStream,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Capability::Combination => "logical combination",
+            Capability::Literal => "literal API",
+            Capability::CompressStream => "stream compression",
+        })
+    }
+}
+
+impl Capability {
+    /// Returns the first Hyperscan version providing the capability.
+    pub fn min_version(self) -> semver::Version {
+        match self {
+            Capability::Combination | Capability::CompressStream => semver::Version::new(5, 0, 0),
+            Capability::Literal => semver::Version::new(5, 2, 0),
+        }
+    }
+
+    /// Returns `true` if the linked Hyperscan library provides the capability.
+    pub fn is_supported(self) -> bool {
+        runtime_version() >= self.min_version()
+    }
+
+    /// Returns `Error::Unsupported` if the linked Hyperscan library doesn't provide the capability.
+    pub fn ensure(self) -> Result<(), Error> {
+        let version = runtime_version();
+
+        if version >= self.min_version() {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(self, version))
+        }
+    }
+}
+
+/// The version of the linked Hyperscan library, which may differ from the headers with the dynamic library.
+///
+/// The version string is parsed on the first call and cached.
+pub fn runtime_version() -> semver::Version {
+    static VERSION: OnceLock<semver::Version> = OnceLock::new();
+
+    VERSION
+        .get_or_init(|| {
+            version_str()
+                .to_str()
+                .ok()
+                .and_then(|s| s.split_whitespace().next())
+                .and_then(|s| semver::Version::parse(s).ok())
+                .unwrap_or_else(version)
+        })
+        .clone()
+}
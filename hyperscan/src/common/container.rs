@@ -0,0 +1,296 @@
+use std::fs;
+use std::io::{self, Read};
+use std::mem;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::common::{runtime_version, Database, DatabaseInfo, DatabaseRef, Mode, Serialized, Tier};
+use crate::errors::Error;
+
+const MAGIC: &[u8; 6] = b"HSDBC\x01";
+
+/// Errors of saving or loading a database container.
+#[derive(Debug, Error)]
+pub enum ContainerError {
+    /// The data is not a database container.
+    #[error("The data is not a database container.")]
+    BadMagic,
+
+    /// The container is truncated or malformed.
+    #[error("The container is truncated or malformed.")]
+    Malformed,
+
+    /// The database was built by a different version of Hyperscan.
+    #[error("The database was built by Hyperscan {found}, but the linked one is {expected}.")]
+    VersionMismatch {
+        /// The version of the linked Hyperscan library.
+        expected: semver::Version,
+        /// The version which built the database.
+        found: semver::Version,
+    },
+
+    /// The database was built for a different mode.
+    #[error("The database was built for {found} mode, but {expected} mode is expected.")]
+    ModeMismatch {
+        /// The expected mode.
+        expected: &'static str,
+        /// The mode of the database.
+        found: &'static str,
+    },
+
+    /// The database was built for the CPU features which the current host doesn't support.
+    #[error("The database was built for {found} CPU, but the host only supports {host}.")]
+    PlatformMismatch {
+        /// The most capable tier supported by the current host.
+        host: Tier,
+        /// The tier which the database was built for.
+        found: Tier,
+    },
+
+    /// Reading or writing the container failed.
+    #[error("An I/O error occurred, {0}.")]
+    Io(#[from] io::Error),
+
+    /// The Hyperscan API failed.
+    #[error(transparent)]
+    Hyperscan(#[from] Error),
+}
+
+impl PartialEq for ContainerError {
+    fn eq(&self, other: &Self) -> bool {
+        use ContainerError::*;
+
+        match (self, other) {
+            (VersionMismatch { expected: a, found: b }, VersionMismatch { expected: c, found: d }) => a == c && b == d,
+            (ModeMismatch { expected: a, found: b }, ModeMismatch { expected: c, found: d }) => a == c && b == d,
+            (PlatformMismatch { host: a, found: b }, PlatformMismatch { host: c, found: d }) => a == c && b == d,
+            // the I/O errors are compared by their kinds
+            (Io(lhs), Io(rhs)) => lhs.kind() == rhs.kind(),
+            (Hyperscan(lhs), Hyperscan(rhs)) => lhs == rhs,
+            _ => mem::discriminant(self) == mem::discriminant(other),
+        }
+    }
+}
+
+impl<T: Mode> DatabaseRef<T> {
+    /// Serialize the database into a versioned container,
+    /// which records the crate version and the information of the database, e.g. the version, the mode and the platform.
+    ///
+    /// The container is laid out in little endian as the magic number, the crate version,
+    /// the information string of the database and the serialized database,
+    /// which is verified by the checksum of Hyperscan on the deserialization.
+    pub fn to_container(&self) -> Result<Vec<u8>, ContainerError> {
+        let info = self.database_info()?.to_string();
+        let payload = self.serialize()?;
+        let crate_version = env!("CARGO_PKG_VERSION").as_bytes();
+
+        let mut buf = Vec::with_capacity(MAGIC.len() + crate_version.len() + info.len() + 10 + payload.len());
+
+        buf.extend_from_slice(MAGIC);
+        buf.push(crate_version.len() as u8);
+        buf.extend_from_slice(crate_version);
+        buf.push(info.len() as u8);
+        buf.extend_from_slice(info.as_bytes());
+        buf.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        buf.extend_from_slice(&payload);
+
+        Ok(buf)
+    }
+
+    /// Save the database into a versioned container file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, ContainerError};
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let path = std::env::temp_dir().join("hyperscan-container.db");
+    ///
+    /// db.save(&path).unwrap();
+    ///
+    /// let db = BlockDatabase::load(&path).unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// assert!(db.is_match("foo test bar", &s).unwrap());
+    ///
+    /// assert_eq!(
+    ///     StreamingDatabase::load(&path).unwrap_err(),
+    ///     ContainerError::ModeMismatch { expected: "Streaming", found: "Block" }
+    /// );
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ContainerError> {
+        fs::write(path, self.to_container()?).map_err(Into::into)
+    }
+}
+
+impl<T: Mode> Database<T> {
+    /// Reconstruct a database from a versioned container,
+    /// the version, the mode and the platform are checked before the deserialization.
+    pub fn from_container(mut bytes: &[u8]) -> Result<Self, ContainerError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(ContainerError::BadMagic);
+        }
+
+        bytes = &bytes[MAGIC.len()..];
+
+        let len = read::<1>(&mut bytes)?[0] as usize;
+        let _crate_version = take(&mut bytes, len)?;
+        let len = read::<1>(&mut bytes)?[0] as usize;
+        let info = take(&mut bytes, len)?;
+        let len = u64::from_le_bytes(read(&mut bytes)?) as usize;
+        let payload = take(&mut bytes, len)?;
+
+        if !bytes.is_empty() {
+            return Err(ContainerError::Malformed);
+        }
+
+        let info = std::str::from_utf8(info)
+            .ok()
+            .and_then(|info| info.parse().ok())
+            .ok_or(ContainerError::Malformed)?;
+
+        check::<T>(&info)?;
+
+        payload.deserialize().map_err(Into::into)
+    }
+
+    /// Load a database from a versioned container file saved by `DatabaseRef::save()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ContainerError> {
+        Self::from_container(&fs::read(path)?)
+    }
+
+    /// Reconstruct a database from a serialized database embedded in the binary, e.g. with `include_bytes!`,
+    /// the version, the mode and the platform in the serialized information are checked before the deserialization.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use hyperscan::prelude::*;
+    ///
+    /// static RULES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/rules.db"));
+    ///
+    /// let db = BlockDatabase::from_embedded(RULES).unwrap();
+    /// ```
+    pub fn from_embedded(bytes: &'static [u8]) -> Result<Self, ContainerError> {
+        check::<T>(&bytes.database_info()?)?;
+
+        bytes.deserialize().map_err(Into::into)
+    }
+}
+
+/// Check the database could be deserialized as the mode `T` on the current host with `DatabaseInfo::check`,
+/// and describe the mismatch with the container error.
+fn check<T: Mode>(info: &DatabaseInfo) -> Result<(), ContainerError> {
+    info.check::<T>().map_err(|err| match err {
+        Error::DbVersionError => {
+            let v = runtime_version();
+
+            ContainerError::VersionMismatch {
+                expected: semver::Version::new(v.major, v.minor, v.patch),
+                found: info.version.clone(),
+            }
+        }
+        Error::DbModeError => ContainerError::ModeMismatch {
+            expected: T::NAME,
+            found: info.mode_name().unwrap_or("unknown"),
+        },
+        Error::DbPlatformError => ContainerError::PlatformMismatch {
+            host: Tier::host(),
+            found: info.tier(),
+        },
+        err => err.into(),
+    })
+}
+
+/// Embed a versioned database container into the binary, and reconstruct the database at runtime.
+///
+/// The container is validated before the deserialization, returns `Result<Database<T>, ContainerError>`.
+/// It is the runtime half of the build-time precompilation, see the `precompile` module
+/// for compiling the container in the build script.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use hyperscan::{include_db, BlockMode};
+///
+/// let db = include_db!(BlockMode, concat!(env!("OUT_DIR"), "/rules.db")).unwrap();
+/// ```
+#[macro_export]
+macro_rules! include_db {
+    ($mode:ty, $path:expr) => {
+        $crate::Database::<$mode>::from_container(include_bytes!($path))
+    };
+}
+
+fn read<const N: usize>(r: &mut &[u8]) -> Result<[u8; N], ContainerError> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf).map_err(|_| ContainerError::Malformed)?;
+    Ok(buf)
+}
+
+fn take<'a>(r: &mut &'a [u8], len: usize) -> Result<&'a [u8], ContainerError> {
+    if len > r.len() {
+        return Err(ContainerError::Malformed);
+    }
+
+    let (data, rest) = r.split_at(len);
+    *r = rest;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_from_embedded() {
+        let db: StreamingDatabase = "test".parse().unwrap();
+        let bytes: &'static [u8] = Box::leak(db.serialize().unwrap().to_vec().into_boxed_slice());
+
+        assert!(StreamingDatabase::from_embedded(bytes).is_ok());
+        assert_eq!(
+            BlockDatabase::from_embedded(bytes).unwrap_err(),
+            ContainerError::ModeMismatch {
+                expected: "Block",
+                found: "Streaming"
+            }
+        );
+        assert!(matches!(
+            BlockDatabase::from_embedded(b"not a database"),
+            Err(ContainerError::Hyperscan(_))
+        ));
+    }
+
+    #[test]
+    fn test_container() {
+        let db: BlockDatabase = "test".parse().unwrap();
+        let mut buf = db.to_container().unwrap();
+
+        assert!(BlockDatabase::from_container(&buf).is_ok());
+        assert_eq!(
+            VectoredDatabase::from_container(&buf).unwrap_err(),
+            ContainerError::ModeMismatch {
+                expected: "Vectored",
+                found: "Block"
+            }
+        );
+        assert_eq!(
+            BlockDatabase::from_container(&buf[..buf.len() - 1]).unwrap_err(),
+            ContainerError::Malformed
+        );
+        assert_eq!(
+            BlockDatabase::from_container(b"HSFAT\x01").unwrap_err(),
+            ContainerError::BadMagic
+        );
+
+        *buf.last_mut().unwrap() ^= 0xff;
+
+        assert_eq!(
+            BlockDatabase::from_container(&buf).unwrap_err(),
+            ContainerError::Hyperscan(Error::Invalid)
+        );
+    }
+}
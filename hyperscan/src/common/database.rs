@@ -1,12 +1,11 @@
 use std::ffi::CStr;
 use std::marker::PhantomData;
-use std::mem::MaybeUninit;
+use std::mem::{ManuallyDrop, MaybeUninit};
 
-use anyhow::Result;
 use foreign_types::{foreign_type, ForeignTypeRef};
 
-use crate::common::{Block, Mode, Streaming, Vectored};
-use crate::errors::AsResult;
+use crate::common::{Block, DatabaseInfo, Mode, Serialized, Streaming, Vectored};
+use crate::errors::{AsResult, Result};
 use crate::ffi;
 
 foreign_type! {
@@ -20,7 +19,11 @@ foreign_type! {
 }
 
 unsafe fn drop_database(db: *mut ffi::hs_database_t) {
-    ffi::hs_free_database(db).expect("drop database");
+    #[cfg(all(feature = "prometheus", feature = "runtime"))]
+    crate::metrics::database_freed(db);
+
+    // best effort, the failure is reported by the diagnostics, use `Database::free` to handle it.
+    let _ = ffi::hs_free_database(db).ok();
 }
 
 /// Block scan (non-streaming) database.
@@ -45,7 +48,48 @@ where
     }
 }
 
+impl<T> Database<T> {
+    /// Free a compiled pattern database, returns the error instead of ignoring it on drop.
+    pub fn free(self) -> Result<()> {
+        let db = ManuallyDrop::new(self);
+
+        #[cfg(all(feature = "prometheus", feature = "runtime"))]
+        crate::metrics::database_freed(db.as_ptr());
+
+        unsafe { ffi::hs_free_database(db.as_ptr()).ok() }
+    }
+}
+
+/// The database is cloned by a round trip of the serialization.
+///
+/// # Panics
+///
+/// Panics if the database can't be serialized or deserialized, use `DatabaseRef::try_clone` to handle the error.
+impl<T> Clone for Database<T> {
+    fn clone(&self) -> Self {
+        self.try_clone().expect("clone database")
+    }
+}
+
 impl<T> DatabaseRef<T> {
+    /// Make a copy of the database by a round trip of the serialization,
+    /// e.g. to place a copy of the database on each NUMA node.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let copy = db.try_clone().unwrap();
+    /// let s = copy.alloc_scratch().unwrap();
+    ///
+    /// assert!(!std::ptr::eq(&*db, &*copy));
+    /// assert!(copy.is_match("foo test bar", &s).unwrap());
+    /// ```
+    pub fn try_clone(&self) -> Result<Database<T>> {
+        self.serialize()?.deserialize()
+    }
+
     /// Provides the size of the given database in bytes.
     pub fn size(&self) -> Result<usize> {
         let mut size = MaybeUninit::uninit();
@@ -66,6 +110,11 @@ impl<T> DatabaseRef<T> {
             })
         }
     }
+
+    /// Utility function providing the parsed information about a database.
+    pub fn database_info(&self) -> Result<DatabaseInfo> {
+        self.info()?.parse()
+    }
 }
 
 #[cfg(test)]
@@ -122,4 +171,15 @@ pub mod tests {
 
         validate_database_info(&db_info);
     }
+
+    #[test]
+    fn test_database_clone() {
+        let db: StreamingDatabase = "test".parse().unwrap();
+        let cloned = db.clone();
+
+        assert_ne!(db.as_ptr(), cloned.as_ptr());
+        assert_eq!(db.info().unwrap(), cloned.info().unwrap());
+
+        validate_database(&cloned);
+    }
 }
@@ -0,0 +1,34 @@
+use crate::common::{BlockDatabase, StreamingDatabase};
+
+const DEFAULT_BLOCK_THRESHOLD: usize = 64 * 1024;
+
+/// The block and streaming mode databases compiled from the same patterns.
+///
+/// The small input is scanned with the block mode database, which is faster,
+/// and the large input is scanned with the streaming mode database chunk by chunk,
+/// so the matches across the chunk boundaries are reported without choosing the database mode per call site.
+pub struct DualDatabase {
+    /// The block mode database.
+    pub block: BlockDatabase,
+    /// The streaming mode database.
+    pub streaming: StreamingDatabase,
+    /// The input larger than it will be scanned in streaming mode, 64KB by default.
+    pub block_threshold: usize,
+}
+
+impl DualDatabase {
+    /// Constructs from the block and streaming mode databases compiled from the same patterns.
+    pub fn new(block: BlockDatabase, streaming: StreamingDatabase) -> Self {
+        DualDatabase {
+            block,
+            streaming,
+            block_threshold: DEFAULT_BLOCK_THRESHOLD,
+        }
+    }
+
+    /// Set the size of the input which is scanned in block mode.
+    pub fn with_block_threshold(mut self, threshold: usize) -> Self {
+        self.block_threshold = threshold;
+        self
+    }
+}
@@ -0,0 +1,368 @@
+#[cfg(feature = "compress")]
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+use ed25519_dalek::{Signer as _, Verifier as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::common::{Database, DatabaseRef, Serialized};
+use crate::errors::Result;
+
+const MAGIC: &[u8; 6] = b"HSENV\x01";
+
+const HMAC_SHA256: u8 = 1;
+const ED25519: u8 = 2;
+
+const ZSTD: u8 = 1;
+
+/// Errors of opening a signed envelope.
+#[derive(Debug, Error, PartialEq)]
+pub enum EnvelopeError {
+    /// The data is not a signed envelope.
+    #[error("The data is not a signed envelope.")]
+    BadMagic,
+
+    /// The envelope is truncated or malformed.
+    #[error("The envelope is truncated or malformed.")]
+    Malformed,
+
+    /// The envelope was signed with a different algorithm.
+    #[error("The envelope was signed with a different algorithm.")]
+    AlgorithmMismatch,
+
+    /// The signature does not match the content.
+    #[error("The signature does not match the content.")]
+    BadSignature,
+
+    /// The envelope was sealed with an unsupported option, e.g. compression.
+    #[error("The envelope was sealed with an unsupported option.")]
+    Unsupported,
+
+    /// Signing the envelope failed, e.g. with an invalid key.
+    #[error("Signing the envelope failed.")]
+    Signature,
+}
+
+/// The key to sign an envelope.
+pub enum Signer {
+    /// HMAC-SHA256 with the shared secret key.
+    Hmac(Vec<u8>),
+    /// Ed25519 with the keypair.
+    Ed25519(ed25519_dalek::Keypair),
+}
+
+impl Signer {
+    fn algorithm(&self) -> u8 {
+        match self {
+            Signer::Hmac(_) => HMAC_SHA256,
+            Signer::Ed25519(_) => ED25519,
+        }
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, EnvelopeError> {
+        match self {
+            Signer::Hmac(key) => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|_| EnvelopeError::Signature)?;
+                mac.update(msg);
+                Ok(mac.finalize().into_bytes().to_vec())
+            }
+            Signer::Ed25519(keypair) => Ok(keypair.sign(msg).to_bytes().to_vec()),
+        }
+    }
+}
+
+/// The key to verify an envelope.
+pub enum Verifier {
+    /// HMAC-SHA256 with the shared secret key.
+    Hmac(Vec<u8>),
+    /// Ed25519 with the public key.
+    Ed25519(ed25519_dalek::PublicKey),
+}
+
+impl Verifier {
+    fn algorithm(&self) -> u8 {
+        match self {
+            Verifier::Hmac(_) => HMAC_SHA256,
+            Verifier::Ed25519(_) => ED25519,
+        }
+    }
+
+    fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<(), EnvelopeError> {
+        match self {
+            Verifier::Hmac(key) => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|_| EnvelopeError::BadSignature)?;
+                mac.update(msg);
+                mac.verify_slice(sig).map_err(|_| EnvelopeError::BadSignature)
+            }
+            Verifier::Ed25519(public_key) => {
+                let sig = ed25519_dalek::Signature::try_from(sig).map_err(|_| EnvelopeError::BadSignature)?;
+
+                public_key.verify(msg, &sig).map_err(|_| EnvelopeError::BadSignature)
+            }
+        }
+    }
+}
+
+/// A serialized database with metadata, signed to detect tampering.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::prelude::*;
+/// # use hyperscan::{Envelope, Signer, Verifier};
+/// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+///
+/// let sealed = Envelope::new(&db)
+///     .unwrap()
+///     .with_metadata("version", "1.0")
+///     .seal(&Signer::Hmac(b"secret".to_vec()))
+///     .unwrap();
+///
+/// let envelope = Envelope::open(&sealed, &Verifier::Hmac(b"secret".to_vec())).unwrap();
+/// assert_eq!(envelope.metadata().get("version").map(String::as_str), Some("1.0"));
+///
+/// let db: BlockDatabase = envelope.deserialize().unwrap();
+///
+/// assert!(Envelope::open(&sealed, &Verifier::Hmac(b"wrong".to_vec())).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Envelope {
+    metadata: BTreeMap<String, String>,
+    database: Vec<u8>,
+    #[cfg(feature = "compress")]
+    compression: Option<i32>,
+}
+
+impl Envelope {
+    /// Serialize the database into a new envelope.
+    pub fn new<T>(db: &DatabaseRef<T>) -> Result<Self> {
+        Ok(Self::from_serialized(db.serialize()?.to_vec()))
+    }
+
+    /// Wrap the bytes previously generated by `DatabaseRef::serialize()` into a new envelope.
+    pub fn from_serialized<B: Into<Vec<u8>>>(database: B) -> Self {
+        Envelope {
+            metadata: BTreeMap::new(),
+            database: database.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Compress the serialized database with zstd at the level when the envelope is sealed.
+    ///
+    /// The compressed database is a standard zstd frame, it will be decompressed transparently
+    /// after the signature was verified when the envelope is opened.
+    #[cfg(feature = "compress")]
+    pub fn with_compression(mut self, level: i32) -> Self {
+        self.compression = Some(level);
+        self
+    }
+
+    /// Add a metadata entry to the envelope.
+    pub fn with_metadata<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// The metadata of the envelope.
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// The serialized database in the envelope.
+    pub fn database(&self) -> &[u8] {
+        &self.database
+    }
+
+    /// Reconstruct the pattern database in the envelope.
+    pub fn deserialize<M>(&self) -> Result<Database<M>> {
+        self.database.deserialize()
+    }
+
+    /// Sign the envelope into the sealed bytes.
+    pub fn seal(&self, signer: &Signer) -> Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(MAGIC.len() + self.database.len() + 256);
+
+        self.write_to(&mut buf, signer.algorithm())?;
+
+        let sig = signer.sign(&buf)?;
+
+        write_bytes(&mut buf, &sig)?;
+
+        Ok(buf)
+    }
+
+    /// Verify the sealed bytes and extract the envelope.
+    ///
+    /// The envelope is refused if it was signed with a different algorithm or key, or the content was modified.
+    pub fn open<B: AsRef<[u8]>>(sealed: B, verifier: &Verifier) -> Result<Self> {
+        let buf = sealed.as_ref();
+
+        if buf.len() < MAGIC.len() || &buf[..MAGIC.len()] != MAGIC {
+            return Err(EnvelopeError::BadMagic.into());
+        }
+
+        let mut r = &buf[MAGIC.len()..];
+        let (algorithm, flags, envelope) = Self::read_from(&mut r).map_err(|_| EnvelopeError::Malformed)?;
+        let signed = buf.len() - r.len();
+        let sig = read_bytes(&mut r).map_err(|_| EnvelopeError::Malformed)?;
+
+        if !r.is_empty() {
+            return Err(EnvelopeError::Malformed.into());
+        }
+        if algorithm != verifier.algorithm() {
+            return Err(EnvelopeError::AlgorithmMismatch.into());
+        }
+
+        verifier.verify(&buf[..signed], &sig)?;
+
+        match flags {
+            0 => Ok(envelope),
+            ZSTD => envelope.decompress(),
+            _ => Err(EnvelopeError::Unsupported.into()),
+        }
+    }
+
+    #[cfg(feature = "compress")]
+    fn decompress(self) -> Result<Self> {
+        Ok(Envelope {
+            database: zstd::stream::decode_all(self.database.as_slice())?,
+            ..self
+        })
+    }
+
+    #[cfg(not(feature = "compress"))]
+    fn decompress(self) -> Result<Self> {
+        Err(EnvelopeError::Unsupported.into())
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W, algorithm: u8) -> io::Result<()> {
+        #[cfg(feature = "compress")]
+        let (flags, database) = match self.compression {
+            Some(level) => (
+                ZSTD,
+                Cow::Owned(zstd::stream::encode_all(self.database.as_slice(), level)?),
+            ),
+            None => (0, Cow::Borrowed(&self.database[..])),
+        };
+        #[cfg(not(feature = "compress"))]
+        let (flags, database) = (0, &self.database[..]);
+
+        w.write_all(MAGIC)?;
+        w.write_all(&[algorithm, flags])?;
+        w.write_all(&(self.metadata.len() as u32).to_le_bytes())?;
+
+        for (key, value) in &self.metadata {
+            write_bytes(w, key.as_bytes())?;
+            write_bytes(w, value.as_bytes())?;
+        }
+
+        write_bytes(w, &database)
+    }
+
+    fn read_from(r: &mut &[u8]) -> io::Result<(u8, u8, Self)> {
+        let mut header = [0; 2];
+        r.read_exact(&mut header)?;
+
+        let n = read_u32(r)?;
+        let mut metadata = BTreeMap::new();
+
+        for _ in 0..n {
+            let key = read_string(r)?;
+            let value = read_string(r)?;
+
+            metadata.insert(key, value);
+        }
+
+        let database = read_bytes(r)?;
+
+        Ok((
+            header[0],
+            header[1],
+            Envelope {
+                metadata,
+                database,
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+fn write_bytes<W: Write>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_le_bytes())?;
+    w.write_all(data)
+}
+
+fn read_u32(r: &mut &[u8]) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_bytes(r: &mut &[u8]) -> io::Result<Vec<u8>> {
+    let len = read_u32(r)? as usize;
+
+    if len > r.len() {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let (data, rest) = r.split_at(len);
+    *r = rest;
+    Ok(data.to_vec())
+}
+
+fn read_string(r: &mut &[u8]) -> io::Result<String> {
+    String::from_utf8(read_bytes(r)?).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_tampered_envelope() {
+        let db: BlockDatabase = "test".parse().unwrap();
+        let key = b"secret".to_vec();
+
+        let mut sealed = Envelope::new(&db)
+            .unwrap()
+            .with_metadata("name", "test")
+            .seal(&Signer::Hmac(key.clone()))
+            .unwrap();
+
+        let envelope = Envelope::open(&sealed, &Verifier::Hmac(key.clone())).unwrap();
+        assert_eq!(envelope.metadata()["name"], "test");
+        assert_eq!(envelope.database(), &db.serialize().unwrap()[..]);
+
+        // the last byte of the serialized database, before the 32 bytes signature and its length
+        let n = sealed.len() - 37;
+        sealed[n] ^= 0xFF;
+
+        let err = Envelope::open(&sealed, &Verifier::Hmac(key)).unwrap_err();
+        assert_eq!(err, crate::Error::Envelope(EnvelopeError::BadSignature));
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_compressed_envelope() {
+        let db: BlockDatabase = "test".parse().unwrap();
+        let key = b"secret".to_vec();
+        let envelope = Envelope::new(&db).unwrap();
+
+        let sealed = envelope.clone().seal(&Signer::Hmac(key.clone())).unwrap();
+        let compressed = envelope.with_compression(3).seal(&Signer::Hmac(key.clone())).unwrap();
+
+        assert!(compressed.len() < sealed.len());
+
+        let envelope = Envelope::open(&compressed, &Verifier::Hmac(key)).unwrap();
+        assert_eq!(envelope.database(), &db.serialize().unwrap()[..]);
+
+        let _: BlockDatabase = envelope.deserialize().unwrap();
+    }
+}
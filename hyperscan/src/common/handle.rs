@@ -0,0 +1,86 @@
+use std::sync::{Arc, RwLock};
+
+use crate::common::Database;
+use crate::errors::Result;
+
+/// A shared handle of the database, which can be replaced at runtime when the patterns are reloaded.
+///
+/// The scanners keep using the database they loaded until they load it again,
+/// and the generation tells them whether the scratch space should be reallocated for the new database.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, DatabaseHandle};
+/// let db: BlockDatabase = pattern! {"foo"}.build().unwrap();
+/// let handle = DatabaseHandle::new(db);
+/// let db = handle.load();
+/// let mut s = db.alloc_scratch().unwrap();
+/// let generation = handle.generation();
+///
+/// handle.store(pattern! {"bar"}.build().unwrap());
+/// assert_ne!(handle.generation(), generation);
+///
+/// let db = handle.load();
+/// db.realloc_scratch(&mut s).unwrap();
+///
+/// let mut matches = vec![];
+/// db.scan("foo bar", &s, |_, _, to, _| {
+///     matches.push(to);
+///     Matching::Continue
+/// }).unwrap();
+///
+/// assert_eq!(matches, vec![7]);
+/// ```
+#[derive(Debug)]
+pub struct DatabaseHandle<T> {
+    /// The current database with its generation, they are always read and replaced together.
+    current: RwLock<(Arc<Database<T>>, usize)>,
+}
+
+impl<T> DatabaseHandle<T> {
+    /// Constructs a handle of the database.
+    pub fn new(db: Database<T>) -> Self {
+        DatabaseHandle {
+            current: RwLock::new((Arc::new(db), 0)),
+        }
+    }
+
+    /// Returns the current database.
+    pub fn load(&self) -> Arc<Database<T>> {
+        self.current.read().unwrap().0.clone()
+    }
+
+    /// Returns the current database with its generation.
+    pub fn snapshot(&self) -> (Arc<Database<T>>, usize) {
+        let cur = self.current.read().unwrap();
+
+        (cur.0.clone(), cur.1)
+    }
+
+    /// Replace the current database, returns the previous one.
+    pub fn store(&self, db: Database<T>) -> Arc<Database<T>> {
+        let mut cur = self.current.write().unwrap();
+        let generation = cur.1 + 1;
+
+        std::mem::replace(&mut *cur, (Arc::new(db), generation)).0
+    }
+
+    /// Returns the generation of the current database, which is increased when the database is replaced.
+    pub fn generation(&self) -> usize {
+        self.current.read().unwrap().1
+    }
+
+    /// Returns the current database, and re-grow the scratch space if it was replaced since the generation.
+    #[cfg(feature = "runtime")]
+    pub fn refresh(&self, generation: &mut usize, scratch: &mut crate::runtime::Scratch) -> Result<Arc<Database<T>>> {
+        let (db, cur) = self.snapshot();
+
+        if cur != *generation {
+            db.realloc_scratch(scratch)?;
+            *generation = cur;
+        }
+
+        Ok(db)
+    }
+}
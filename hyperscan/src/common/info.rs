@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::common::{runtime_version, Block, Mode, Streaming, Tier, Vectored};
+use crate::errors::{Error, Result};
+
+/// The information of a database or a serialized database,
+/// parsed from the string like `Version: 5.4.0 Features: AVX2 Mode: BLOCK`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, BlockMode, SerializedDatabase, StreamingMode};
+/// let db: StreamingDatabase = pattern! {"test"}.build().unwrap();
+/// let buf = db.serialize().unwrap();
+/// let info = buf.database_info().unwrap();
+///
+/// assert_eq!(info.mode, "STREAM");
+/// assert!(info.is_mode::<StreamingMode>());
+/// assert!(info.check::<StreamingMode>().is_ok());
+/// assert_eq!(info.check::<BlockMode>(), Err(hyperscan::Error::DbModeError));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatabaseInfo {
+    /// The version of Hyperscan which built the database.
+    pub version: semver::Version,
+    /// The CPU features which the database was built for, e.g. `AVX2`, `None` for the generic platform.
+    pub features: Option<String>,
+    /// The mode of the database, e.g. `BLOCK`, `STREAM` or `VECTORED`.
+    pub mode: String,
+}
+
+impl DatabaseInfo {
+    /// Returns `true` if the database was built for the mode.
+    pub fn is_mode<M: Mode>(&self) -> bool {
+        let mode = if M::ID == Block::ID {
+            "BLOCK"
+        } else if M::ID == Streaming::ID {
+            "STREAM"
+        } else if M::ID == Vectored::ID {
+            "VECTORED"
+        } else {
+            return false;
+        };
+
+        self.mode == mode
+    }
+
+    /// Returns the CPU feature tier which the database was built for.
+    pub fn tier(&self) -> Tier {
+        match self.features.as_deref() {
+            Some(features) if features.starts_with("AVX512") => Tier::Avx512,
+            Some("AVX2") => Tier::Avx2,
+            _ => Tier::Generic,
+        }
+    }
+
+    /// Returns the name of the mode which the database was built for, e.g. `Block`.
+    pub fn mode_name(&self) -> Option<&'static str> {
+        match self.mode.as_str() {
+            "BLOCK" => Some(Block::NAME),
+            "STREAM" => Some(Streaming::NAME),
+            "VECTORED" => Some(Vectored::NAME),
+            _ => None,
+        }
+    }
+
+    /// Check the database could be deserialized as the mode by the linked Hyperscan library on the current host,
+    /// returns `Error::DbVersionError`, `Error::DbModeError` or `Error::DbPlatformError` otherwise.
+    pub fn check<M: Mode>(&self) -> Result<()> {
+        let v = runtime_version();
+
+        if (self.version.major, self.version.minor, self.version.patch) != (v.major, v.minor, v.patch) {
+            Err(Error::DbVersionError)
+        } else if !self.is_mode::<M>() {
+            Err(Error::DbModeError)
+        } else if self.tier() > Tier::host() {
+            Err(Error::DbPlatformError)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for DatabaseInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Version: {} Features: {} Mode: {}",
+            self.version,
+            self.features.as_deref().unwrap_or_default(),
+            self.mode
+        )
+    }
+}
+
+impl FromStr for DatabaseInfo {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.strip_prefix("Version:").ok_or(Error::Invalid)?;
+        let (version, s) = s.split_once("Features:").ok_or(Error::Invalid)?;
+        let (features, mode) = s.split_once("Mode:").ok_or(Error::Invalid)?;
+
+        let version = semver::Version::parse(version.trim()).map_err(|_| Error::Invalid)?;
+        let features = Some(features.trim()).filter(|s| !s.is_empty()).map(String::from);
+        let mode = mode.trim().to_owned();
+
+        Ok(DatabaseInfo {
+            version,
+            features,
+            mode,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_database_info() {
+        let info: DatabaseInfo = "Version: 5.4.0 Features: AVX2 Mode: BLOCK".parse().unwrap();
+
+        assert_eq!(info.version, semver::Version::new(5, 4, 0));
+        assert_eq!(info.features.as_deref(), Some("AVX2"));
+        assert_eq!(info.mode, "BLOCK");
+        assert!(info.is_mode::<Block>());
+        assert!(!info.is_mode::<Streaming>());
+        assert_eq!(info.mode_name(), Some("Block"));
+        assert_eq!(info.tier(), Tier::Avx2);
+        assert_eq!(info.to_string(), "Version: 5.4.0 Features: AVX2 Mode: BLOCK");
+
+        let info: DatabaseInfo = "Version: 5.4.0 Features:  Mode: VECTORED".parse().unwrap();
+
+        assert_eq!(info.features, None);
+        assert!(info.is_mode::<Vectored>());
+        assert_eq!(info.tier(), Tier::Generic);
+
+        assert_eq!("Mode: BLOCK".parse::<DatabaseInfo>(), Err(Error::Invalid));
+    }
+}
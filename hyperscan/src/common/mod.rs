@@ -1,10 +1,30 @@
+mod bundle;
+mod capability;
+mod container;
 mod database;
+mod dual;
+#[cfg(feature = "signed")]
+mod envelope;
+mod handle;
+mod info;
 mod mode;
 mod serialized;
+#[cfg(unix)]
+mod shared;
 
+pub use self::bundle::{Bundle, BundleError, Tier};
+pub use self::capability::{runtime_version, Capability};
+pub use self::container::ContainerError;
 pub use self::database::{BlockDatabase, Database, DatabaseRef, StreamingDatabase, VectoredDatabase};
+pub use self::dual::DualDatabase;
+#[cfg(feature = "signed")]
+pub use self::envelope::{Envelope, EnvelopeError, Signer, Verifier};
+pub use self::handle::DatabaseHandle;
+pub use self::info::DatabaseInfo;
 pub use self::mode::{Block, Mode, Streaming, Vectored};
 pub use self::serialized::Serialized;
+#[cfg(unix)]
+pub use self::shared::{MappedDatabase, SharedDatabase};
 
 #[cfg(test)]
 pub mod tests {
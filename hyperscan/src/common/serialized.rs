@@ -1,16 +1,36 @@
 use std::ffi::CStr;
 use std::fmt;
+use std::io::Read;
 use std::mem::MaybeUninit;
 
-use anyhow::{Error, Result};
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::c_char;
 use malloc_buf::Malloc;
 
-use crate::common::{Database, DatabaseRef};
-use crate::errors::AsResult;
+use crate::common::{runtime_version, Database, DatabaseInfo, DatabaseRef};
+use crate::diagnostics;
+use crate::errors::{AsResult, Error, Result};
 use crate::ffi;
 
+#[cfg(feature = "async")]
+use futures::io::{AsyncRead, AsyncReadExt};
+
+/// The magic number of a serialized database.
+const DB_MAGIC: u32 = 0xdbdb_dbdb;
+
+/// The alignment of a deserialized database.
+const DB_ALIGN: usize = 8;
+
+/// The size of the leading fields of a serialized database, the magic number, the version and the bytecode length.
+const HEADER_SIZE: usize = 12;
+
+/// The size of the whole header of a serialized database, the `struct hs_database` of Hyperscan,
+/// which is followed by the bytecode.
+const DB_HEADER_SIZE: usize = 104;
+
+/// The maximum size of a serialized database read from a reader.
+const MAX_SERIALIZED_SIZE: usize = 1 << 30;
+
 /// A serialized database
 pub trait Serialized {
     /// The type of error if it fails in a normal fashion.
@@ -22,6 +42,15 @@ pub trait Serialized {
     /// Providing information about a serialized database.
     fn info(&self) -> Result<String, Self::Error>;
 
+    /// Providing the parsed information about a serialized database,
+    /// which could be checked before attempting the deserialization.
+    fn database_info(&self) -> Result<DatabaseInfo, Self::Error>
+    where
+        Self::Error: From<Error>,
+    {
+        Ok(self.info()?.parse()?)
+    }
+
     /// Reconstruct a pattern database from a stream of bytes previously generated by `Database::serialize()`.
     fn deserialize<M>(&self) -> Result<Database<M>, Self::Error>;
 }
@@ -59,7 +88,7 @@ impl<T: AsRef<[u8]>> Serialized for T {
 
         unsafe {
             ffi::hs_deserialize_database(buf.as_ptr() as *const c_char, buf.len(), db.as_mut_ptr())
-                .map(|_| Database::from_ptr(db.assume_init()))
+                .map(|_| diagnostics::database(Database::from_ptr(db.assume_init())))
         }
     }
 }
@@ -106,11 +135,156 @@ impl<T> DatabaseRef<T> {
 
         unsafe { ffi::hs_deserialize_database_at(bytes.as_ptr() as *const c_char, bytes.len(), self.as_ptr()).ok() }
     }
+
+    /// Reconstruct a pattern database from a stream of bytes into a memory region owned by the caller,
+    /// e.g. an arena or a shared memory segment, instead of letting Hyperscan allocate it.
+    ///
+    /// The region must be aligned to 8 bytes and large enough for the deserialized database,
+    /// as reported by `Serialized::size()`, otherwise `Error::BadAlign` or `Error::InsufficientSpace` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, SerializedDatabase};
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let buf = db.serialize().unwrap();
+    ///
+    /// let mut region = vec![0u64; (buf.size().unwrap() + 7) / 8];
+    /// let region = unsafe { std::slice::from_raw_parts_mut(region.as_mut_ptr() as *mut u8, region.len() * 8) };
+    ///
+    /// let db = DatabaseRef::<BlockMode>::deserialize_into(&buf, region).unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    ///
+    /// assert!(db.is_match("foo test bar", &s).unwrap());
+    /// ```
+    pub fn deserialize_into<'a, B: AsRef<[u8]>>(bytes: B, region: &'a mut [u8]) -> Result<&'a mut Self> {
+        let bytes = bytes.as_ref();
+
+        if region.as_ptr() as usize % DB_ALIGN != 0 {
+            return Err(Error::BadAlign);
+        }
+        if region.len() < bytes.size()? {
+            return Err(Error::InsufficientSpace);
+        }
+
+        unsafe {
+            let db = Self::from_ptr_mut(region.as_mut_ptr() as *mut _);
+
+            db.deserialize_at(bytes)?;
+
+            Ok(db)
+        }
+    }
+}
+
+impl<T> Database<T> {
+    /// Reconstruct a pattern database from a stream of bytes previously generated by `DatabaseRef::serialize()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let buf = db.serialize().unwrap();
+    ///
+    /// let db = BlockDatabase::deserialize(&buf).unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    ///
+    /// assert!(db.is_match("foo test bar", &s).unwrap());
+    /// ```
+    pub fn deserialize<B: AsRef<[u8]>>(bytes: B) -> Result<Self> {
+        bytes.as_ref().deserialize()
+    }
+
+    /// Reconstruct a pattern database from a reader, e.g. a network fetch or an object store download.
+    ///
+    /// The header is validated before reading the bytecode, so an incompatible or oversized database fails fast
+    /// with `Error::Invalid` or `Error::DbVersionError`, and a truncated one fails with `Error::Invalid`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, Error};
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let buf = db.serialize().unwrap();
+    ///
+    /// let db = BlockDatabase::deserialize_from(&mut &buf[..]).unwrap();
+    /// assert!(db.size().unwrap() > 0);
+    ///
+    /// let err = BlockDatabase::deserialize_from(&mut &b"not a database"[..]).unwrap_err();
+    /// assert_eq!(err, Error::Invalid);
+    /// ```
+    pub fn deserialize_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut header = [0; HEADER_SIZE];
+
+        reader.read_exact(&mut header)?;
+
+        let len = check_header(&header)?;
+        let mut buf = Vec::with_capacity(HEADER_SIZE + len);
+
+        buf.extend_from_slice(&header);
+
+        if reader.take(len as u64).read_to_end(&mut buf)? != len {
+            return Err(Error::Invalid);
+        }
+
+        buf.deserialize()
+    }
+
+    /// Reconstruct a pattern database from an async reader, the header is validated before reading the bytecode.
+    #[cfg(feature = "async")]
+    pub async fn async_deserialize_from<R>(reader: &mut R) -> Result<Self>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut header = [0; HEADER_SIZE];
+
+        reader.read_exact(&mut header).await?;
+
+        let len = check_header(&header)?;
+        let mut buf = Vec::with_capacity(HEADER_SIZE + len);
+
+        buf.extend_from_slice(&header);
+
+        if reader.take(len as u64).read_to_end(&mut buf).await? != len {
+            return Err(Error::Invalid);
+        }
+
+        buf.deserialize()
+    }
+}
+
+/// Check the magic number, the version and the length of a serialized database,
+/// returns the length of the rest of the serialized database following the leading fields.
+fn check_header(header: &[u8; HEADER_SIZE]) -> Result<usize> {
+    let field = |i: usize| u32::from_ne_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+
+    if field(0) != DB_MAGIC {
+        return Err(Error::Invalid);
+    }
+
+    let v = runtime_version();
+    let version = ((v.major as u32) << 24) | ((v.minor as u32) << 16) | ((v.patch as u32) << 8);
+
+    if field(4) != version {
+        return Err(Error::DbVersionError);
+    }
+
+    let len = (field(8) as usize).saturating_add(DB_HEADER_SIZE - HEADER_SIZE);
+
+    if len > MAX_SERIALIZED_SIZE - HEADER_SIZE {
+        return Err(Error::Invalid);
+    }
+
+    Ok(len)
 }
 
 #[cfg(test)]
 pub mod tests {
+    use std::convert::TryFrom;
+
     use crate::common::database::tests::*;
+    use crate::common::Block;
     use crate::prelude::*;
 
     use super::*;
@@ -145,6 +319,48 @@ pub mod tests {
         validate_database(&db);
     }
 
+    #[test]
+    fn test_database_deserialize_from() {
+        let db: StreamingDatabase = "test".parse().unwrap();
+
+        let data = db.serialize().unwrap();
+        let db = StreamingDatabase::deserialize_from(&mut &data[..]).unwrap();
+
+        validate_database(&db);
+
+        let mut truncated = &data[..data.len() / 2];
+
+        assert!(StreamingDatabase::deserialize_from(&mut truncated).is_err());
+
+        let mut header = data[..HEADER_SIZE].to_vec();
+
+        header[4] ^= 0xff;
+
+        assert_eq!(
+            StreamingDatabase::deserialize_from(&mut &header[..]).unwrap_err(),
+            Error::DbVersionError
+        );
+
+        let mut header = data[..HEADER_SIZE].to_vec();
+
+        header[8..].copy_from_slice(&u32::MAX.to_ne_bytes());
+
+        assert_eq!(
+            StreamingDatabase::deserialize_from(&mut &header[..]).unwrap_err(),
+            Error::Invalid
+        );
+    }
+
+    #[test]
+    fn test_serialized_header() {
+        let db: BlockDatabase = "test".parse().unwrap();
+
+        let data = db.serialize().unwrap();
+        let header = <[u8; HEADER_SIZE]>::try_from(&data[..HEADER_SIZE]).unwrap();
+
+        assert_eq!(HEADER_SIZE + check_header(&header).unwrap(), data.len());
+    }
+
     #[test]
     fn test_database_deserialize_at() {
         let mut db: BlockDatabase = "test".parse().unwrap();
@@ -157,4 +373,27 @@ pub mod tests {
 
         validate_database(&db);
     }
+
+    #[test]
+    fn test_database_deserialize_into() {
+        let db: BlockDatabase = "test".parse().unwrap();
+
+        let data = db.serialize().unwrap();
+        let size = data.size().unwrap();
+        let mut region = vec![0u64; size / DB_ALIGN + 2];
+        let region = unsafe { std::slice::from_raw_parts_mut(region.as_mut_ptr() as *mut u8, region.len() * DB_ALIGN) };
+
+        assert_eq!(
+            DatabaseRef::<Block>::deserialize_into(&data, &mut region[1..]).unwrap_err(),
+            Error::BadAlign
+        );
+        assert_eq!(
+            DatabaseRef::<Block>::deserialize_into(&data, &mut region[..size - 1]).unwrap_err(),
+            Error::InsufficientSpace
+        );
+
+        let db = DatabaseRef::<Block>::deserialize_into(&data, region).unwrap();
+
+        validate_database(db);
+    }
 }
@@ -0,0 +1,242 @@
+use std::fs::File;
+use std::io;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ops::Deref;
+use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+use std::path::Path;
+
+use foreign_types::ForeignTypeRef;
+use libc::c_char;
+use memmap2::{Mmap, MmapMut, MmapOptions};
+
+use crate::common::{DatabaseRef, Mode, Serialized};
+use crate::errors::{AsResult, Error, Result};
+use crate::ffi;
+
+/// A pattern database placed in the shared memory, which can be mapped read-only by the worker processes.
+///
+/// The shared memory is backed by an anonymous `memfd` on Linux, or an unlinked POSIX shared memory object.
+/// The file descriptor is inherited by the forked worker processes, or could be passed with `SCM_RIGHTS`,
+/// each worker maps the database with `SharedDatabase::map()` and allocates its own scratch space.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, SharedDatabase};
+/// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+/// let shared = SharedDatabase::new(&db).unwrap();
+///
+/// // in the worker process
+/// let db = shared.map().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut matches = vec![];
+///
+/// db.scan("some test data", &s, |_, from, to, _| {
+///     matches.push(from..to);
+///     Matching::Continue
+/// }).unwrap();
+///
+/// assert_eq!(matches, vec![0..9]);
+/// ```
+#[derive(Debug)]
+pub struct SharedDatabase<T> {
+    fd: RawFd,
+    len: usize,
+    _mode: PhantomData<T>,
+}
+
+impl<T> Drop for SharedDatabase<T> {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+impl<T> AsRawFd for SharedDatabase<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl<T> IntoRawFd for SharedDatabase<T> {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+        std::mem::forget(self);
+        fd
+    }
+}
+
+impl<T> SharedDatabase<T> {
+    /// Place a copy of the database in a new shared memory.
+    pub fn new(db: &DatabaseRef<T>) -> Result<Self> {
+        let buf = db.serialize()?;
+        let mut len = MaybeUninit::uninit();
+
+        unsafe {
+            ffi::hs_serialized_database_size(buf.as_ptr() as *const c_char, buf.len(), len.as_mut_ptr()).ok()?;
+        }
+
+        let shared = SharedDatabase {
+            fd: create_shm()?,
+            len: unsafe { len.assume_init() },
+            _mode: PhantomData,
+        };
+
+        if unsafe { libc::ftruncate(shared.fd, shared.len as libc::off_t) } != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        let mut mem = unsafe { MmapOptions::new().len(shared.len).map_mut(shared.fd)? };
+
+        unsafe {
+            ffi::hs_deserialize_database_at(buf.as_ptr() as *const c_char, buf.len(), mem.as_mut_ptr() as *mut _)
+                .ok()?;
+        }
+
+        Ok(shared)
+    }
+
+    /// Constructs from the file descriptor of a shared memory created by `SharedDatabase::new()`.
+    ///
+    /// # Safety
+    ///
+    /// The file descriptor must be owned by the caller,
+    /// and refer to a shared database which was compiled for the mode `T`.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        let mut stat = MaybeUninit::<libc::stat>::uninit();
+
+        if libc::fstat(fd, stat.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        Ok(SharedDatabase {
+            fd,
+            len: stat.assume_init().st_size as usize,
+            _mode: PhantomData,
+        })
+    }
+
+    /// Returns the size of the database in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the shared memory is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Map the database read-only into the current process.
+    pub fn map(&self) -> Result<MappedDatabase<T>> {
+        Ok(MappedDatabase {
+            map: unsafe { MmapOptions::new().len(self.len).map(self.fd)? },
+            _mode: PhantomData,
+        })
+    }
+}
+
+/// A read-only mapping of the `SharedDatabase` or a serialized database file, which is unmapped when dropped.
+#[derive(Debug)]
+pub struct MappedDatabase<T> {
+    map: Mmap,
+    _mode: PhantomData<T>,
+}
+
+impl<T: Mode> MappedDatabase<T> {
+    /// Load a serialized database file without copying it to the heap.
+    ///
+    /// The file is mapped read-only and checked before the database is deserialized
+    /// into a page aligned anonymous mapping, the file mapping is released once it is loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, MappedDatabase};
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let path = std::env::temp_dir().join("hyperscan-mapped.db");
+    ///
+    /// std::fs::write(&path, db.serialize().unwrap()).unwrap();
+    ///
+    /// let db = MappedDatabase::<BlockMode>::open(&path).unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    ///
+    /// assert!(db.is_match("foo test bar", &s).unwrap());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+
+        if len == 0 {
+            return Err(Error::Invalid);
+        }
+
+        let src = unsafe { Mmap::map(&file)? };
+
+        src.database_info()?.check::<T>()?;
+
+        let size = src.size()?;
+        let mut dst = MmapMut::map_anon(size)?;
+
+        unsafe {
+            ffi::hs_deserialize_database_at(src.as_ptr() as *const c_char, src.len(), dst.as_mut_ptr() as *mut _)
+                .ok()?;
+        }
+
+        Ok(MappedDatabase {
+            map: dst.make_read_only()?,
+            _mode: PhantomData,
+        })
+    }
+}
+
+impl<T> Deref for MappedDatabase<T> {
+    type Target = DatabaseRef<T>;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { DatabaseRef::from_ptr(self.map.as_ptr() as *mut ffi::hs_database_t) }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn create_shm() -> io::Result<RawFd> {
+    let fd = unsafe { libc::memfd_create(b"hyperscan\0".as_ptr() as *const c_char, libc::MFD_CLOEXEC) };
+
+    if fd < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(fd)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn create_shm() -> io::Result<RawFd> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = format!(
+        "/hyperscan-{}-{}\0",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let fd = unsafe {
+        libc::shm_open(
+            name.as_ptr() as *const c_char,
+            libc::O_RDWR | libc::O_CREAT | libc::O_EXCL,
+            0o600,
+        )
+    };
+
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    unsafe {
+        libc::shm_unlink(name.as_ptr() as *const c_char);
+    }
+
+    Ok(fd)
+}
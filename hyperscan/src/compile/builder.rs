@@ -7,8 +7,10 @@ use anyhow::Error;
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::c_char;
 
-use crate::common::{Database, Mode};
-use crate::compile::{AsCompileResult, Flags, Pattern, Patterns, PlatformRef};
+use crate::common::{Bundle, Capability, Database, DualDatabase, Mode, Tier};
+use crate::compile::{AsCompileResult, Flags, Pattern, Patterns, Platform, PlatformRef, Tune};
+use crate::diagnostics;
+use crate::errors::Error as HsError;
 use crate::ffi;
 
 #[cfg(feature = "literal")]
@@ -26,6 +28,43 @@ pub trait Builder {
 
     /// Build an expression is compiled into a Hyperscan database for a target platform.
     fn for_platform<T: Mode>(&self, platform: Option<&PlatformRef>) -> Result<Database<T>, Self::Err>;
+
+    /// Build an expression is compiled into both of the block and streaming mode databases.
+    fn build_dual(&self) -> Result<DualDatabase, Self::Err> {
+        Ok(DualDatabase::new(self.build()?, self.build()?))
+    }
+
+    /// Build an expression is compiled into a bundle of databases for the CPU feature tiers.
+    fn build_bundle<T: Mode>(&self, tiers: &[Tier]) -> Result<Bundle, Self::Err>
+    where
+        Self::Err: From<Error>,
+    {
+        let mut bundle = Bundle::new();
+
+        for &tier in tiers {
+            let platform = Platform::new(Tune::Generic, tier.cpu_features());
+            let db: Database<T> = self.for_platform(Some(&platform))?;
+
+            bundle.insert(tier, &db)?;
+        }
+
+        Ok(bundle)
+    }
+}
+
+/// Check the flags of the pattern are supported by the linked Hyperscan library.
+#[cfg(feature = "v5")]
+fn ensure_flags(flags: Flags) -> Result<(), Error> {
+    if flags.intersects(Flags::COMBINATION | Flags::QUIET) {
+        Capability::Combination.ensure()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "v5"))]
+fn ensure_flags(_flags: Flags) -> Result<(), Error> {
+    Ok(())
 }
 
 /// Compile an expression into a Hyperscan database.
@@ -87,6 +126,8 @@ impl Builder for Pattern {
     /// into a Hyperscan database which can be passed to the runtime functions
     ///
     fn for_platform<T: Mode>(&self, platform: Option<&PlatformRef>) -> Result<Database<T>, Self::Err> {
+        ensure_flags(self.flags)?;
+
         let expr = CString::new(self.expression.as_bytes())?;
         let mode = T::ID | if T::is_streaming() { self.som() } else { None }.map_or(0, |som| som as _);
         let mut db = MaybeUninit::uninit();
@@ -102,7 +143,7 @@ impl Builder for Pattern {
                 err.as_mut_ptr(),
             )
             .ok_or_else(|| err.assume_init())
-            .map(|_| Database::from_ptr(db.assume_init()))
+            .map(|_| diagnostics::database(Database::from_ptr(db.assume_init())))
         }
     }
 }
@@ -118,7 +159,17 @@ impl Builder for Patterns {
     /// Each expression can be labelled with a unique integer
     // which is passed into the match callback to identify the pattern that has matched.
     ///
+    /// An empty pattern set fails with the `EmptyPatternSet` error.
+    ///
     fn for_platform<T: Mode>(&self, platform: Option<&PlatformRef>) -> Result<Database<T>, Self::Err> {
+        if self.is_empty() {
+            return Err(HsError::EmptyPatternSet.into());
+        }
+
+        for Pattern { flags, .. } in self.iter() {
+            ensure_flags(*flags)?;
+        }
+
         let expressions = self
             .iter()
             .map(|Pattern { expression, .. }| CString::new(expression.as_str()))
@@ -152,7 +203,7 @@ impl Builder for Patterns {
                 err.as_mut_ptr(),
             )
             .ok_or_else(|| err.assume_init())
-            .map(|_| Database::from_ptr(db.assume_init()))
+            .map(|_| diagnostics::database(Database::from_ptr(db.assume_init())))
         }
     }
 }
@@ -168,6 +219,8 @@ impl Builder for Literal {
     /// into a Hyperscan database which can be passed to the runtime functions
     ///
     fn for_platform<T: Mode>(&self, platform: Option<&PlatformRef>) -> Result<Database<T>, Self::Err> {
+        Capability::Literal.ensure()?;
+
         let mode = T::ID | if T::is_streaming() { self.som() } else { None }.map_or(0, |som| som as _);
         let mut db = MaybeUninit::uninit();
         let mut err = MaybeUninit::uninit();
@@ -183,7 +236,7 @@ impl Builder for Literal {
                 err.as_mut_ptr(),
             )
             .ok_or_else(|| err.assume_init())
-            .map(|_| Database::from_ptr(db.assume_init()))
+            .map(|_| diagnostics::database(Database::from_ptr(db.assume_init())))
         }
     }
 }
@@ -200,7 +253,15 @@ impl Builder for Literals {
     /// Each expression can be labelled with a unique integer
     // which is passed into the match callback to identify the pattern that has matched.
     ///
+    /// An empty literal set fails with the `EmptyPatternSet` error.
+    ///
     fn for_platform<T: Mode>(&self, platform: Option<&PlatformRef>) -> Result<Database<T>, Self::Err> {
+        Capability::Literal.ensure()?;
+
+        if self.is_empty() {
+            return Err(HsError::EmptyPatternSet.into());
+        }
+
         let ptrs = self
             .iter()
             .map(|Literal { expression, .. }| expression.as_ptr() as *const _)
@@ -235,7 +296,7 @@ impl Builder for Literals {
                 err.as_mut_ptr(),
             )
             .ok_or_else(|| err.assume_init())
-            .map(|_| Database::from_ptr(db.assume_init()))
+            .map(|_| diagnostics::database(Database::from_ptr(db.assume_init())))
         }
     }
 }
@@ -280,6 +341,7 @@ pub mod tests {
     use crate::common::tests::validate_database;
     use crate::compile::{Flags, Platform};
     use crate::prelude::*;
+    use crate::{BlockMode, StreamingMode};
 
     #[test]
     fn test_database_compile() {
@@ -289,4 +351,15 @@ pub mod tests {
 
         validate_database(&db);
     }
+
+    #[test]
+    fn test_empty_patterns() {
+        let err = Patterns(vec![]).build::<BlockMode>().unwrap_err();
+
+        assert_eq!(err.downcast_ref::<crate::Error>(), Some(&crate::Error::EmptyPatternSet));
+
+        let patterns: Patterns = "# no rules\n\n".parse().unwrap();
+
+        assert!(patterns.build::<StreamingMode>().is_err());
+    }
 }
@@ -0,0 +1,214 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use foreign_types::ForeignTypeRef;
+
+use crate::common::{runtime_version, Database, Mode, Serialized};
+use crate::compile::{Builder, Platform, PlatformRef};
+
+/// A cache of the compiled databases in a directory, keyed by the hash of the patterns, the mode,
+/// the target platform and the Hyperscan version, so an unchanged pattern set is only compiled once.
+///
+/// The full key is stored with the cached database and compared on load, so a hash collision is a miss.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, BlockMode, CompileCache};
+/// let cache = CompileCache::new(std::env::temp_dir().join("hyperscan-cache"));
+/// let patterns = patterns!("foo", "bar");
+///
+/// let db: BlockDatabase = cache.build(&patterns).unwrap();
+/// assert!(cache.path::<BlockMode, _>(&patterns, None).unwrap().is_file());
+///
+/// // the cached database is loaded without compiling
+/// let db: BlockDatabase = cache.build(&patterns).unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// assert!(db.is_match("foo", &s).unwrap());
+/// # cache.clear().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct CompileCache {
+    dir: PathBuf,
+}
+
+impl CompileCache {
+    /// Constructs a cache in the directory, which is created on the first miss.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        CompileCache { dir: dir.into() }
+    }
+
+    /// Returns the directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the path of the cached database for the patterns, the mode and the target platform,
+    /// the host platform is used if the target platform is not specified.
+    pub fn path<T, B>(&self, builder: &B, platform: Option<&PlatformRef>) -> Result<PathBuf>
+    where
+        T: Mode,
+        B: Builder + fmt::Debug,
+    {
+        self.key::<T, B>(builder, platform).map(|key| self.entry_path(&key))
+    }
+
+    fn key<T, B>(&self, builder: &B, platform: Option<&PlatformRef>) -> Result<String>
+    where
+        T: Mode,
+        B: Builder + fmt::Debug,
+    {
+        let host;
+        let platform: &PlatformRef = match platform {
+            Some(platform) => platform,
+            None => {
+                host = Platform::host()?;
+                &host
+            }
+        };
+        let info = unsafe { &*platform.as_ptr() };
+
+        Ok(format!(
+            "{:?}|{}|{}|{}|{}|{}",
+            builder,
+            T::ID,
+            info.tune,
+            info.cpu_features,
+            runtime_version(),
+            env!("CARGO_PKG_VERSION")
+        ))
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.db", fnv1a(key.as_bytes())))
+    }
+
+    /// Load the cached database of the patterns, or compile and cache it on a miss.
+    pub fn build<T, B>(&self, builder: &B) -> Result<Database<T>>
+    where
+        T: Mode,
+        B: Builder + fmt::Debug,
+        B::Err: Into<anyhow::Error>,
+    {
+        self.for_platform(builder, None)
+    }
+
+    /// Load the cached database of the patterns for a target platform, or compile and cache it on a miss.
+    ///
+    /// A corrupted cache entry is compiled again and replaced.
+    pub fn for_platform<T, B>(&self, builder: &B, platform: Option<&PlatformRef>) -> Result<Database<T>>
+    where
+        T: Mode,
+        B: Builder + fmt::Debug,
+        B::Err: Into<anyhow::Error>,
+    {
+        let key = self.key::<T, B>(builder, platform)?;
+        let path = self.entry_path(&key);
+        let mut entry = entry_header(&key);
+
+        if let Ok(bytes) = fs::read(&path) {
+            match bytes.strip_prefix(entry.as_slice()) {
+                Some(serialized) => match serialized.deserialize() {
+                    Ok(db) => return Ok(db),
+                    Err(err) => log::warn!(target: "hyperscan", "drop cached database {}, {}", path.display(), err),
+                },
+                None => log::debug!(target: "hyperscan", "replace cached database {} of another key", path.display()),
+            }
+        }
+
+        let db: Database<T> = builder.for_platform(platform).map_err(Into::into)?;
+
+        entry.extend_from_slice(&db.serialize()?);
+
+        // the temporary file is unique to the process and the call, so the concurrent writers never share it
+        let seq = NEXT_TMP.fetch_add(1, Ordering::Relaxed);
+        let tmp = path.with_extension(format!("{}.{}.tmp", process::id(), seq));
+
+        fs::create_dir_all(&self.dir)?;
+
+        if let Err(err) = fs::write(&tmp, &entry).and_then(|_| fs::rename(&tmp, &path)) {
+            let _ = fs::remove_file(&tmp);
+
+            return Err(err.into());
+        }
+
+        Ok(db)
+    }
+
+    /// Remove all the cached databases.
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.is_dir() {
+            for entry in fs::read_dir(&self.dir)? {
+                let path = entry?.path();
+
+                if path.extension().map_or(false, |ext| ext == "db") {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The sequence number of the temporary files written by the current process.
+static NEXT_TMP: AtomicUsize = AtomicUsize::new(0);
+
+/// The header of a cached database, the length and the bytes of the full key.
+fn entry_header(key: &str) -> Vec<u8> {
+    let mut header = (key.len() as u32).to_le_bytes().to_vec();
+
+    header.extend_from_slice(key.as_bytes());
+    header
+}
+
+/// The 64-bit FNV-1a hash, which is stable across the Rust releases unlike the `DefaultHasher`.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_hash_collision() {
+        use crate::common::{Block, BlockDatabase};
+
+        let cache = CompileCache::new(std::env::temp_dir().join(format!("hyperscan-cache-{}", process::id())));
+        let patterns = patterns!("foo");
+        let path = cache.path::<Block, _>(&patterns, None).unwrap();
+
+        // another pattern set cached with the same hash
+        let other: BlockDatabase = "bar".parse().unwrap();
+        let mut entry = entry_header("other");
+
+        entry.extend_from_slice(&other.serialize().unwrap());
+        fs::create_dir_all(cache.dir()).unwrap();
+        fs::write(&path, entry).unwrap();
+
+        let db: BlockDatabase = cache.build(&patterns).unwrap();
+        let s = db.alloc_scratch().unwrap();
+
+        assert!(db.is_match("foo", &s).unwrap());
+        assert!(!db.is_match("bar", &s).unwrap());
+
+        let db: BlockDatabase = cache.build(&patterns).unwrap();
+
+        assert!(db.is_match("foo", &s).unwrap());
+
+        cache.clear().unwrap();
+    }
+}
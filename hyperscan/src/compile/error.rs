@@ -1,8 +1,10 @@
 use std::ffi::CStr;
 use std::fmt;
+use std::panic::Location;
 
 use foreign_types::{foreign_type, ForeignType};
 
+use crate::diagnostics;
 use crate::errors::{AsResult, Error as HsError};
 use crate::ffi;
 
@@ -23,17 +25,24 @@ impl AsCompileResult for ffi::hs_error_t {
     type Output = ();
     type Err = anyhow::Error;
 
+    #[track_caller]
     fn ok_or_else<F>(self, err: F) -> Result<Self::Output, Self::Err>
     where
         F: FnOnce() -> *mut ffi::hs_compile_error_t,
     {
         if self == ffi::HS_SUCCESS as ffi::hs_error_t {
-            Ok(())
-        } else if self == ffi::HS_COMPILER_ERROR {
-            Err(HsError::CompileError(unsafe { Error::from_ptr(err()) }).into())
-        } else {
-            Err(HsError::from(self).into())
+            return Ok(());
         }
+
+        let err = if self == ffi::HS_COMPILER_ERROR {
+            HsError::CompileError(unsafe { Error::from_ptr(err()) })
+        } else {
+            HsError::from(self)
+        };
+
+        diagnostics::failed(self, &err, Location::caller());
+
+        Err(err.into())
     }
 }
 
@@ -10,7 +10,7 @@ use derive_more::{From, Into};
 use foreign_types::{foreign_type, ForeignType, ForeignTypeRef};
 use libc::c_char;
 
-use crate::compile::{AsCompileResult, Pattern};
+use crate::compile::{AsCompileResult, Pattern, Patterns};
 use crate::ffi;
 
 bitflags! {
@@ -318,3 +318,41 @@ impl Pattern {
         Ok(info)
     }
 }
+
+impl Patterns {
+    /// The minimum length in bytes of a match for any pattern in the set.
+    ///
+    /// The input shorter than the minimum width can't match any pattern,
+    /// it could be skipped without scanning with `DatabaseRef::with_min_width()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{patterns, prelude::*};
+    /// let patterns = patterns!("foobar", r"\d{4}-\d{2}");
+    ///
+    /// assert_eq!(patterns.min_width().unwrap(), 6);
+    /// ```
+    pub fn min_width(&self) -> Result<usize> {
+        self.iter()
+            .map(|pattern| pattern.info().map(|info| info.min_width()))
+            .collect::<Result<Vec<_>>>()
+            .map(|widths| widths.into_iter().min().unwrap_or_default())
+    }
+
+    /// Whether any pattern in the set can produce matches that are not returned in order,
+    /// such matches could be reordered by the end offset with `Reorder`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{patterns, prelude::*};
+    /// assert!(!patterns!("foobar", r"\d{4}-\d{2}").unordered_matches().unwrap());
+    /// ```
+    pub fn unordered_matches(&self) -> Result<bool> {
+        self.iter()
+            .map(|pattern| pattern.info().map(|info| info.unordered_matches()))
+            .collect::<Result<Vec<_>>>()
+            .map(|unordered| unordered.into_iter().any(|b| b))
+    }
+}
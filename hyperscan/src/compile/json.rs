@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+
+use crate::compile::{Pattern, Patterns};
+
+/// The version of the JSON schema of the pattern sets.
+const SCHEMA_VERSION: u64 = 1;
+
+impl Patterns {
+    /// Export the patterns with the names of the pattern ids as JSON, for exchanging the rule sets between tooling.
+    ///
+    /// The schema is an object with the `version` of the schema, `1` for now, and the `patterns` array
+    /// of the `serde` objects of `Pattern` with the fields:
+    ///
+    /// - `expression`: the expression string.
+    /// - `id`: the optional pattern id.
+    /// - `name`: the optional name of the pattern id.
+    /// - `flags`: the optional array of the flag names, e.g. `["CASELESS", "SOM_LEFTMOST"]`.
+    /// - `ext`: the optional object of the extended parameters, `min_offset`, `max_offset`, `min_length`,
+    ///   `edit_distance` and `hamming_distance`.
+    /// - `som_horizon`: the optional precision of the start of match in stream mode,
+    ///   `"large"`, `"medium"` or `"small"`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// # use hyperscan::prelude::*;
+    /// # use serde_json::json;
+    /// let patterns = Patterns(vec![r"1:/foo\d+/iL{min_offset=4}".parse().unwrap(), "bar".parse().unwrap()]);
+    /// let mut names = HashMap::new();
+    /// names.insert(1, "foo".to_string());
+    ///
+    /// let value = patterns.to_json(&names);
+    ///
+    /// assert_eq!(
+    ///     value,
+    ///     json!({
+    ///         "version": 1,
+    ///         "patterns": [
+    ///             {
+    ///                 "id": 1,
+    ///                 "name": "foo",
+    ///                 "expression": r"foo\d+",
+    ///                 "flags": ["CASELESS", "SOM_LEFTMOST"],
+    ///                 "ext": {"min_offset": 4}
+    ///             },
+    ///             {"expression": "bar"}
+    ///         ]
+    ///     })
+    /// );
+    ///
+    /// let (imported, imported_names) = Patterns::from_json(&value).unwrap();
+    ///
+    /// assert_eq!(imported.0, patterns.0);
+    /// assert_eq!(imported_names, names);
+    /// ```
+    pub fn to_json(&self, names: &HashMap<u32, String>) -> Value {
+        let patterns = self
+            .iter()
+            .map(|pattern| {
+                let mut value = serde_json::to_value(pattern).expect("serialize pattern");
+
+                if let Some(name) = pattern.id.and_then(|id| names.get(&(id as u32))) {
+                    value["name"] = name.clone().into();
+                }
+
+                value
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "version": SCHEMA_VERSION,
+            "patterns": patterns,
+        })
+    }
+
+    /// Import the patterns and the names of the pattern ids from JSON exported by `Patterns::to_json`.
+    ///
+    /// The patterns are deserialized by the `serde` implementation of `Pattern`, the `name` field is taken out first.
+    pub fn from_json(value: &Value) -> Result<(Self, HashMap<u32, String>)> {
+        match value.get("version").and_then(Value::as_u64) {
+            Some(SCHEMA_VERSION) => {}
+            Some(version) => bail!("unsupported schema version: {}", version),
+            None => bail!("missing schema version"),
+        }
+
+        let mut patterns = vec![];
+        let mut names = HashMap::new();
+
+        for (i, value) in value
+            .get("patterns")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("missing patterns"))?
+            .iter()
+            .enumerate()
+        {
+            let mut value = value.clone();
+            let name = match value.as_object_mut().and_then(|obj| obj.remove("name")) {
+                Some(Value::String(name)) => Some(name),
+                Some(Value::Null) | None => None,
+                Some(_) => bail!("invalid `name` of pattern #{}", i),
+            };
+            let pattern: Pattern =
+                serde_json::from_value(value).map_err(|err| anyhow!("invalid pattern #{}, {}", i, err))?;
+
+            if let (Some(id), Some(name)) = (pattern.id, name) {
+                names.insert(id as u32, name);
+            }
+
+            patterns.push(pattern);
+        }
+
+        Ok((Patterns(patterns), names))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::compile::SomHorizon;
+
+    use super::*;
+
+    #[test]
+    fn test_patterns_json() {
+        let mut pattern: Pattern = "/ab{2}/sH".parse().unwrap();
+        pattern.som = Some(SomHorizon::Small);
+        pattern.ext.set_edit_distance(1);
+
+        let patterns = Patterns(vec![pattern]);
+        let json = patterns.to_json(&HashMap::new());
+
+        assert_eq!(
+            json,
+            json!({
+                "version": 1,
+                "patterns": [{
+                    "expression": "ab{2}",
+                    "flags": ["DOTALL", "SINGLEMATCH"],
+                    "ext": {"edit_distance": 1},
+                    "som_horizon": "small",
+                }],
+            })
+        );
+
+        let (imported, names) = Patterns::from_json(&json).unwrap();
+
+        assert_eq!(imported.0, patterns.0);
+        assert!(names.is_empty());
+
+        assert!(Patterns::from_json(&json!({"version": 2, "patterns": []})).is_err());
+        assert!(
+            Patterns::from_json(&json!({"version": 1, "patterns": [{"expression": "a", "flags": ["X"]}]})).is_err()
+        );
+    }
+}
@@ -2,13 +2,22 @@ mod error;
 #[macro_use]
 mod pattern;
 mod builder;
+mod cache;
 mod expr;
+#[cfg(feature = "json")]
+mod json;
 #[macro_use]
 #[cfg(feature = "literal")]
 mod literal;
 mod platform;
+#[cfg(feature = "literal")]
+mod prefilter;
+#[cfg(feature = "serde")]
+mod serialize;
+mod stats;
 
 pub use self::builder::{compile, Builder};
+pub use self::cache::CompileCache;
 pub use self::error::{AsCompileResult, Error};
 #[doc(hidden)]
 #[deprecated = "use `ExprExt` instead"]
@@ -21,3 +30,4 @@ pub use self::expr::{ExprExt, ExprInfo};
 pub use self::literal::{Flags as LiteralFlags, Literal, Literals};
 pub use self::pattern::{Flags, Pattern, Patterns, SomHorizon};
 pub use self::platform::{CpuFeatures, Platform, PlatformRef, Tune};
+pub use self::stats::BuildStats;
@@ -39,6 +39,28 @@ bitflags! {
     }
 }
 
+/// The names of the flags, e.g. `CASELESS`.
+#[cfg(feature = "serde")]
+pub(crate) fn flag_names() -> Vec<(&'static str, Flags)> {
+    #[allow(unused_mut)]
+    let mut names = vec![
+        ("CASELESS", Flags::CASELESS),
+        ("DOTALL", Flags::DOTALL),
+        ("MULTILINE", Flags::MULTILINE),
+        ("SINGLEMATCH", Flags::SINGLEMATCH),
+        ("ALLOWEMPTY", Flags::ALLOWEMPTY),
+        ("UTF8", Flags::UTF8),
+        ("UCP", Flags::UCP),
+        ("PREFILTER", Flags::PREFILTER),
+        ("SOM_LEFTMOST", Flags::SOM_LEFTMOST),
+    ];
+
+    #[cfg(feature = "v5")]
+    names.extend_from_slice(&[("COMBINATION", Flags::COMBINATION), ("QUIET", Flags::QUIET)]);
+
+    names
+}
+
 impl FromStr for Flags {
     type Err = Error;
 
@@ -114,6 +136,11 @@ impl fmt::Display for Flags {
 /// Defines the precision to track start of match offsets in stream state.
 #[repr(u32)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum SomHorizon {
     /// use full precision to track start of match offsets in stream state.
     ///
@@ -173,6 +200,107 @@ impl Pattern {
         })
     }
 
+    /// Construct a pattern matching the bytes literally, e.g. a binary signature.
+    ///
+    /// The bytes other than the ASCII alphanumeric characters are escaped as `\xHH`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let pattern = Pattern::from_bytes_escaped(b"MZ\x90\x00.*").unwrap();
+    ///
+    /// assert_eq!(pattern.expression, r"MZ\x90\x00\x2e\x2a");
+    /// ```
+    pub fn from_bytes_escaped(bytes: &[u8]) -> Result<Pattern> {
+        let mut expr = String::with_capacity(bytes.len() * 4);
+
+        for &b in bytes {
+            escape_byte(&mut expr, b);
+        }
+
+        Pattern::new(expr)
+    }
+
+    /// Construct a pattern from a hex signature, e.g. `"4D 5A ?? 00 [2-4] 5?"`.
+    ///
+    /// The signature consists of the hex bytes, the whitespaces between them are ignored,
+    /// `??` matches any byte, `A?` or `?A` matches a byte with the given high or low nibble,
+    /// and `[n]`, `[n-m]` or `[n-]` skips a number of any bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let pattern = Pattern::from_hex_signature("4D 5A ?? 00 [2-4] 5?").unwrap();
+    ///
+    /// assert_eq!(pattern.expression, r"MZ.\x00.{2,4}[\x50-\x5f]");
+    /// assert_eq!(pattern.flags, CompileFlags::DOTALL);
+    ///
+    /// let db: BlockDatabase = pattern.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let mut matches = vec![];
+    ///
+    /// db.scan(b"\x00MZ\x90\x00\x01\x02\x03\x5e", &s, |_, _, to, _| {
+    ///     matches.push(to);
+    ///     Matching::Continue
+    /// }).unwrap();
+    ///
+    /// assert_eq!(matches, vec![9]);
+    /// ```
+    pub fn from_hex_signature(sig: &str) -> Result<Pattern> {
+        let sig = sig.chars().filter(|c| !c.is_whitespace()).collect::<Vec<_>>();
+        let mut expr = String::new();
+        let mut i = 0;
+
+        while i < sig.len() {
+            if sig[i] == '[' {
+                let end = match sig[i..].iter().position(|&c| c == ']') {
+                    Some(off) => i + off,
+                    None => bail!("unterminated jump in hex signature"),
+                };
+                let jump = sig[i + 1..end].iter().collect::<String>();
+                let (min, max) = match jump.find('-') {
+                    Some(off) => (&jump[..off], Some(&jump[off + 1..])),
+                    None => (jump.as_str(), None),
+                };
+                let min = min.parse::<usize>()?;
+
+                match max {
+                    None => expr.push_str(&format!(".{{{}}}", min)),
+                    Some("") => expr.push_str(&format!(".{{{},}}", min)),
+                    Some(max) => expr.push_str(&format!(".{{{},{}}}", min, max.parse::<usize>()?)),
+                }
+
+                i = end + 1;
+                continue;
+            }
+
+            let (hi, lo) = match sig.get(i + 1) {
+                Some(&lo) => (sig[i], lo),
+                None => bail!("incomplete byte in hex signature"),
+            };
+
+            match (hi, lo, hi.to_digit(16), lo.to_digit(16)) {
+                ('?', '?', _, _) => expr.push('.'),
+                (_, '?', Some(hi), _) => expr.push_str(&format!("[\\x{:x}0-\\x{:x}f]", hi, hi)),
+                ('?', _, _, Some(lo)) => {
+                    expr.push('[');
+                    for hi in 0..16 {
+                        escape_byte(&mut expr, (hi << 4 | lo) as u8);
+                    }
+                    expr.push(']');
+                }
+                (_, _, Some(hi), Some(lo)) => escape_byte(&mut expr, (hi << 4 | lo) as u8),
+                _ => bail!("invalid byte `{}{}` in hex signature", hi, lo),
+            }
+
+            i += 2;
+        }
+
+        Pattern::with_flags(expr, Flags::DOTALL)
+    }
+
     /// Set case-insensitive matching.
     pub fn caseless(mut self) -> Self {
         self.flags |= Flags::CASELESS;
@@ -250,6 +378,14 @@ impl Pattern {
     }
 }
 
+fn escape_byte(expr: &mut String, b: u8) {
+    if b.is_ascii_alphanumeric() {
+        expr.push(b as char);
+    } else {
+        expr.push_str(&format!("\\x{:02x}", b));
+    }
+}
+
 impl fmt::Display for Pattern {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(id) = self.id {
@@ -319,9 +455,7 @@ impl FromStr for Pattern {
 #[derive(Clone, Debug, Deref, DerefMut, From, Index, IndexMut, Into, IntoIterator)]
 #[deref(forward)]
 #[deref_mut(forward)]
-pub struct Patterns(
-    pub Vec<Pattern>
-);
+pub struct Patterns(pub Vec<Pattern>);
 
 impl FromIterator<Pattern> for Patterns {
     fn from_iter<T: IntoIterator<Item = Pattern>>(iter: T) -> Self {
@@ -429,6 +563,17 @@ mod tests {
         assert!("test".parse::<Flags>().is_err());
     }
 
+    #[test]
+    fn test_hex_signature() {
+        assert_eq!(
+            Pattern::from_hex_signature("?A[3-]b0").unwrap().expression,
+            r"[\x0a\x1a\x2a\x3aJZjz\x8a\x9a\xaa\xba\xca\xda\xea\xfa].{3,}\xb0"
+        );
+        assert!(Pattern::from_hex_signature("4D 5").is_err());
+        assert!(Pattern::from_hex_signature("4D [2").is_err());
+        assert!(Pattern::from_hex_signature("4D ZZ").is_err());
+    }
+
     #[test]
     fn test_pattern() {
         let p: Pattern = "test".parse().unwrap();
@@ -1,10 +1,9 @@
 use std::mem::{self, MaybeUninit};
 
-use anyhow::Result;
 use bitflags::bitflags;
 use foreign_types::{foreign_type, ForeignType};
 
-use crate::errors::AsResult;
+use crate::errors::{AsResult, Result};
 use crate::ffi;
 
 /// Tuning Parameter
@@ -0,0 +1,341 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::compile::{Flags, Literal, LiteralFlags, Literals, Pattern, Patterns};
+
+impl Pattern {
+    /// Extract the longest literal fragment which every match of the pattern must contain.
+    ///
+    /// The extraction is conservative, it only considers the fragments at the top level of the expression,
+    /// and returns `None` if the pattern has a top-level alternation, an approximate matching parameter,
+    /// an extended (`x`) or quoted (`\Q...\E`) syntax, or no literal at all.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, LiteralFlags};
+    /// let pattern: Pattern = r"/user=\w+&token=[0-9a-f]{32}/i".parse().unwrap();
+    /// let literal = pattern.required_literal().unwrap();
+    ///
+    /// assert_eq!(literal.expression, "&token=");
+    /// assert!(literal.flags.contains(LiteralFlags::CASELESS));
+    ///
+    /// assert!(pattern! {"foo|bar"}.required_literal().is_none());
+    /// ```
+    pub fn required_literal(&self) -> Option<Literal> {
+        #[cfg(feature = "v5")]
+        {
+            if self.flags.contains(Flags::COMBINATION) {
+                return None;
+            }
+        }
+
+        if self.ext.edit_distance().is_some() || self.ext.hamming_distance().is_some() {
+            return None;
+        }
+
+        let mut caseless = self.flags.contains(Flags::CASELESS);
+        let mut fragments = Fragments::default();
+        let mut chars = self.expression.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '|' => return None,
+                '\\' => match chars.next()? {
+                    'Q' => return None,
+                    c if c.is_ascii_alphanumeric() => {
+                        fragments.cut();
+                        skip_escape(c, &mut chars);
+                    }
+                    c => fragments.push(c, caseless),
+                },
+                '[' => {
+                    fragments.cut();
+                    skip_class(&mut chars)?;
+                }
+                '(' => {
+                    fragments.cut();
+
+                    let group = take_group(&mut chars)?;
+
+                    // the inline options, e.g. `(?i)` or `(?i-s:...)`
+                    if let Some(options) = group.strip_prefix('?') {
+                        let (options, scoped) = match options.find(':') {
+                            Some(off) => (&options[..off], true),
+                            None => (options, false),
+                        };
+
+                        if options.chars().all(|c| c.is_ascii_alphabetic() || c == '-') {
+                            let enabled = options.split('-').next().unwrap_or_default();
+
+                            if enabled.contains('x') {
+                                return None;
+                            }
+                            if enabled.contains('i') && !scoped {
+                                caseless = true;
+                            }
+                        }
+                    }
+                }
+                '*' | '?' => {
+                    fragments.pop();
+                    fragments.cut();
+                    skip_lazy(&mut chars);
+                }
+                '+' => {
+                    fragments.cut();
+                    skip_lazy(&mut chars);
+                }
+                '{' => match take_repeat(&mut chars) {
+                    Some(min) => {
+                        if min == 0 {
+                            fragments.pop();
+                        }
+                        fragments.cut();
+                        skip_lazy(&mut chars);
+                    }
+                    None => fragments.push('{', caseless),
+                },
+                '.' | '^' | '$' => fragments.cut(),
+                c => fragments.push(c, caseless),
+            }
+        }
+
+        fragments.longest().map(|expression| Literal {
+            expression,
+            flags: if caseless {
+                LiteralFlags::CASELESS | LiteralFlags::SINGLEMATCH
+            } else {
+                LiteralFlags::SINGLEMATCH
+            },
+            id: self.id,
+            som: None,
+        })
+    }
+}
+
+impl Patterns {
+    /// Extract the required literals of all the patterns, which could be compiled as a cheap first-pass gate,
+    /// the data can't match any pattern if none of the literals is found.
+    ///
+    /// Returns `None` if any pattern has no required literal, since the gate would always have to be passed.
+    /// The literal without an explicit id is assigned with the index of its pattern.
+    pub fn required_literals(&self) -> Option<Literals> {
+        self.iter()
+            .enumerate()
+            .map(|(i, pattern)| {
+                pattern.required_literal().map(|mut literal| {
+                    literal.id.get_or_insert(i);
+                    literal
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct Fragments {
+    current: String,
+    longest: String,
+}
+
+impl Fragments {
+    fn push(&mut self, c: char, caseless: bool) {
+        // the caseless literal only folds the ASCII letters
+        if caseless && !c.is_ascii() {
+            self.cut()
+        } else {
+            self.current.push(c)
+        }
+    }
+
+    fn pop(&mut self) {
+        self.current.pop();
+    }
+
+    fn cut(&mut self) {
+        if self.current.len() > self.longest.len() {
+            self.longest = std::mem::take(&mut self.current);
+        } else {
+            self.current.clear();
+        }
+    }
+
+    fn longest(mut self) -> Option<String> {
+        self.cut();
+
+        Some(self.longest).filter(|s| !s.is_empty())
+    }
+}
+
+/// Skip the arguments of an escape sequence, e.g. `\x41`, `\x{263a}`, `\pL`, `\p{Greek}` or `\k<name>`.
+fn skip_escape(c: char, chars: &mut Peekable<Chars<'_>>) {
+    if chars.next_if_eq(&'{').is_some() {
+        while chars.next().map_or(false, |c| c != '}') {}
+        return;
+    }
+
+    match c {
+        'x' => {
+            for _ in 0..2 {
+                chars.next_if(|c| c.is_ascii_hexdigit());
+            }
+        }
+        '0'..='9' => {
+            for _ in 0..2 {
+                chars.next_if(|c| c.is_ascii_digit());
+            }
+        }
+        'p' | 'P' | 'c' => {
+            chars.next();
+        }
+        'g' | 'k' => match chars.next_if(|&c| c == '<' || c == '\'') {
+            Some(open) => {
+                let close = if open == '<' { '>' } else { open };
+
+                while chars.next().map_or(false, |c| c != close) {}
+            }
+            None => {
+                chars.next_if_eq(&'-');
+                while chars.next_if(|c| c.is_ascii_digit()).is_some() {}
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Skip a character class, returns `None` if it isn't closed.
+fn skip_class(chars: &mut Peekable<Chars<'_>>) -> Option<()> {
+    chars.next_if_eq(&'^');
+    chars.next_if_eq(&']');
+
+    loop {
+        match chars.next()? {
+            ']' => return Some(()),
+            '\\' => {
+                chars.next()?;
+            }
+            '[' if chars.next_if_eq(&':').is_some() => while chars.next()? != ']' {},
+            _ => {}
+        }
+    }
+}
+
+/// Take the content of a group until the matched `)`, returns `None` if it isn't closed.
+fn take_group(chars: &mut Peekable<Chars<'_>>) -> Option<String> {
+    let mut group = String::new();
+    let mut depth = 0;
+
+    loop {
+        let c = chars.next()?;
+
+        match c {
+            ')' if depth == 0 => return Some(group),
+            ')' => depth -= 1,
+            '(' => depth += 1,
+            '\\' => {
+                group.push(c);
+                group.push(chars.next()?);
+                continue;
+            }
+            '[' => {
+                skip_class(chars)?;
+                group.push_str("[]");
+                continue;
+            }
+            _ => {}
+        }
+
+        group.push(c);
+    }
+}
+
+/// Take a counted repetition `{n}`, `{n,}` or `{n,m}` and returns the minimum count,
+/// the brace is a literal if it isn't a valid repetition.
+fn take_repeat(chars: &mut Peekable<Chars<'_>>) -> Option<usize> {
+    let mut lookahead = chars.clone();
+    let mut min = String::new();
+
+    while let Some(c) = lookahead.next_if(|c| c.is_ascii_digit()) {
+        min.push(c);
+    }
+
+    if lookahead.next_if_eq(&',').is_some() {
+        while lookahead.next_if(|c| c.is_ascii_digit()).is_some() {}
+    }
+
+    let min = min.parse().ok()?;
+
+    lookahead.next_if_eq(&'}')?;
+    *chars = lookahead;
+
+    Some(min)
+}
+
+/// Skip the lazy or possessive modifier of a quantifier.
+fn skip_lazy(chars: &mut Peekable<Chars<'_>>) {
+    let _ = chars.next_if(|&c| c == '?' || c == '+');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn required(expr: &str) -> Option<String> {
+        expr.parse::<Pattern>()
+            .unwrap()
+            .required_literal()
+            .map(|literal| literal.expression)
+    }
+
+    #[test]
+    fn test_required_literal() {
+        assert_eq!(required("foobar"), Some("foobar".into()));
+        assert_eq!(required(r"foo\d+barbaz"), Some("barbaz".into()));
+        assert_eq!(required(r"abcd?e"), Some("abc".into()));
+        assert_eq!(required(r"ab{0,2}cd"), Some("cd".into()));
+        assert_eq!(required(r"ab{2}c"), Some("ab".into()));
+        assert_eq!(required(r"a{b"), Some("a{b".into()));
+        assert_eq!(required(r"\x41\x42xyz"), Some("xyz".into()));
+        assert_eq!(required(r"\.com\b"), Some(".com".into()));
+        assert_eq!(required(r"[a-z]+(foo|bar)baz"), Some("baz".into()));
+        assert_eq!(required(r"[]x]yz"), Some("yz".into()));
+        assert_eq!(required(r"\k<name>foo"), Some("foo".into()));
+        assert_eq!(required(r"(?i-s)Foo"), Some("Foo".into()));
+        assert!(r"(?i)Foo"
+            .parse::<Pattern>()
+            .unwrap()
+            .required_literal()
+            .unwrap()
+            .flags
+            .contains(LiteralFlags::CASELESS));
+        assert!(!r"(?i:x)Foo"
+            .parse::<Pattern>()
+            .unwrap()
+            .required_literal()
+            .unwrap()
+            .flags
+            .contains(LiteralFlags::CASELESS));
+
+        assert_eq!(required("foo|bar"), None);
+        assert_eq!(required(r"\d+"), None);
+        assert_eq!(required(r"(?x) foo"), None);
+        assert_eq!(required(r"\Qfoo\E"), None);
+        assert_eq!(required(r"[abc"), None);
+    }
+
+    #[test]
+    fn test_required_literals() {
+        let patterns: Patterns = "foo\\d+\n2:/bar/i".parse().unwrap();
+        let literals = patterns.required_literals().unwrap();
+
+        assert_eq!(literals.len(), 2);
+        assert_eq!(literals[0].id, Some(0));
+        assert_eq!(literals[1].id, Some(2));
+        assert!(literals[1].flags.contains(LiteralFlags::CASELESS));
+
+        let patterns: Patterns = "foo\n.*".parse().unwrap();
+
+        assert!(patterns.required_literals().is_none());
+    }
+}
@@ -0,0 +1,215 @@
+use serde::ser::SerializeSeq;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::compile::pattern::flag_names;
+use crate::compile::{ExprExt, Flags, Pattern, Patterns, SomHorizon};
+
+/// The flags are serialized as an array of the flag names, e.g. `["CASELESS", "SOM_LEFTMOST"]`,
+/// and could be deserialized from the short form of the flags, e.g. `"iL"`, as well.
+impl Serialize for Flags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let names = flag_names()
+            .into_iter()
+            .filter(|&(_, flag)| self.contains(flag))
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        let mut seq = serializer.serialize_seq(Some(names.len()))?;
+
+        for name in names {
+            seq.serialize_element(name)?;
+        }
+
+        seq.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FlagsDef {
+    Names(Vec<String>),
+    Short(String),
+}
+
+impl<'de> Deserialize<'de> for Flags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match FlagsDef::deserialize(deserializer)? {
+            FlagsDef::Names(names) => {
+                let table = flag_names();
+
+                names.iter().try_fold(Flags::empty(), |flags, name| {
+                    table
+                        .iter()
+                        .find(|&&(n, _)| n == name.as_str())
+                        .map(|&(_, flag)| flags | flag)
+                        .ok_or_else(|| de::Error::custom(format!("invalid pattern flag: {}", name)))
+                })
+            }
+            FlagsDef::Short(s) => s.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExprExtDef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_length: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    edit_distance: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hamming_distance: Option<u32>,
+}
+
+/// The extended parameters are serialized as an object of the parameters which are set, e.g. `{"min_offset": 4}`.
+impl Serialize for ExprExt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ExprExtDef {
+            min_offset: self.min_offset(),
+            max_offset: self.max_offset(),
+            min_length: self.min_length(),
+            edit_distance: self.edit_distance(),
+            hamming_distance: self.hamming_distance(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExprExt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let def = ExprExtDef::deserialize(deserializer)?;
+        let mut ext = ExprExt::default();
+
+        if let Some(n) = def.min_offset {
+            ext.set_min_offset(n);
+        }
+        if let Some(n) = def.max_offset {
+            ext.set_max_offset(n);
+        }
+        if let Some(n) = def.min_length {
+            ext.set_min_length(n);
+        }
+        if let Some(n) = def.edit_distance {
+            ext.set_edit_distance(n);
+        }
+        if let Some(n) = def.hamming_distance {
+            ext.set_hamming_distance(n);
+        }
+
+        Ok(ext)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PatternDef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    expression: String,
+    #[serde(default, skip_serializing_if = "Flags::is_empty")]
+    flags: Flags,
+    #[serde(default, skip_serializing_if = "ExprExt::is_empty")]
+    ext: ExprExt,
+    #[serde(default, rename = "som_horizon", skip_serializing_if = "Option::is_none")]
+    som: Option<SomHorizon>,
+}
+
+/// The pattern is serialized as an object with the `expression`, and the optional `id`, `flags`, `ext`
+/// and `som_horizon` fields, and could be deserialized from the string form of the pattern,
+/// e.g. `"1:/foo\d+/iL{min_offset=4}"`, as well.
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PatternDef {
+            id: self.id,
+            expression: self.expression.clone(),
+            flags: self.flags,
+            ext: self.ext,
+            som: self.som,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PatternOrStr {
+    Str(String),
+    Pattern(PatternDef),
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match PatternOrStr::deserialize(deserializer)? {
+            PatternOrStr::Str(s) => s.parse().map_err(de::Error::custom),
+            PatternOrStr::Pattern(PatternDef {
+                id,
+                expression,
+                flags,
+                ext,
+                som,
+            }) => Ok(Pattern {
+                expression,
+                flags,
+                id,
+                ext,
+                som,
+            }),
+        }
+    }
+}
+
+/// The patterns are serialized as an array of the patterns.
+impl Serialize for Patterns {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Patterns {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Patterns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_serde_patterns() {
+        let patterns = Patterns(vec![
+            r"1:/foo\d+/iL{min_offset=4}".parse().unwrap(),
+            "bar".parse().unwrap(),
+        ]);
+        let value = json!([
+            {
+                "id": 1,
+                "expression": r"foo\d+",
+                "flags": ["CASELESS", "SOM_LEFTMOST"],
+                "ext": {"min_offset": 4}
+            },
+            {"expression": "bar"}
+        ]);
+
+        assert_eq!(serde_json::to_value(&patterns).unwrap(), value);
+        assert_eq!(serde_json::from_value::<Patterns>(value).unwrap().0, patterns.0);
+
+        let imported: Patterns = serde_json::from_value(json!([
+            r"1:/foo\d+/iL{min_offset=4}",
+            {"expression": "bar", "flags": "s", "som_horizon": "small"}
+        ]))
+        .unwrap();
+
+        assert_eq!(imported[0], patterns[0]);
+        assert_eq!(imported[1].flags, Flags::DOTALL);
+        assert_eq!(imported[1].som, Some(SomHorizon::Small));
+
+        assert!(serde_json::from_value::<Pattern>(json!({"expression": "foo", "flags": ["UNKNOWN"]})).is_err());
+        assert!(serde_json::from_value::<Pattern>(json!({"expression": "foo", "ext": {"unknown": 1}})).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::common::{Database, Mode};
+use crate::compile::{Builder, Patterns, PlatformRef};
+
+/// The statistics of building a database, for tracking the compile cost across the versions of the rules.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BuildStats {
+    /// The number of the patterns.
+    pub patterns: usize,
+    /// The time of validating the patterns.
+    pub validation: Duration,
+    /// The time of compiling the database.
+    pub compilation: Duration,
+    /// The size of the compiled database in bytes.
+    pub database_size: usize,
+    /// The size of the scratch space allocated for the database in bytes.
+    pub scratch_size: Option<usize>,
+}
+
+impl fmt::Display for BuildStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} patterns, validated in {:?}, compiled in {:?}, database {} bytes",
+            self.patterns, self.validation, self.compilation, self.database_size
+        )?;
+
+        if let Some(size) = self.scratch_size {
+            write!(f, ", scratch {} bytes", size)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Patterns {
+    /// Build the patterns into a database, returns it with the statistics of building.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{patterns, prelude::*, BlockMode};
+    /// let (db, stats) = patterns!("foo", "bar").build_with_stats::<BlockMode>().unwrap();
+    ///
+    /// assert_eq!(stats.patterns, 2);
+    /// assert_eq!(stats.database_size, db.size().unwrap());
+    /// assert!(stats.scratch_size.unwrap() > 0);
+    /// ```
+    pub fn build_with_stats<T: Mode>(&self) -> Result<(Database<T>, BuildStats)> {
+        self.for_platform_with_stats(None)
+    }
+
+    /// Build the patterns into a database for a target platform, returns it with the statistics of building.
+    pub fn for_platform_with_stats<T: Mode>(
+        &self,
+        platform: Option<&PlatformRef>,
+    ) -> Result<(Database<T>, BuildStats)> {
+        let start = Instant::now();
+
+        for pattern in self.iter() {
+            // the logical combinations are validated by the compiler
+            #[cfg(feature = "v5")]
+            if pattern.flags.contains(crate::compile::Flags::COMBINATION) {
+                continue;
+            }
+
+            pattern.info()?;
+        }
+
+        let validation = start.elapsed();
+        let start = Instant::now();
+        let db: Database<T> = self.for_platform(platform)?;
+        let compilation = start.elapsed();
+
+        #[cfg(feature = "runtime")]
+        let scratch_size = Some(db.alloc_scratch()?.size()?);
+        #[cfg(not(feature = "runtime"))]
+        let scratch_size = None;
+
+        let stats = BuildStats {
+            patterns: self.len(),
+            validation,
+            compilation,
+            database_size: db.size()?,
+            scratch_size,
+        };
+
+        Ok((db, stats))
+    }
+}
@@ -0,0 +1,190 @@
+//! Diagnostics of the FFI calls.
+//!
+//! The diagnostics mode logs the return codes of failed FFI calls, the size of databases and scratch spaces,
+//! and a summary of each scan through the [`log`](https://docs.rs/log) crate with target `hyperscan`.
+//!
+//! It can be enabled with the `HYPERSCAN_DIAGNOSTICS` environment variable (`1`, `true` or `on`),
+//! or with the `diagnostics::enable` function at runtime.
+//!
+//! The failed FFI calls could also be counted or alerted on with the hook set by `diagnostics::set_error_hook`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use hyperscan::{diagnostics, prelude::*};
+//! diagnostics::enable();
+//!
+//! assert!(diagnostics::is_enabled());
+//!
+//! let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+//!
+//! diagnostics::disable();
+//! ```
+use std::env;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::errors::Error;
+use crate::ffi;
+
+/// The environment variable to enable the diagnostics mode.
+pub const ENV_VAR: &str = "HYPERSCAN_DIAGNOSTICS";
+
+const UNKNOWN: u8 = 0;
+const DISABLED: u8 = 1;
+const ENABLED: u8 = 2;
+
+static STATE: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+/// Enable the diagnostics mode.
+pub fn enable() {
+    STATE.store(ENABLED, Ordering::Relaxed)
+}
+
+/// Disable the diagnostics mode.
+pub fn disable() {
+    STATE.store(DISABLED, Ordering::Relaxed)
+}
+
+/// Returns `true` if the diagnostics mode is enabled.
+pub fn is_enabled() -> bool {
+    match STATE.load(Ordering::Relaxed) {
+        UNKNOWN => {
+            let enabled = env::var(ENV_VAR).map_or(false, |v| {
+                v == "1" || v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("on")
+            });
+
+            STATE.store(if enabled { ENABLED } else { DISABLED }, Ordering::Relaxed);
+
+            enabled
+        }
+        state => state == ENABLED,
+    }
+}
+
+/// The context of a failed FFI call passed to the error hook.
+#[derive(Debug)]
+pub struct FailedCall<'a> {
+    /// The typed error of the call.
+    pub error: &'a Error,
+    /// The return code of the call.
+    pub code: i32,
+    /// The location of the call in the crate.
+    pub location: &'static Location<'static>,
+}
+
+type ErrorHook = Arc<dyn Fn(&FailedCall<'_>) + Send + Sync>;
+
+static ERROR_HOOK: RwLock<Option<ErrorHook>> = RwLock::new(None);
+
+/// Set a hook which is called whenever an FFI call returns a non-success code,
+/// e.g. to count and alert on `Error::ScratchInUse` or `Error::NoMem`.
+///
+/// The hook is called regardless of the diagnostics mode, except for the scans terminated by the callbacks.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// # use hyperscan::{diagnostics, prelude::*, SerializedDatabase};
+/// static FAILED: AtomicUsize = AtomicUsize::new(0);
+///
+/// diagnostics::set_error_hook(|call| {
+///     log::warn!("{} at {}: {}", call.code, call.location, call.error);
+///
+///     FAILED.fetch_add(1, Ordering::Relaxed);
+/// });
+///
+/// let res: hyperscan::Result<BlockDatabase> = [0u8; 16].deserialize();
+///
+/// assert!(res.is_err());
+/// assert_eq!(FAILED.load(Ordering::Relaxed), 1);
+///
+/// diagnostics::clear_error_hook();
+/// ```
+pub fn set_error_hook<F>(hook: F)
+where
+    F: Fn(&FailedCall<'_>) + Send + Sync + 'static,
+{
+    *ERROR_HOOK.write().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(hook));
+}
+
+/// Remove the hook of the failed FFI calls.
+pub fn clear_error_hook() {
+    *ERROR_HOOK.write().unwrap_or_else(|err| err.into_inner()) = None;
+}
+
+/// Log a failed FFI call, and pass it to the error hook.
+pub(crate) fn failed(code: ffi::hs_error_t, err: &Error, location: &'static Location<'static>) {
+    if code != ffi::HS_SCAN_TERMINATED {
+        let hook = ERROR_HOOK.read().unwrap_or_else(|err| err.into_inner()).clone();
+
+        if let Some(hook) = hook {
+            hook(&FailedCall {
+                error: err,
+                code,
+                location,
+            });
+        }
+    }
+
+    if is_enabled() {
+        if code == ffi::HS_SCAN_TERMINATED {
+            log::debug!(target: "hyperscan", "{}: scan terminated by callback", location);
+        } else {
+            log::warn!(target: "hyperscan", "{}: FFI call returns {}, {}", location, code, err);
+        }
+    }
+}
+
+/// Log the size of a compiled or deserialized database.
+pub(crate) fn database<T>(db: crate::Database<T>) -> crate::Database<T> {
+    if is_enabled() {
+        match db.size() {
+            Ok(size) => log::debug!(target: "hyperscan", "database @ {:p} allocated, {} bytes", &*db, size),
+            Err(err) => log::warn!(target: "hyperscan", "database @ {:p} allocated, unknown size, {}", &*db, err),
+        }
+    }
+
+    db
+}
+
+/// Log the size of an allocated scratch space.
+#[cfg(feature = "runtime")]
+pub(crate) fn scratch(s: &crate::ScratchRef) {
+    if is_enabled() {
+        match s.size() {
+            Ok(size) => log::debug!(target: "hyperscan", "scratch @ {:p} allocated, {} bytes", s, size),
+            Err(err) => log::warn!(target: "hyperscan", "scratch @ {:p} allocated, unknown size, {}", s, err),
+        }
+    }
+}
+
+/// Log a summary of the scan.
+#[cfg(feature = "runtime")]
+pub(crate) fn scan<F>(mode: &str, len: usize, f: F) -> ffi::hs_error_t
+where
+    F: FnOnce() -> ffi::hs_error_t,
+{
+    #[cfg(feature = "prometheus")]
+    let f = move || crate::metrics::scan(mode, len, f);
+
+    if !is_enabled() {
+        return f();
+    }
+
+    let start = std::time::Instant::now();
+    let code = f();
+
+    log::debug!(
+        target: "hyperscan",
+        "{} scan {} bytes in {:?}, returns {}",
+        mode,
+        len,
+        start.elapsed(),
+        code
+    );
+
+    code
+}
@@ -0,0 +1,217 @@
+//! Normalizing and scanning the DNS names.
+//!
+//! The domain blocklist patterns are usually written against the canonical form of the names,
+//! the `Normalizer` lowercases the names, removes the trailing dots, and optionally decodes
+//! the punycode labels (`xn--`), and the `DomainScanner` feeds the normalized names to the block scans.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use hyperscan::{prelude::*, dns::{DomainScanner, Normalizer}};
+//! let db: BlockDatabase = pattern! {r"(^|\.)bücher\.example$"; UTF8}.build().unwrap();
+//! let s = db.alloc_scratch().unwrap();
+//! let scanner = DomainScanner::new(&db).normalizer(Normalizer::new().punycode(true));
+//! let mut matches = vec![];
+//!
+//! for name in &["WWW.xn--bcher-kva.Example.", "www.example.com"] {
+//!     scanner
+//!         .scan(name, &s, |name, _id, _from, _to| {
+//!             matches.push(name.to_string());
+//!             Matching::Continue
+//!         })
+//!         .unwrap();
+//! }
+//!
+//! assert_eq!(matches, vec!["www.bücher.example"]);
+//! ```
+use anyhow::Result;
+
+use crate::common::BlockDatabase;
+use crate::runtime::{Matching, ScanOutcome, ScratchRef};
+
+/// A normalizer of the host names.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Normalizer {
+    punycode: bool,
+}
+
+impl Normalizer {
+    /// Constructs a normalizer which lowercases the names and removes the trailing dots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode the punycode labels to Unicode, the malformed labels are kept as is.
+    pub fn punycode(mut self, yes: bool) -> Self {
+        self.punycode = yes;
+        self
+    }
+
+    /// Returns the normalized name.
+    pub fn normalize(&self, name: &str) -> String {
+        let name = name.trim().trim_end_matches('.');
+
+        if !self.punycode {
+            return name.to_lowercase();
+        }
+
+        name.split('.')
+            .map(|label| {
+                let label = label.to_lowercase();
+
+                match label.strip_prefix("xn--").and_then(punycode::decode) {
+                    Some(decoded) => decoded.to_lowercase(),
+                    None => label,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+/// A scanner feeding the normalized names to a block mode database.
+pub struct DomainScanner<'a> {
+    db: &'a BlockDatabase,
+    normalizer: Normalizer,
+}
+
+impl<'a> DomainScanner<'a> {
+    /// Constructs a scanner with the default normalizer.
+    pub fn new(db: &'a BlockDatabase) -> Self {
+        DomainScanner {
+            db,
+            normalizer: Normalizer::default(),
+        }
+    }
+
+    /// Set the normalizer of the names.
+    pub fn normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    /// Scan the normalized name, the matches are passed to the callback with the normalized name,
+    /// the offsets are relative to the normalized name.
+    pub fn scan<F>(&self, name: &str, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        F: FnMut(&str, u32, u64, u64) -> Matching,
+    {
+        let name = self.normalizer.normalize(name);
+
+        Ok(self.db.scan(&name, scratch, |id, from, to, _flags| {
+            on_match_event(&name, id, from, to)
+        })?)
+    }
+}
+
+/// The punycode decoder of RFC 3492.
+mod punycode {
+    use std::char;
+
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+    const SKEW: u32 = 38;
+    const DAMP: u32 = 700;
+    const INITIAL_BIAS: u32 = 72;
+    const INITIAL_N: u32 = 128;
+
+    pub fn decode(input: &str) -> Option<String> {
+        let (basic, extended) = match input.rfind('-') {
+            Some(pos) => (&input[..pos], &input[pos + 1..]),
+            None => ("", input),
+        };
+
+        if !basic.is_ascii() {
+            return None;
+        }
+
+        let mut output = basic.chars().collect::<Vec<_>>();
+        let mut n = INITIAL_N;
+        let mut i = 0u32;
+        let mut bias = INITIAL_BIAS;
+        let mut digits = extended.bytes().peekable();
+
+        while digits.peek().is_some() {
+            let old_i = i;
+            let mut w = 1u32;
+            let mut k = BASE;
+
+            loop {
+                let digit = match digits.next()? {
+                    c @ b'a'..=b'z' => c - b'a',
+                    c @ b'A'..=b'Z' => c - b'A',
+                    c @ b'0'..=b'9' => c - b'0' + 26,
+                    _ => return None,
+                } as u32;
+
+                i = i.checked_add(digit.checked_mul(w)?)?;
+
+                let t = if k <= bias {
+                    T_MIN
+                } else if k >= bias + T_MAX {
+                    T_MAX
+                } else {
+                    k - bias
+                };
+
+                if digit < t {
+                    break;
+                }
+
+                w = w.checked_mul(BASE - t)?;
+                k += BASE;
+            }
+
+            let len = output.len() as u32 + 1;
+
+            bias = adapt(i - old_i, len, old_i == 0);
+            n = n.checked_add(i / len)?;
+            i %= len;
+
+            output.insert(i as usize, char::from_u32(n)?);
+            i += 1;
+        }
+
+        Some(output.into_iter().collect())
+    }
+
+    fn adapt(delta: u32, points: u32, first: bool) -> u32 {
+        let mut delta = if first { delta / DAMP } else { delta / 2 };
+        let mut k = 0;
+
+        delta += delta / points;
+
+        while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+            delta /= BASE - T_MIN;
+            k += BASE;
+        }
+
+        k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punycode() {
+        assert_eq!(punycode::decode("bcher-kva").as_deref(), Some("bücher"));
+        assert_eq!(punycode::decode("mnchen-3ya").as_deref(), Some("münchen"));
+        assert_eq!(punycode::decode("fiqs8s").as_deref(), Some("中国"));
+        assert_eq!(punycode::decode("a-!").as_deref(), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let normalizer = Normalizer::new();
+
+        assert_eq!(normalizer.normalize(" WWW.Example.COM. "), "www.example.com");
+        assert_eq!(normalizer.normalize("xn--bcher-kva.example"), "xn--bcher-kva.example");
+        assert_eq!(
+            normalizer.punycode(true).normalize("XN--BCHER-KVA.example."),
+            "bücher.example"
+        );
+    }
+}
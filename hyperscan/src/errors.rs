@@ -1,13 +1,21 @@
 use std::fmt;
+use std::io;
+use std::mem;
+use std::panic::Location;
+use std::str::Utf8Error;
 
 use thiserror::Error;
 
+#[cfg(feature = "signed")]
+use crate::common::EnvelopeError;
+use crate::common::{BundleError, Capability};
 #[cfg(feature = "compile")]
 use crate::compile::Error as CompileError;
+use crate::diagnostics;
 use crate::ffi;
 
 /// Error Codes
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error)]
 pub enum Error {
     /// A parameter passed to this function was invalid.
     #[error("A parameter passed to this function was invalid.")]
@@ -26,6 +34,11 @@ pub enum Error {
     #[error("The pattern compiler failed with more detail, {0}.")]
     CompileError(CompileError),
 
+    /// The pattern set to compile is empty.
+    #[cfg(feature = "compile")]
+    #[error("The pattern set is empty.")]
+    EmptyPatternSet,
+
     /// The given database was built for a different version of Hyperscan.
     #[error("The given database was built for a different version of Hyperscan.")]
     DbVersionError,
@@ -63,11 +76,78 @@ pub enum Error {
     #[error("Unexpected internal error.")]
     UnknownError,
 
+    /// The API is not supported by the linked Hyperscan library.
+    #[error("The {0} is not supported by the linked Hyperscan {1}.")]
+    Unsupported(Capability, semver::Version),
+
+    /// The scan was cancelled by a `CancellationToken`.
+    #[error("The scan was cancelled.")]
+    Cancelled,
+
+    /// The session table is full.
+    #[error("The session table is full with {0} sessions.")]
+    SessionLimit(usize),
+
+    /// Reading a database bundle failed.
+    #[error(transparent)]
+    Bundle(#[from] BundleError),
+
+    /// Sealing or opening a signed envelope failed.
+    #[cfg(feature = "signed")]
+    #[error(transparent)]
+    Envelope(#[from] EnvelopeError),
+
+    /// Reading or writing the data failed.
+    #[error("An I/O error occurred, {0}.")]
+    Io(#[from] io::Error),
+
+    /// The string returned by Hyperscan is not valid UTF-8.
+    #[error("The string is not valid UTF-8, {0}.")]
+    Utf8(#[from] Utf8Error),
+
     /// Unknown error code
     #[error("Unknown error code: {0}")]
     Code(ffi::hs_error_t),
 }
 
+/// The result of the Hyperscan API with the typed `Error`.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        use Error::*;
+
+        match (self, other) {
+            #[cfg(feature = "compile")]
+            (CompileError(lhs), CompileError(rhs)) => lhs == rhs,
+            (Unsupported(lhs, lhs_version), Unsupported(rhs, rhs_version)) => lhs == rhs && lhs_version == rhs_version,
+            (SessionLimit(lhs), SessionLimit(rhs)) => lhs == rhs,
+            (Bundle(lhs), Bundle(rhs)) => lhs == rhs,
+            #[cfg(feature = "signed")]
+            (Envelope(lhs), Envelope(rhs)) => lhs == rhs,
+            // the I/O errors are compared by their kinds
+            (Io(lhs), Io(rhs)) => lhs.kind() == rhs.kind(),
+            (Utf8(lhs), Utf8(rhs)) => lhs == rhs,
+            (Code(lhs), Code(rhs)) => lhs == rhs,
+            _ => mem::discriminant(self) == mem::discriminant(other),
+        }
+    }
+}
+
+impl Error {
+    /// Returns `true` if a memory allocation failed, which may be recovered by releasing memory and retrying.
+    pub fn is_alloc_failure(&self) -> bool {
+        matches!(self, Error::NoMem | Error::BadAlloc)
+    }
+
+    /// Recover the typed error from the `anyhow::Error` returned by `Builder::build()`,
+    /// the other failures, e.g. an expression with a NUL character, are reported as `Error::Invalid`.
+    #[cfg(feature = "compile")]
+    pub(crate) fn from_build(err: anyhow::Error) -> Self {
+        err.downcast().unwrap_or(Error::Invalid)
+    }
+}
+
 impl From<ffi::hs_error_t> for Error {
     fn from(err: ffi::hs_error_t) -> Self {
         use Error::*;
@@ -101,14 +181,17 @@ where
 
     fn ok(self) -> Result<Self::Output, Self::Error>;
 
+    #[track_caller]
     fn map<U, F: FnOnce(Self::Output) -> U>(self, op: F) -> Result<U, Self::Error> {
         self.ok().map(op)
     }
 
+    #[track_caller]
     fn and_then<U, F: FnOnce(Self::Output) -> Result<U, Self::Error>>(self, op: F) -> Result<U, Self::Error> {
         self.ok().and_then(op)
     }
 
+    #[track_caller]
     fn expect(self, msg: &str) -> Self::Output {
         self.ok().expect(msg)
     }
@@ -116,13 +199,18 @@ where
 
 impl AsResult for ffi::hs_error_t {
     type Output = ();
-    type Error = anyhow::Error;
+    type Error = Error;
 
+    #[track_caller]
     fn ok(self) -> Result<Self::Output, Self::Error> {
         if self == ffi::HS_SUCCESS as ffi::hs_error_t {
             Ok(())
         } else {
-            Err(Error::from(self).into())
+            let err = Error::from(self);
+
+            diagnostics::failed(self, &err, Location::caller());
+
+            Err(err)
         }
     }
 }
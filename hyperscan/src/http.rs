@@ -0,0 +1,180 @@
+//! Scanning the parsed HTTP messages with the section attribution.
+//!
+//! The `HttpScanner` scans the start line and the header values of a request or response in block mode,
+//! and streams the body through a stream mode database, so a large body is not buffered in memory.
+//! The matches are reported with the `Section` they were found in, the offsets of a header match are relative
+//! to the header value, and the offsets of a body match are relative to the start of the body.
+//!
+//! The messages should be parsed by the caller, e.g. with `httparse` or `hyper`,
+//! this module is a building block for the WAF-style scanning.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use hyperscan::{prelude::*, http::{HttpScanner, Section}};
+//! let head: BlockDatabase = pattern! {"select"; CASELESS | SOM_LEFTMOST}.build().unwrap();
+//! let body: StreamingDatabase = pattern! {"select"; CASELESS | SOM_LEFTMOST}.build().unwrap();
+//! let mut s = head.alloc_scratch().unwrap();
+//! body.realloc_scratch(&mut s).unwrap();
+//!
+//! let headers = [("Host", &b"example.com"[..]), ("X-Query", &b"1 union SELECT"[..])];
+//! let mut matches = vec![];
+//!
+//! HttpScanner::new(&head, &body)
+//!     .scan(
+//!         b"POST /search HTTP/1.1",
+//!         &headers,
+//!         &mut &b"q=select * from users"[..],
+//!         &s,
+//!         |section, _id, from, to| {
+//!             matches.push((section.to_string(), from, to));
+//!             Matching::Continue
+//!         },
+//!     )
+//!     .unwrap();
+//!
+//! assert_eq!(
+//!     matches,
+//!     vec![("header X-Query".to_string(), 8, 14), ("body".to_string(), 2, 8)]
+//! );
+//! ```
+use std::fmt;
+use std::io::Read;
+
+use anyhow::Result;
+
+use crate::common::{BlockDatabase, StreamingDatabase};
+use crate::runtime::{read_chunk, Matching, ScanOutcome, ScratchRef, SCAN_BUF_SIZE};
+
+/// The section of an HTTP message where a match was found.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Section<'a> {
+    /// The request line or the status line.
+    StartLine,
+    /// The value of the header with the name.
+    Header(&'a str),
+    /// The message body.
+    Body,
+}
+
+impl fmt::Display for Section<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::StartLine => f.write_str("start line"),
+            Section::Header(name) => write!(f, "header {}", name),
+            Section::Body => f.write_str("body"),
+        }
+    }
+}
+
+/// A scanner of the HTTP messages, which scans the head in block mode and the body in stream mode.
+///
+/// The scratch space passed to the scanner must be allocated for both databases,
+/// e.g. with `DatabaseRef::realloc_scratch()`.
+pub struct HttpScanner<'a> {
+    head: &'a BlockDatabase,
+    body: &'a StreamingDatabase,
+}
+
+impl<'a> HttpScanner<'a> {
+    /// Constructs a scanner with the databases of the message head and body.
+    pub fn new(head: &'a BlockDatabase, body: &'a StreamingDatabase) -> Self {
+        HttpScanner { head, body }
+    }
+
+    /// Scan the start line and the headers of a message.
+    pub fn scan_head<F>(
+        &self,
+        start_line: &[u8],
+        headers: &[(&str, &[u8])],
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: FnMut(Section<'_>, u32, u64, u64) -> Matching,
+    {
+        let outcome = self.head.scan(start_line, scratch, |id, from, to, _flags| {
+            on_match_event(Section::StartLine, id, from, to)
+        })?;
+
+        if outcome.is_terminated() {
+            return Ok(outcome);
+        }
+
+        for &(name, value) in headers {
+            let outcome = self.head.scan(value, scratch, |id, from, to, _flags| {
+                on_match_event(Section::Header(name), id, from, to)
+            })?;
+
+            if outcome.is_terminated() {
+                return Ok(outcome);
+            }
+        }
+
+        Ok(ScanOutcome::Completed)
+    }
+
+    /// Stream the body of a message through the stream mode database.
+    pub fn scan_body<R, F>(&self, body: &mut R, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        R: Read,
+        F: FnMut(Section<'_>, u32, u64, u64) -> Matching,
+    {
+        let stream = self.body.open_stream()?;
+        let mut buf = [0; SCAN_BUF_SIZE];
+        let mut callback = |id, from, to, _flags: u32| on_match_event(Section::Body, id, from, to);
+
+        loop {
+            let len = match read_chunk(body, &mut buf, false) {
+                Ok(len) => len,
+                Err(err) => {
+                    stream.free()?;
+
+                    return Err(err.into());
+                }
+            };
+
+            if len == 0 {
+                break;
+            }
+
+            match stream.scan(&buf[..len], scratch, &mut callback) {
+                Ok(ScanOutcome::Completed) => {}
+                Ok(ScanOutcome::Terminated) => {
+                    stream.free()?;
+
+                    return Ok(ScanOutcome::Terminated);
+                }
+                Err(err) => {
+                    stream.free()?;
+
+                    return Err(err.into());
+                }
+            }
+        }
+
+        Ok(stream.close(scratch, callback)?)
+    }
+
+    /// Scan the head and the body of a message.
+    pub fn scan<R, F>(
+        &self,
+        start_line: &[u8],
+        headers: &[(&str, &[u8])],
+        body: &mut R,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        R: Read,
+        F: FnMut(Section<'_>, u32, u64, u64) -> Matching,
+    {
+        let outcome = self.scan_head(start_line, headers, scratch, &mut on_match_event)?;
+
+        if outcome.is_terminated() {
+            return Ok(outcome);
+        }
+
+        self.scan_body(body, scratch, on_match_event)
+    }
+}
@@ -0,0 +1,138 @@
+//! Scanning the JSON documents with the path attribution.
+//!
+//! The `JsonScanner` walks a JSON document, scans the string values (and optionally the object keys)
+//! in block mode, and reports the matches with the [JSON pointer](https://tools.ietf.org/html/rfc6901)
+//! of the scanned string, the match offsets are relative to the string.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use hyperscan::{prelude::*, json::JsonScanner};
+//! let db: BlockDatabase = pattern! {"secret"; SOM_LEFTMOST}.build().unwrap();
+//! let s = db.alloc_scratch().unwrap();
+//! let mut matches = vec![];
+//!
+//! JsonScanner::new(&db)
+//!     .scan_str(
+//!         r#"{"user": {"name": "bob", "tags": ["admin", "top secret"]}, "a/b": "secret"}"#,
+//!         &s,
+//!         |path, _id, from, to| {
+//!             matches.push((path.to_string(), from, to));
+//!             Matching::Continue
+//!         },
+//!     )
+//!     .unwrap();
+//!
+//! matches.sort();
+//!
+//! assert_eq!(
+//!     matches,
+//!     vec![("/a~1b".to_string(), 0, 6), ("/user/tags/1".to_string(), 4, 10)]
+//! );
+//! ```
+use std::fmt::Write;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::common::BlockDatabase;
+use crate::runtime::{Matching, ScanOutcome, ScratchRef};
+
+/// A scanner walking the JSON documents and scanning the strings with a block mode database.
+pub struct JsonScanner<'a> {
+    db: &'a BlockDatabase,
+    keys: bool,
+}
+
+impl<'a> JsonScanner<'a> {
+    /// Constructs a scanner scanning the string values with the database.
+    pub fn new(db: &'a BlockDatabase) -> Self {
+        JsonScanner { db, keys: false }
+    }
+
+    /// Scan the object keys as well, the matches of a key are reported with the pointer of its value.
+    pub fn keys(mut self, yes: bool) -> Self {
+        self.keys = yes;
+        self
+    }
+
+    /// Parse the JSON text and scan the document.
+    pub fn scan_str<F>(&self, json: &str, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        F: FnMut(&str, u32, u64, u64) -> Matching,
+    {
+        let value = serde_json::from_str(json)?;
+
+        self.scan(&value, scratch, on_match_event)
+    }
+
+    /// Scan the JSON document, the matches are passed to the callback with the JSON pointer of the string.
+    pub fn scan<F>(&self, value: &Value, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        F: FnMut(&str, u32, u64, u64) -> Matching,
+    {
+        let mut path = String::new();
+
+        self.walk(value, &mut path, scratch, &mut on_match_event)
+    }
+
+    fn walk<F>(
+        &self,
+        value: &Value,
+        path: &mut String,
+        scratch: &ScratchRef,
+        on_match_event: &mut F,
+    ) -> Result<ScanOutcome>
+    where
+        F: FnMut(&str, u32, u64, u64) -> Matching,
+    {
+        match value {
+            Value::String(s) => self.scan_string(s, path, scratch, on_match_event),
+            Value::Array(values) => {
+                let len = path.len();
+
+                for (i, value) in values.iter().enumerate() {
+                    write!(path, "/{}", i)?;
+
+                    if self.walk(value, path, scratch, on_match_event)?.is_terminated() {
+                        return Ok(ScanOutcome::Terminated);
+                    }
+
+                    path.truncate(len);
+                }
+
+                Ok(ScanOutcome::Completed)
+            }
+            Value::Object(map) => {
+                let len = path.len();
+
+                for (key, value) in map {
+                    path.push('/');
+                    path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+
+                    if self.keys && self.scan_string(key, path, scratch, on_match_event)?.is_terminated() {
+                        return Ok(ScanOutcome::Terminated);
+                    }
+
+                    if self.walk(value, path, scratch, on_match_event)?.is_terminated() {
+                        return Ok(ScanOutcome::Terminated);
+                    }
+
+                    path.truncate(len);
+                }
+
+                Ok(ScanOutcome::Completed)
+            }
+            _ => Ok(ScanOutcome::Completed),
+        }
+    }
+
+    fn scan_string<F>(&self, s: &str, path: &str, scratch: &ScratchRef, on_match_event: &mut F) -> Result<ScanOutcome>
+    where
+        F: FnMut(&str, u32, u64, u64) -> Matching,
+    {
+        Ok(self
+            .db
+            .scan(s, scratch, |id, from, to, _flags| on_match_event(path, id, from, to))?)
+    }
+}
@@ -41,17 +41,43 @@ mod ffi {
     pub use hyperscan_sys::*;
 }
 
+#[cfg(any(feature = "runtime", feature = "chimera"))]
+mod closure;
 mod common;
+pub mod diagnostics;
 mod errors;
 #[cfg(feature = "compile")]
 #[macro_use]
 mod compile;
 #[cfg(feature = "chimera")]
 pub mod chimera;
+#[cfg(feature = "dns")]
+pub mod dns;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(all(feature = "prometheus", feature = "runtime"))]
+pub mod metrics;
+pub mod output;
+#[cfg(feature = "compile")]
+pub mod patterns;
+#[cfg(feature = "compile")]
+pub mod precompile;
+#[cfg(feature = "recompile")]
+pub mod recompile;
 #[cfg(all(feature = "compile", feature = "runtime"))]
 pub mod regex;
 #[cfg(feature = "runtime")]
 mod runtime;
+#[cfg(feature = "tcp")]
+pub mod tcp;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "walk")]
+pub mod walk;
+#[cfg(feature = "watch")]
+pub mod watch;
 
 #[doc(hidden)]
 #[deprecated = "use `BlockMode` instead"]
@@ -66,14 +92,19 @@ pub use crate::common::Streaming;
 #[deprecated = "use `VectoredMode` instead"]
 pub use crate::common::Vectored;
 pub use crate::common::{
-    version, version_str, Block as BlockMode, BlockDatabase, Database, DatabaseRef, Mode,
-    Serialized as SerializedDatabase, Streaming as StreamingMode, StreamingDatabase, Vectored as VectoredMode,
+    runtime_version, version, version_str, Block as BlockMode, BlockDatabase, Bundle, BundleError, Capability,
+    ContainerError, Database, DatabaseHandle, DatabaseInfo, DatabaseRef, DualDatabase, Mode,
+    Serialized as SerializedDatabase, Streaming as StreamingMode, StreamingDatabase, Tier, Vectored as VectoredMode,
     VectoredDatabase,
 };
+#[cfg(feature = "signed")]
+pub use crate::common::{Envelope, EnvelopeError, Signer, Verifier};
+#[cfg(unix)]
+pub use crate::common::{MappedDatabase, SharedDatabase};
 #[doc(hidden)]
 #[deprecated = "use `Error` instead"]
 pub use crate::errors::Error as HsError;
-pub use crate::errors::Error;
+pub use crate::errors::{Error, Result};
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "compile")] {
@@ -87,16 +118,30 @@ cfg_if::cfg_if! {
         #[deprecated = "use `PatternFlags` instead"]
         pub use crate::compile::Flags as CompileFlags;
         pub use crate::compile::{
-            compile, Builder as DatabaseBuilder, Builder, CpuFeatures, Error as CompileError, ExprExt, ExprInfo,
-            Flags as PatternFlags, Pattern, Patterns, Platform, PlatformRef, SomHorizon, Tune,
+            compile, BuildStats, Builder as DatabaseBuilder, Builder, CompileCache, CpuFeatures, Error as CompileError, ExprExt,
+            ExprInfo, Flags as PatternFlags, Pattern, Patterns, Platform, PlatformRef, SomHorizon, Tune,
         };
         #[cfg(feature = "literal")]
         pub use crate::compile::{Literal, LiteralFlags, Literals};
     }
 }
 
+#[cfg(feature = "chimera")]
+pub use crate::chimera::Database as ChimeraDatabase;
+#[cfg(all(feature = "compile", feature = "runtime"))]
+pub use crate::regex::Regex;
+#[cfg(all(feature = "runtime", feature = "parallel"))]
+pub use crate::runtime::Sharded;
 #[cfg(feature = "runtime")]
-pub use crate::runtime::{MatchEventHandler, Matching, Scratch, ScratchRef, Stream, StreamRef};
+pub use crate::runtime::{
+    clear_alloc_failure_hook, matched_bytes, set_alloc_failure_hook, BufferedStream, CancellationToken, ChunkMatch,
+    Comparison, ComparisonReport, Distribution, FollowScanner, Match, MatchEventHandler, MatchHistogram, MatchSink,
+    MatchStats, Matching, MinWidth, OwnedScratch, PatternFilter, PatternStats, PooledScratch, Prefiltered,
+    PreparedCallback, RawMatchEventHandler, RecordedEvent, Recorder, Reorder, Replayer, RingBuffer, RuleDiff, Scan,
+    ScanOptions, ScanOutcome, ScopedStream, Scratch, ScratchPool, ScratchRef, SelfTest, SessionTable, SingleMatch,
+    Stream, StreamRef, StreamScanner, StreamStats, Suppression, Suppressor, Throttle, Throttled, TrackedStream,
+    Validators, VectoredBuffer, Window, MATCH_AT_EOD,
+};
 
 /// The `hyperscan` Prelude
 pub mod prelude {
@@ -106,6 +151,9 @@ pub mod prelude {
     #[cfg(feature = "runtime")]
     pub use crate::{Matching, Scratch, Stream};
 
+    #[cfg(feature = "chimera")]
+    pub use crate::ChimeraDatabase;
+
     pub use crate::{BlockDatabase, Database, Mode, StreamingDatabase, VectoredDatabase};
 }
 
@@ -0,0 +1,241 @@
+//! Prometheus metrics of the scans and streams.
+//!
+//! The `Metrics` exposes the counters, histograms and gauges which are ready to register into an existing registry,
+//! the scans and streams are recorded once the metrics are installed, and the matches are counted
+//! by the rule id with the wrapped match event callbacks.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use hyperscan::{metrics::Metrics, prelude::*};
+//! # use prometheus::Registry;
+//! let registry = Registry::new();
+//! let metrics = Metrics::new().unwrap();
+//!
+//! metrics.register(&registry).unwrap();
+//!
+//! let metrics = metrics.install().unwrap();
+//!
+//! let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+//! let s = db.alloc_scratch().unwrap();
+//!
+//! db.scan("foo test bar", &s, metrics.wrap(|_, _, _, _| Matching::Continue)).unwrap();
+//!
+//! let families = registry.gather();
+//! let scanned = families.iter().find(|m| m.get_name() == "hyperscan_scanned_bytes_total").unwrap();
+//!
+//! assert_eq!(scanned.get_metric()[0].get_counter().get_value(), 12.0);
+//! ```
+use std::collections::HashMap;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use prometheus::{HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry};
+
+use crate::ffi;
+use crate::runtime::Matching;
+
+const NAMESPACE: &str = "hyperscan";
+
+/// The number of shards of the open streams, so the streams opened by the threads rarely contend on the same lock.
+const STREAM_SHARDS: usize = 16;
+
+static INSTALLED: AtomicPtr<Metrics> = AtomicPtr::new(ptr::null_mut());
+
+/// The Prometheus metrics of the scans and streams.
+pub struct Metrics {
+    scanned_bytes: IntCounterVec,
+    scan_latency: HistogramVec,
+    matches: IntCounterVec,
+    open_streams: IntGauge,
+    stream_memory: IntGauge,
+    rules: RwLock<HashMap<u32, IntCounter>>,
+    stream_sizes: RwLock<HashMap<usize, i64>>,
+    streams: [Mutex<HashMap<usize, i64>>; STREAM_SHARDS],
+}
+
+impl Metrics {
+    /// Constructs the metrics in the `hyperscan` namespace.
+    ///
+    /// - `hyperscan_scanned_bytes_total`: the scanned bytes by the scan mode.
+    /// - `hyperscan_scan_duration_seconds`: the scan latency by the scan mode.
+    /// - `hyperscan_matches_total`: the matches by the rule id.
+    /// - `hyperscan_open_streams`: the number of the open streams.
+    /// - `hyperscan_stream_memory_bytes`: the stream state memory of the open streams.
+    pub fn new() -> Result<Self> {
+        Ok(Metrics {
+            scanned_bytes: IntCounterVec::new(
+                Opts::new("scanned_bytes_total", "The scanned bytes by the scan mode.").namespace(NAMESPACE),
+                &["mode"],
+            )?,
+            scan_latency: HistogramVec::new(
+                HistogramOpts::new("scan_duration_seconds", "The scan latency by the scan mode.").namespace(NAMESPACE),
+                &["mode"],
+            )?,
+            matches: IntCounterVec::new(
+                Opts::new("matches_total", "The matches by the rule id.").namespace(NAMESPACE),
+                &["rule"],
+            )?,
+            open_streams: IntGauge::with_opts(
+                Opts::new("open_streams", "The number of the open streams.").namespace(NAMESPACE),
+            )?,
+            stream_memory: IntGauge::with_opts(
+                Opts::new("stream_memory_bytes", "The stream state memory of the open streams.").namespace(NAMESPACE),
+            )?,
+            rules: RwLock::new(HashMap::new()),
+            stream_sizes: RwLock::new(HashMap::new()),
+            streams: Default::default(),
+        })
+    }
+
+    /// Register the metrics into the registry.
+    pub fn register(&self, registry: &Registry) -> Result<()> {
+        registry.register(Box::new(self.scanned_bytes.clone()))?;
+        registry.register(Box::new(self.scan_latency.clone()))?;
+        registry.register(Box::new(self.matches.clone()))?;
+        registry.register(Box::new(self.open_streams.clone()))?;
+        registry.register(Box::new(self.stream_memory.clone()))?;
+
+        Ok(())
+    }
+
+    /// Install the metrics to record the scans and streams of the process, it can only be installed once.
+    pub fn install(self) -> Result<&'static Metrics> {
+        let metrics = Box::into_raw(Box::new(self));
+
+        match INSTALLED.compare_exchange(ptr::null_mut(), metrics, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => Ok(unsafe { &*metrics }),
+            Err(_) => {
+                drop(unsafe { Box::from_raw(metrics) });
+
+                Err(anyhow!("metrics already installed"))
+            }
+        }
+    }
+
+    /// Returns the installed metrics.
+    pub fn installed() -> Option<&'static Metrics> {
+        unsafe { INSTALLED.load(Ordering::Acquire).as_ref() }
+    }
+
+    /// Record a match of the rule.
+    ///
+    /// The counter of the rule is looked up by its label on the first match, and cached for the later matches.
+    pub fn record_match(&self, id: u32) {
+        if let Some(counter) = self.rules.read().unwrap().get(&id) {
+            return counter.inc();
+        }
+
+        self.rules
+            .write()
+            .unwrap()
+            .entry(id)
+            .or_insert_with(|| self.matches.with_label_values(&[&id.to_string()]))
+            .inc()
+    }
+
+    /// Wrap a match event callback that counts every match by the rule id before delivering it.
+    pub fn wrap<'a, F>(&'a self, mut on_match_event: F) -> impl FnMut(u32, u64, u64, u32) -> Matching + 'a
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching + 'a,
+    {
+        move |id, from, to, flags| {
+            self.record_match(id);
+
+            on_match_event(id, from, to, flags)
+        }
+    }
+
+    fn shard(&self, s: *mut ffi::hs_stream_t) -> &Mutex<HashMap<usize, i64>> {
+        // skip the low bits of the address, which are always zero with the alignment of the allocator.
+        &self.streams[(s as usize >> 4) % STREAM_SHARDS]
+    }
+
+    fn stream_size<F>(&self, db: *const ffi::hs_database_t, size: F) -> i64
+    where
+        F: FnOnce() -> crate::errors::Result<usize>,
+    {
+        if let Some(&size) = self.stream_sizes.read().unwrap().get(&(db as usize)) {
+            return size;
+        }
+
+        let size = size().unwrap_or_default() as i64;
+
+        self.stream_sizes.write().unwrap().insert(db as usize, size);
+
+        size
+    }
+
+    fn stream_opened(&self, s: *mut ffi::hs_stream_t, size: i64) {
+        self.shard(s).lock().unwrap().insert(s as usize, size);
+        self.open_streams.inc();
+        self.stream_memory.add(size);
+    }
+
+    fn stream_closed(&self, s: *mut ffi::hs_stream_t) {
+        if let Some(size) = self.shard(s).lock().unwrap().remove(&(s as usize)) {
+            self.open_streams.dec();
+            self.stream_memory.sub(size);
+        }
+    }
+}
+
+/// Record the scanned bytes and the latency of the scan.
+pub(crate) fn scan<F>(mode: &str, len: usize, f: F) -> ffi::hs_error_t
+where
+    F: FnOnce() -> ffi::hs_error_t,
+{
+    let metrics = match Metrics::installed() {
+        Some(metrics) => metrics,
+        None => return f(),
+    };
+
+    let start = Instant::now();
+    let code = f();
+
+    metrics.scanned_bytes.with_label_values(&[mode]).inc_by(len as u64);
+    metrics
+        .scan_latency
+        .with_label_values(&[mode])
+        .observe(start.elapsed().as_secs_f64());
+
+    code
+}
+
+/// Record an opened stream with the size of its state, which is cached by the database.
+pub(crate) fn stream_opened<F>(db: *const ffi::hs_database_t, s: *mut ffi::hs_stream_t, size: F)
+where
+    F: FnOnce() -> crate::errors::Result<usize>,
+{
+    if let Some(metrics) = Metrics::installed() {
+        metrics.stream_opened(s, metrics.stream_size(db, size))
+    }
+}
+
+/// Record a stream copied from another stream.
+pub(crate) fn stream_copied(from: *mut ffi::hs_stream_t, to: *mut ffi::hs_stream_t) {
+    if let Some(metrics) = Metrics::installed() {
+        let size = metrics.shard(from).lock().unwrap().get(&(from as usize)).copied();
+
+        if let Some(size) = size {
+            metrics.stream_opened(to, size)
+        }
+    }
+}
+
+/// Record a closed stream.
+pub(crate) fn stream_closed(s: *mut ffi::hs_stream_t) {
+    if let Some(metrics) = Metrics::installed() {
+        metrics.stream_closed(s)
+    }
+}
+
+/// Forget the cached stream size of a freed database, its address may be reused by another one.
+pub(crate) fn database_freed(db: *mut ffi::hs_database_t) {
+    if let Some(metrics) = Metrics::installed() {
+        metrics.stream_sizes.write().unwrap().remove(&(db as usize));
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use anyhow::Result;
+
+const HEADERS: &[&str] = &["source", "pattern_id", "pattern_name", "from", "to", "matched"];
+
+/// A writer exporting the match events as CSV records, for the spreadsheet triage and downstream ETL.
+///
+/// Each record has the columns `source,pattern_id,pattern_name,from,to,matched`,
+/// the source is the file path or stream id, the pattern name is empty if it is unknown,
+/// and the matched text is empty if the scanned data is not available.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, output::CsvWriter};
+/// let db: BlockDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+///
+/// let mut csv = CsvWriter::new(vec![]).unwrap();
+/// csv.name(0, "test");
+///
+/// let data = "some test data";
+///
+/// db.scan(data, &s, |id, from, to, _| {
+///     csv.write_match_in("input.txt", data.as_bytes(), id, from, to).unwrap();
+///     Matching::Continue
+/// }).unwrap();
+///
+/// let buf = csv.into_inner().unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(buf).unwrap(),
+///     "source,pattern_id,pattern_name,from,to,matched\ninput.txt,0,test,5,9,test\n"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+    writer: csv::Writer<W>,
+    names: HashMap<u32, String>,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Constructs a writer and writes the header record.
+    pub fn new(w: W) -> Result<Self> {
+        let mut writer = csv::Writer::from_writer(w);
+
+        writer.write_record(HEADERS)?;
+
+        Ok(CsvWriter {
+            writer,
+            names: HashMap::new(),
+        })
+    }
+
+    /// Set the name of the pattern id.
+    pub fn name<S: Into<String>>(&mut self, id: u32, name: S) -> &mut Self {
+        self.names.insert(id, name.into());
+        self
+    }
+
+    /// Set the names of the curated rules.
+    #[cfg(feature = "compile")]
+    pub fn rules(&mut self, rules: &[crate::patterns::Rule]) -> &mut Self {
+        for rule in rules {
+            self.name(rule.id as u32, rule.name);
+        }
+
+        self
+    }
+
+    /// Write a record of the match event without the matched text.
+    pub fn write_match(&mut self, source: &str, id: u32, from: u64, to: u64) -> Result<()> {
+        self.write_record(source, id, from, to, "")
+    }
+
+    /// Write a record of the match event with the matched text of the scanned data.
+    pub fn write_match_in(&mut self, source: &str, data: &[u8], id: u32, from: u64, to: u64) -> Result<()> {
+        let matched = data.get(from as usize..to as usize).unwrap_or_default();
+
+        self.write_record(source, id, from, to, &String::from_utf8_lossy(matched))
+    }
+
+    /// Flush the buffered records to the underlying writer.
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush().map_err(|err| err.into())
+    }
+
+    /// Flush the buffered records and returns the underlying writer.
+    pub fn into_inner(self) -> Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(|err| anyhow::anyhow!("{}", err.error()))
+    }
+
+    fn write_record(&mut self, source: &str, id: u32, from: u64, to: u64, matched: &str) -> Result<()> {
+        let name = self.names.get(&id).map(String::as_str).unwrap_or_default();
+
+        self.writer
+            .write_record(&[
+                source,
+                &id.to_string(),
+                name,
+                &from.to_string(),
+                &to.to_string(),
+                matched,
+            ])
+            .map_err(|err| err.into())
+    }
+}
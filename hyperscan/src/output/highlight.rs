@@ -0,0 +1,268 @@
+use std::io::{self, Write};
+use std::ops::Range;
+
+const DEFAULT_STYLE: &str = "1;31";
+
+/// A renderer highlighting the matched regions of the scanned data with the ANSI escape sequences.
+///
+/// The whole data is rendered by default, or only the matched lines with the surrounding context lines
+/// like `grep -A`, `-B` or `-C`, the non-adjacent groups of lines are separated by `--`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, output::Highlighter};
+/// let db: BlockDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+///
+/// let data = "foo\nsome test data\nbar\nbaz\n";
+/// let mut matches = vec![];
+///
+/// db.scan(data, &s, |_, from, to, _| {
+///     matches.push(from as usize..to as usize);
+///     Matching::Continue
+/// }).unwrap();
+///
+/// let highlighter = Highlighter::new().context(1).line_numbers(true);
+///
+/// assert_eq!(
+///     highlighter.render_to_string(data.as_bytes(), &matches),
+///     "1-foo\n2:some \x1b[1;31mtest\x1b[0m data\n3-bar\n"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Highlighter {
+    style: String,
+    colored: bool,
+    context: Option<(usize, usize)>,
+    line_numbers: bool,
+}
+
+impl Default for Highlighter {
+    fn default() -> Self {
+        Highlighter {
+            style: DEFAULT_STYLE.to_owned(),
+            colored: true,
+            context: None,
+            line_numbers: false,
+        }
+    }
+}
+
+impl Highlighter {
+    /// Constructs a renderer highlighting the matches in bold red.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the SGR parameters of the highlighted regions, e.g. `"1;31"` for bold red.
+    pub fn style<S: Into<String>>(mut self, style: S) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// Enable or disable the escape sequences, e.g. when the output is not a terminal.
+    pub fn colored(mut self, colored: bool) -> Self {
+        self.colored = colored;
+        self
+    }
+
+    /// Only render the matched lines with the number of lines before and after them, like `grep -C`.
+    pub fn context(mut self, lines: usize) -> Self {
+        self.context = Some((lines, lines));
+        self
+    }
+
+    /// Only render the matched lines with the number of lines before them, like `grep -B`.
+    pub fn before_context(mut self, lines: usize) -> Self {
+        self.context = Some((lines, self.context.map_or(0, |(_, after)| after)));
+        self
+    }
+
+    /// Only render the matched lines with the number of lines after them, like `grep -A`.
+    pub fn after_context(mut self, lines: usize) -> Self {
+        self.context = Some((self.context.map_or(0, |(before, _)| before), lines));
+        self
+    }
+
+    /// Prefix the lines with the line numbers, followed by `:` for the matched lines or `-` for the context lines.
+    pub fn line_numbers(mut self, enabled: bool) -> Self {
+        self.line_numbers = enabled;
+        self
+    }
+
+    /// Render the data with the matched regions highlighted to a `String`.
+    pub fn render_to_string(&self, data: &[u8], matches: &[Range<usize>]) -> String {
+        let mut buf = vec![];
+
+        self.render(&mut buf, data, matches).expect("write to buffer");
+
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    /// Render the data with the matched regions highlighted to the writer.
+    pub fn render<W: Write>(&self, w: &mut W, data: &[u8], matches: &[Range<usize>]) -> io::Result<()> {
+        let matches = merge(data.len(), matches);
+        let lines = split_lines(data);
+
+        let line_of = |pos: usize| lines.partition_point(|line| line.end <= pos);
+
+        let mut matched = vec![false; lines.len()];
+
+        for m in &matches {
+            matched[line_of(m.start)..=line_of(m.end - 1)]
+                .iter_mut()
+                .for_each(|b| *b = true);
+        }
+
+        let selected = match self.context {
+            None => vec![true; lines.len()],
+            Some((before, after)) => {
+                let mut selected = vec![false; lines.len()];
+
+                for (i, _) in matched.iter().enumerate().filter(|(_, &b)| b) {
+                    let end = (i + after + 1).min(lines.len());
+
+                    selected[i.saturating_sub(before)..end]
+                        .iter_mut()
+                        .for_each(|b| *b = true);
+                }
+
+                selected
+            }
+        };
+
+        let mut last = None;
+
+        for (i, line) in lines.iter().enumerate().filter(|&(i, _)| selected[i]) {
+            if self.context.is_some() && last.map_or(false, |last| last + 1 != i) {
+                w.write_all(b"--\n")?;
+            }
+
+            last = Some(i);
+
+            if self.line_numbers {
+                write!(w, "{}{}", i + 1, if matched[i] { ':' } else { '-' })?;
+            }
+
+            let (content, eol) = match data[line.clone()].strip_suffix(b"\n") {
+                Some(content) => (line.start..line.start + content.len(), true),
+                None => (line.clone(), false),
+            };
+
+            self.write_line(w, data, content, &matches)?;
+
+            if eol || self.context.is_some() {
+                w.write_all(b"\n")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_line<W: Write>(
+        &self,
+        w: &mut W,
+        data: &[u8],
+        line: Range<usize>,
+        matches: &[Range<usize>],
+    ) -> io::Result<()> {
+        let mut pos = line.start;
+
+        for m in matches.iter().filter(|m| m.start < line.end && m.end > line.start) {
+            let start = m.start.max(line.start);
+            let end = m.end.min(line.end);
+
+            w.write_all(&data[pos..start])?;
+
+            if self.colored {
+                write!(w, "\x1b[{}m", self.style)?;
+                w.write_all(&data[start..end])?;
+                w.write_all(b"\x1b[0m")?;
+            } else {
+                w.write_all(&data[start..end])?;
+            }
+
+            pos = end;
+        }
+
+        w.write_all(&data[pos..line.end])
+    }
+}
+
+/// Sort the matched regions, and merge the overlapping or adjacent ones.
+pub(super) fn merge(len: usize, matches: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut ranges = matches
+        .iter()
+        .map(|m| m.start.min(len)..m.end.min(len))
+        .filter(|m| m.start < m.end)
+        .collect::<Vec<_>>();
+
+    ranges.sort_by_key(|m| m.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+
+    for m in ranges {
+        match merged.last_mut() {
+            Some(last) if m.start <= last.end => last.end = last.end.max(m.end),
+            _ => merged.push(m),
+        }
+    }
+
+    merged
+}
+
+/// Split the data into lines, each line includes the trailing newline.
+pub(super) fn split_lines(data: &[u8]) -> Vec<Range<usize>> {
+    let mut lines = vec![];
+    let mut start = 0;
+
+    for (i, _) in data.iter().enumerate().filter(|(_, &b)| b == b'\n') {
+        lines.push(start..i + 1);
+        start = i + 1;
+    }
+
+    if start < data.len() {
+        lines.push(start..data.len());
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_whole() {
+        let highlighter = Highlighter::new().style("4");
+
+        assert_eq!(
+            highlighter.render_to_string(b"foo bar\nbaz", &[4..9, 0..1, 5..6]),
+            "\x1b[4mf\x1b[0moo \x1b[4mbar\x1b[0m\n\x1b[4mb\x1b[0maz"
+        );
+    }
+
+    #[test]
+    fn test_render_context() {
+        let data = b"1\n2\nfoo\n4\n5\n6\nbar\n8";
+        let highlighter = Highlighter::new().colored(false).context(1).line_numbers(true);
+
+        assert_eq!(
+            highlighter.render_to_string(data, &[4..7, 14..17]),
+            "2-2\n3:foo\n4-4\n--\n6-6\n7:bar\n8-8\n"
+        );
+        assert_eq!(highlighter.render_to_string(data, &[]), "");
+
+        let highlighter = Highlighter::new().colored(false).after_context(1).line_numbers(true);
+
+        assert_eq!(
+            highlighter.render_to_string(data, &[4..7, 14..17]),
+            "3:foo\n4-4\n--\n7:bar\n8-8\n"
+        );
+
+        let highlighter = Highlighter::new().colored(false).before_context(2);
+
+        assert_eq!(highlighter.render_to_string(data, &[4..7]), "1\n2\nfoo\n");
+    }
+}
@@ -0,0 +1,162 @@
+use std::ops::Range;
+
+use crate::output::highlight::{merge, split_lines};
+
+/// A matched line with the surrounding context lines, the lines don't include the trailing newlines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineMatch<'a> {
+    /// The 1-based line number of the matched line.
+    pub line_number: usize,
+    /// The matched line.
+    pub line: &'a [u8],
+    /// The matched regions relative to the start of the line.
+    pub matches: Vec<Range<usize>>,
+    /// The context lines before the matched line.
+    pub before: Vec<&'a [u8]>,
+    /// The context lines after the matched line.
+    pub after: Vec<&'a [u8]>,
+}
+
+/// Group the matches by lines with the surrounding context lines like `grep -A`, `-B` or `-C`.
+///
+/// The context lines of a matched line stop at the adjacent matched lines,
+/// which are reported as the separated `LineMatch`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, output::LineContext};
+/// let db: BlockDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+///
+/// let data = "foo\nsome test data\nbar\nbaz\n";
+/// let mut matches = vec![];
+///
+/// db.scan(data, &s, |_, from, to, _| {
+///     matches.push(from as usize..to as usize);
+///     Matching::Continue
+/// }).unwrap();
+///
+/// let lines = LineContext::new().before(1).after(2).lines(data.as_bytes(), &matches);
+///
+/// assert_eq!(lines.len(), 1);
+/// assert_eq!(lines[0].line_number, 2);
+/// assert_eq!(lines[0].line, b"some test data");
+/// assert_eq!(lines[0].matches, vec![5..9]);
+/// assert_eq!(lines[0].before, vec![b"foo"]);
+/// assert_eq!(lines[0].after, vec![b"bar", b"baz"]);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LineContext {
+    before: usize,
+    after: usize,
+}
+
+impl LineContext {
+    /// Constructs a grouping without the context lines.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of lines before and after the matched lines, like `grep -C`.
+    pub fn context(self, lines: usize) -> Self {
+        self.before(lines).after(lines)
+    }
+
+    /// Set the number of lines before the matched lines, like `grep -B`.
+    pub fn before(mut self, lines: usize) -> Self {
+        self.before = lines;
+        self
+    }
+
+    /// Set the number of lines after the matched lines, like `grep -A`.
+    pub fn after(mut self, lines: usize) -> Self {
+        self.after = lines;
+        self
+    }
+
+    /// Group the matched regions of the data by lines, in the order of the line numbers.
+    pub fn lines<'a>(&self, data: &'a [u8], matches: &[Range<usize>]) -> Vec<LineMatch<'a>> {
+        let matches = merge(data.len(), matches);
+        let lines = split_lines(data);
+        let line_of = |pos: usize| lines.partition_point(|line| line.end <= pos);
+        let content = |i: usize| {
+            let line = &data[lines[i].clone()];
+
+            line.strip_suffix(b"\n").unwrap_or(line)
+        };
+
+        let mut matched = vec![vec![]; lines.len()];
+
+        for m in &matches {
+            for (i, line_matches) in matched
+                .iter_mut()
+                .enumerate()
+                .take(line_of(m.end - 1) + 1)
+                .skip(line_of(m.start))
+            {
+                let line = &lines[i];
+                let end = line.start + content(i).len();
+
+                line_matches.push(m.start.max(line.start) - line.start..m.end.min(end) - line.start);
+            }
+        }
+
+        let is_matched = |i: usize| !matched[i].is_empty();
+
+        (0..lines.len())
+            .filter(|&i| is_matched(i))
+            .map(|i| {
+                let first = (i.saturating_sub(self.before)..i)
+                    .rev()
+                    .take_while(|&j| !is_matched(j))
+                    .last()
+                    .unwrap_or(i);
+                let last = (i + 1..(i + self.after + 1).min(lines.len()))
+                    .take_while(|&j| !is_matched(j))
+                    .last()
+                    .unwrap_or(i);
+
+                LineMatch {
+                    line_number: i + 1,
+                    line: content(i),
+                    matches: matched[i].clone(),
+                    before: (first..i).map(content).collect(),
+                    after: (i + 1..=last).map(content).collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let data = b"1\n2\nfoo\n4\n5\nbar\n7\nfoo\nbar";
+        let lines = LineContext::new()
+            .context(2)
+            .lines(data, &[4..7, 12..15, 18..21, 22..25]);
+
+        assert_eq!(
+            lines.iter().map(|m| m.line_number).collect::<Vec<_>>(),
+            vec![3, 6, 8, 9]
+        );
+        assert_eq!(lines[0].before, vec![b"1", b"2"]);
+        assert_eq!(lines[0].after, vec![b"4", b"5"]);
+        assert_eq!(lines[1].before, vec![b"4", b"5"]);
+        assert_eq!(lines[1].after, vec![b"7"]);
+        assert_eq!(lines[2].before, vec![b"7"]);
+        assert!(lines[2].after.is_empty());
+        assert!(lines[3].before.is_empty());
+        assert_eq!(lines[3].matches, vec![0..3]);
+
+        let lines = LineContext::new().lines(b"foo\nbar\n", &[2..5]);
+
+        assert_eq!(lines[0].matches, vec![2..3]);
+        assert_eq!(lines[1].matches, vec![0..1]);
+        assert!(lines[0].before.is_empty() && lines[0].after.is_empty());
+    }
+}
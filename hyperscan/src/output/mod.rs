@@ -0,0 +1,14 @@
+//! Rendering and exporting the matches for the terminal tooling and reports.
+#[cfg(feature = "csv")]
+mod csv;
+mod highlight;
+mod lines;
+#[cfg(feature = "sarif")]
+mod sarif;
+
+#[cfg(feature = "csv")]
+pub use self::csv::CsvWriter;
+pub use self::highlight::Highlighter;
+pub use self::lines::{LineContext, LineMatch};
+#[cfg(feature = "sarif")]
+pub use self::sarif::SarifWriter;
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use super::highlight::split_lines;
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A writer producing a [SARIF](https://sarifweb.azurewebsites.net/) log of the matches.
+///
+/// The patterns are mapped to the rules of the tool, and the matches to the results
+/// located by the artifact URI and the byte offsets, so the log can be consumed by the existing SARIF tooling,
+/// e.g. the code scanning of GitHub.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, output::SarifWriter, patterns::pii};
+/// let db: BlockDatabase = pii::patterns().build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+///
+/// let mut sarif = SarifWriter::new("scanner").version("1.0.0");
+/// sarif.add_rules(pii::RULES);
+///
+/// let data = "contact: john@example.com\n";
+///
+/// db.scan(data, &s, |id, from, to, _| {
+///     sarif.add_match_in("contact.txt", data.as_bytes(), id, from, to);
+///     Matching::Continue
+/// }).unwrap();
+///
+/// let log = sarif.to_json();
+/// let result = &log["runs"][0]["results"][0];
+///
+/// assert_eq!(result["ruleId"], "email");
+/// assert_eq!(result["locations"][0]["physicalLocation"]["region"]["startLine"], 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SarifWriter {
+    tool: String,
+    version: Option<String>,
+    rules: Vec<Value>,
+    index: HashMap<u32, (String, usize)>,
+    results: Vec<Value>,
+}
+
+impl SarifWriter {
+    /// Constructs a writer with the name of the tool.
+    pub fn new<S: Into<String>>(tool: S) -> Self {
+        SarifWriter {
+            tool: tool.into(),
+            version: None,
+            rules: vec![],
+            index: HashMap::new(),
+            results: vec![],
+        }
+    }
+
+    /// Set the version of the tool.
+    pub fn version<S: Into<String>>(mut self, version: S) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Returns the number of the results.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns `true` if there is no result.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Add a rule for the pattern id, the name is used as the rule id in the log.
+    pub fn add_rule(&mut self, id: u32, name: &str, description: &str) -> &mut Self {
+        self.push_rule(
+            id,
+            json!({
+                "id": name,
+                "name": name,
+                "shortDescription": { "text": description },
+                "properties": { "patternId": id },
+            }),
+        )
+    }
+
+    /// Add the curated rules with their metadata.
+    #[cfg(feature = "compile")]
+    pub fn add_rules(&mut self, rules: &[crate::patterns::Rule]) -> &mut Self {
+        for rule in rules {
+            self.push_rule(
+                rule.id as u32,
+                json!({
+                    "id": rule.name,
+                    "name": rule.name,
+                    "shortDescription": { "text": rule.description },
+                    "fullDescription": { "text": rule.expression },
+                    "properties": { "patternId": rule.id, "tags": [rule.category] },
+                }),
+            );
+        }
+
+        self
+    }
+
+    /// Add a rule per pattern, named by the pattern id and described by the expression.
+    #[cfg(feature = "compile")]
+    pub fn add_patterns(&mut self, patterns: &crate::compile::Patterns) -> &mut Self {
+        for (i, pattern) in patterns.iter().enumerate() {
+            let id = pattern.id.unwrap_or(i) as u32;
+
+            self.add_rule(id, &format!("pattern-{}", id), &pattern.expression);
+        }
+
+        self
+    }
+
+    /// Add a result of the match in the artifact, located by the byte offsets.
+    pub fn add_match<P: AsRef<Path>>(&mut self, path: P, id: u32, from: u64, to: u64) -> &mut Self {
+        let region = json!({
+            "byteOffset": from,
+            "byteLength": to.saturating_sub(from),
+        });
+
+        self.push_result(path.as_ref(), id, region)
+    }
+
+    /// Add a result of the match in the scanned data of the artifact, located by the lines and the byte offsets,
+    /// with the matched text as the snippet.
+    ///
+    /// The lines of the data are split on each call, use `add_matches_in` for the matches in the same data.
+    pub fn add_match_in<P: AsRef<Path>>(&mut self, path: P, data: &[u8], id: u32, from: u64, to: u64) -> &mut Self {
+        self.add_matches_in(path, data, &[(id, from, to)])
+    }
+
+    /// Add the results of the matches in the scanned data of the artifact, located by the lines and the byte offsets,
+    /// with the matched text as the snippet, the lines of the data are split once for all the matches.
+    pub fn add_matches_in<P: AsRef<Path>>(&mut self, path: P, data: &[u8], matches: &[(u32, u64, u64)]) -> &mut Self {
+        let lines = split_lines(data);
+        let line_of = |pos: usize| lines.partition_point(|line| line.end <= pos) + 1;
+
+        for &(id, from, to) in matches {
+            let from = (from as usize).min(data.len());
+            let to = (to as usize).min(data.len()).max(from);
+
+            let region = json!({
+                "byteOffset": from,
+                "byteLength": to.saturating_sub(from),
+                "startLine": line_of(from),
+                "endLine": line_of(to.saturating_sub(1).max(from)),
+                "snippet": { "text": String::from_utf8_lossy(&data[from..to]) },
+            });
+
+            self.push_result(path.as_ref(), id, region);
+        }
+
+        self
+    }
+
+    /// Returns the SARIF log as a JSON value.
+    pub fn to_json(&self) -> Value {
+        let mut driver = json!({
+            "name": self.tool,
+            "rules": self.rules,
+        });
+
+        if let Some(ref version) = self.version {
+            driver["version"] = json!(version);
+        }
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": SARIF_VERSION,
+            "runs": [{
+                "tool": { "driver": driver },
+                "results": self.results,
+            }],
+        })
+    }
+
+    /// Write the SARIF log to the writer.
+    pub fn write_to<W: Write>(&self, w: W) -> Result<()> {
+        serde_json::to_writer_pretty(w, &self.to_json()).map_err(|err| err.into())
+    }
+
+    fn push_rule(&mut self, id: u32, rule: Value) -> &mut Self {
+        let name = rule["id"].as_str().unwrap_or_default().to_owned();
+
+        self.index.insert(id, (name, self.rules.len()));
+        self.rules.push(rule);
+        self
+    }
+
+    fn push_result(&mut self, path: &Path, id: u32, region: Value) -> &mut Self {
+        let uri = encode_uri(&path.to_string_lossy().replace('\\', "/"));
+        let mut result = json!({
+            "level": "warning",
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": uri },
+                    "region": region,
+                },
+            }],
+        });
+
+        match self.index.get(&id) {
+            Some((name, index)) => {
+                result["ruleId"] = json!(name);
+                result["ruleIndex"] = json!(index);
+                result["message"] = json!({ "text": format!("matched rule {}", name) });
+            }
+            None => {
+                result["ruleId"] = json!(id.to_string());
+                result["message"] = json!({ "text": format!("matched pattern {}", id) });
+            }
+        }
+
+        self.results.push(result);
+        self
+    }
+}
+
+/// Percent-encode the path as a relative URI reference, only the unreserved characters and `/` are kept.
+fn encode_uri(path: &str) -> String {
+    let mut uri = String::with_capacity(path.len());
+
+    for &b in path.as_bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(b as char),
+            _ => {
+                let _ = write!(uri, "%{:02X}", b);
+            }
+        }
+    }
+
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_matches_in() {
+        let mut sarif = SarifWriter::new("scanner");
+
+        sarif.add_matches_in(
+            "dir/my file#1.txt",
+            b"foo\nbar\nbaz",
+            &[(0, 0, 3), (0, 5, 9), (0, 9, 3)],
+        );
+
+        let log = sarif.to_json();
+        let regions = log["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| &result["locations"][0]["physicalLocation"]["region"])
+            .map(|region| {
+                (
+                    region["startLine"].clone(),
+                    region["endLine"].clone(),
+                    region["byteLength"].clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            regions,
+            vec![
+                (json!(1), json!(1), json!(3)),
+                (json!(2), json!(3), json!(4)),
+                (json!(3), json!(3), json!(0)),
+            ]
+        );
+        assert_eq!(
+            log["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "dir/my%20file%231.txt"
+        );
+    }
+}
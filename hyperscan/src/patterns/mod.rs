@@ -0,0 +1,59 @@
+//! Curated pattern collections.
+//!
+//! The collections are versioned, each rule has a stable id, a name and metadata,
+//! they can be compiled directly or merged with the user's own patterns.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use hyperscan::{prelude::*, patterns::pii};
+//! let mut patterns = pii::patterns();
+//! patterns.push(pattern! {"secret"});
+//!
+//! let db: BlockDatabase = patterns.build().unwrap();
+//! let s = db.alloc_scratch().unwrap();
+//! let mut matches = vec![];
+//!
+//! db.scan("mail to john@example.com", &s, |id, _, _, _| {
+//!     matches.push(pii::rule(id as usize).map(|rule| rule.name));
+//!     Matching::Continue
+//! }).unwrap();
+//!
+//! // every end offset of the match is reported
+//! matches.dedup();
+//!
+//! assert_eq!(matches, vec![Some("email")]);
+//! ```
+use crate::compile::{ExprExt, Flags, Pattern, Patterns};
+
+pub mod pii;
+
+/// A curated rule with its metadata.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rule {
+    /// The stable id of the rule.
+    pub id: usize,
+    /// The unique name of the rule.
+    pub name: &'static str,
+    /// The category of the rule.
+    pub category: &'static str,
+    /// The description of the rule.
+    pub description: &'static str,
+    /// The regular expression of the rule.
+    pub expression: &'static str,
+    /// The flags of the expression.
+    pub flags: Flags,
+}
+
+impl Rule {
+    /// Returns the pattern of the rule with its id.
+    pub fn pattern(&self) -> Pattern {
+        Pattern {
+            expression: self.expression.into(),
+            flags: self.flags,
+            id: Some(self.id),
+            ext: ExprExt::default(),
+            som: None,
+        }
+    }
+}
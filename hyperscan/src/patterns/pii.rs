@@ -0,0 +1,133 @@
+//! Personally identifiable information and indicator patterns.
+//!
+//! The ids of the rules start from `ID_BASE`, and the rules are matched with `SOM_LEFTMOST`
+//! to report the start of the matches.
+use crate::compile::{Flags, Patterns};
+use crate::patterns::Rule;
+
+/// The version of the collection, which is changed when any rule is added, removed or modified.
+pub const VERSION: &str = "1.0.0";
+
+/// The first id of the rules in the collection.
+pub const ID_BASE: usize = 1000;
+
+const FLAGS: Flags =
+    Flags::from_bits_truncate(Flags::CASELESS.bits() | Flags::DOTALL.bits() | Flags::SOM_LEFTMOST.bits());
+
+/// US dollar currency amounts, e.g. `$1,234.56`.
+pub const US_CURRENCY: Rule = Rule {
+    id: ID_BASE,
+    name: "us_currency",
+    category: "financial",
+    description: "US dollar currency amount",
+    expression: r"\$[+-]?[0-9]{1,3}(?:,?[0-9]{3})*(?:\.[0-9]{2})",
+    flags: FLAGS,
+};
+
+/// US social security numbers, e.g. `123-45-6789`.
+pub const US_SSN: Rule = Rule {
+    id: ID_BASE + 1,
+    name: "us_ssn",
+    category: "national_id",
+    description: "US social security number",
+    expression: r"[0-8][0-9]{2}(-|\s)?[0-9]{2}(-|\s)[0-9]{4}",
+    flags: FLAGS,
+};
+
+/// Visa credit card numbers.
+pub const VISA: Rule = Rule {
+    id: ID_BASE + 2,
+    name: "credit_card_visa",
+    category: "financial",
+    description: "Visa credit card number",
+    expression: r"\b4\d{3}[\s-]?\d{4}[\s-]?\d{4}[\s-]?\d{4}\b",
+    flags: FLAGS,
+};
+
+/// MasterCard credit card numbers.
+pub const MASTERCARD: Rule = Rule {
+    id: ID_BASE + 3,
+    name: "credit_card_mastercard",
+    category: "financial",
+    description: "MasterCard credit card number",
+    expression: r"\b5[1-5]\d{2}[\s-]?\d{4}[\s-]?\d{4}[\s-]?\d{4}\b",
+    flags: FLAGS,
+};
+
+/// Discover credit card numbers.
+pub const DISCOVER: Rule = Rule {
+    id: ID_BASE + 4,
+    name: "credit_card_discover",
+    category: "financial",
+    description: "Discover credit card number",
+    expression: r"\b6011[\s-]?\d{4}[\s-]?\d{4}[\s-]?\d{4}\b",
+    flags: FLAGS,
+};
+
+/// American Express credit card numbers.
+pub const AMEX: Rule = Rule {
+    id: ID_BASE + 5,
+    name: "credit_card_amex",
+    category: "financial",
+    description: "American Express credit card number",
+    expression: r"\b3[47]\d{2}[\s-]?\d{6}[\s-]?\d{5}\b",
+    flags: FLAGS,
+};
+
+/// Email addresses, as defined by RFC 5322.
+pub const EMAIL: Rule = Rule {
+    id: ID_BASE + 6,
+    name: "email",
+    category: "contact",
+    description: "Email address",
+    expression: r#"(?:[a-z0-9!#$%&'*+/=?^_`{|}~-]+(?:\.[a-z0-9!#$%&'*+/=?^_`{|}~-]+)*|"(?:[\x01-\x08\x0b\x0c\x0e-\x1f\x21\x23-\x5b\x5d-\x7f]|\\[\x01-\x09\x0b\x0c\x0e-\x7f])*")@(?:(?:[a-z0-9](?:[a-z0-9-]*[a-z0-9])?\.)+[a-z0-9](?:[a-z0-9-]*[a-z0-9])?|\[(?:(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)\.){3}(?:25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?|[a-z0-9-]*[a-z0-9]:(?:[\x01-\x08\x0b\x0c\x0e-\x1f\x21-\x5a\x53-\x7f]|\\[\x01-\x09\x0b\x0c\x0e-\x7f])+)\])"#,
+    flags: FLAGS,
+};
+
+/// IPv4 addresses in dotted decimal notation.
+pub const IPV4: Rule = Rule {
+    id: ID_BASE + 7,
+    name: "ipv4_address",
+    category: "network",
+    description: "IPv4 address",
+    expression: r"\b(?:[0-9]{1,3}\.){3}[0-9]{1,3}\b",
+    flags: FLAGS,
+};
+
+/// US phone numbers, e.g. `(555) 123-4567`.
+pub const US_PHONE: Rule = Rule {
+    id: ID_BASE + 8,
+    name: "us_phone_number",
+    category: "contact",
+    description: "US phone number",
+    expression: r"\(?([0-9]{3})\)?[-. ]?([0-9]{3})[-. ]?([0-9]{4})",
+    flags: FLAGS,
+};
+
+/// All the rules in the collection.
+pub const RULES: &[Rule] = &[
+    US_CURRENCY,
+    US_SSN,
+    VISA,
+    MASTERCARD,
+    DISCOVER,
+    AMEX,
+    EMAIL,
+    IPV4,
+    US_PHONE,
+];
+
+/// Returns the rule of the id.
+pub fn rule(id: usize) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.id == id)
+}
+
+/// Returns the rules of the category.
+pub fn category(category: &str) -> impl Iterator<Item = &'static Rule> + '_ {
+    RULES.iter().filter(move |rule| rule.category == category)
+}
+
+/// Returns the patterns of all the rules.
+pub fn patterns() -> Patterns {
+    RULES.iter().map(Rule::pattern).collect()
+}
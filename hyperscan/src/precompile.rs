@@ -0,0 +1,98 @@
+//! Build-time pattern precompilation.
+//!
+//! A static rule set could be compiled by the build script into a versioned database container,
+//! and embedded into the binary with [`include_db!`](crate::include_db), which removes the compile latency
+//! from the service startup. The container is validated before the database is deserialized,
+//! so a database built for another Hyperscan version, mode or CPU fails with a typed `ContainerError`.
+//!
+//! # Examples
+//!
+//! In `build.rs`, with `hyperscan` in the `[build-dependencies]`:
+//!
+//! ```rust,no_run
+//! # use hyperscan::{precompile, BlockMode, Platform, Tune, CpuFeatures};
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! let platform = Platform::new(Tune::Generic, CpuFeatures::empty());
+//!
+//! precompile::compile_file::<BlockMode, _, _>("rules.txt", format!("{}/rules.db", out_dir), Some(&platform))
+//!     .unwrap();
+//! ```
+//!
+//! In the service:
+//!
+//! ```rust,ignore
+//! use hyperscan::{include_db, BlockMode};
+//!
+//! let db = include_db!(BlockMode, concat!(env!("OUT_DIR"), "/rules.db")).unwrap();
+//! ```
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::common::{ContainerError, Mode};
+use crate::compile::{Builder, Patterns, PlatformRef};
+use crate::errors::Error as HsError;
+
+/// Errors of precompiling a pattern file.
+#[derive(Debug, Error)]
+pub enum PrecompileError {
+    /// Reading the pattern file failed.
+    #[error("Failed to read the patterns from {}, {source}.", .path.display())]
+    Io {
+        /// The path of the pattern file.
+        path: PathBuf,
+        /// The I/O error.
+        source: io::Error,
+    },
+
+    /// The pattern file is malformed.
+    #[error("Failed to parse the patterns from {}, {message}.", .path.display())]
+    Parse {
+        /// The path of the pattern file.
+        path: PathBuf,
+        /// The reason of the failure.
+        message: String,
+    },
+
+    /// Compiling the patterns failed.
+    #[error("Failed to compile the patterns, {0}")]
+    Compile(HsError),
+
+    /// Writing the database container failed.
+    #[error("Failed to save the database container, {0}")]
+    Container(#[from] ContainerError),
+}
+
+/// Compile a pattern file in the format of `Patterns::from_str()` into a versioned database container,
+/// the host platform is used if the target platform is not specified.
+///
+/// The build script is asked to rerun when the pattern file is changed.
+pub fn compile_file<T, P, Q>(input: P, output: Q, platform: Option<&PlatformRef>) -> Result<(), PrecompileError>
+where
+    T: Mode,
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let input = input.as_ref();
+
+    println!("cargo:rerun-if-changed={}", input.display());
+
+    let patterns: Patterns = fs::read_to_string(input)
+        .map_err(|source| PrecompileError::Io {
+            path: input.to_owned(),
+            source,
+        })?
+        .parse()
+        .map_err(|err: anyhow::Error| PrecompileError::Parse {
+            path: input.to_owned(),
+            message: err.to_string(),
+        })?;
+
+    patterns
+        .for_platform::<T>(platform)
+        .map_err(|err| PrecompileError::Compile(HsError::from_build(err)))?
+        .save(output)
+        .map_err(Into::into)
+}
@@ -0,0 +1,256 @@
+//! Background recompiling service.
+//!
+//! The `Recompiler` watches a rule source, compiles the new versions of the patterns off the hot path,
+//! and publishes them through a `DatabaseHandle`. The scanners re-grow their scratch space
+//! with `DatabaseHandle::refresh()` when the generation is changed, and the retired databases
+//! are released by the service after the scanners stop using them.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use std::sync::{mpsc, Arc};
+//! # use hyperscan::{prelude::*, recompile::{Progress, Recompiler}, BlockMode, DatabaseHandle};
+//! let handle = Arc::new(DatabaseHandle::new(pattern! {"foo"}.build::<BlockMode>().unwrap()));
+//! let mut generation = handle.generation();
+//! let mut s = handle.load().alloc_scratch().unwrap();
+//!
+//! let (tx, rx) = mpsc::channel();
+//! let (progress_tx, progress_rx) = mpsc::channel();
+//! let recompiler = Recompiler::new(handle.clone(), rx).spawn(move |progress| {
+//!     if let Progress::Published { .. } = progress {
+//!         progress_tx.send(()).unwrap();
+//!     }
+//! });
+//!
+//! tx.send("bar".parse().unwrap()).unwrap();
+//! progress_rx.recv().unwrap();
+//!
+//! let db = handle.refresh(&mut generation, &mut s).unwrap();
+//! let mut matches = vec![];
+//! db.scan("foo bar", &s, |_, _, to, _| {
+//!     matches.push(to);
+//!     Matching::Continue
+//! }).unwrap();
+//!
+//! assert_eq!(matches, vec![7]);
+//!
+//! recompiler.cancel();
+//! recompiler.join().unwrap();
+//! ```
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{anyhow, Result};
+
+use crate::common::{Database, DatabaseHandle, Mode};
+use crate::compile::{Builder, Patterns};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The source of the rules.
+pub enum Source {
+    /// A rule file in the format of `Patterns::from_str()`, which is reloaded when its modified time is changed.
+    File(PathBuf),
+    /// A channel of the pattern sets, the service is stopped when the channel is disconnected.
+    Channel(Receiver<Patterns>),
+}
+
+impl From<PathBuf> for Source {
+    fn from(path: PathBuf) -> Self {
+        Source::File(path)
+    }
+}
+
+impl From<Receiver<Patterns>> for Source {
+    fn from(rx: Receiver<Patterns>) -> Self {
+        Source::Channel(rx)
+    }
+}
+
+/// The progress of the recompiling service.
+#[derive(Debug)]
+pub enum Progress {
+    /// A new version of the rules is compiling.
+    Compiling {
+        /// The number of the patterns.
+        patterns: usize,
+    },
+    /// The new database is published.
+    Published {
+        /// The generation of the new database.
+        generation: usize,
+        /// The time of compiling the database.
+        elapsed: Duration,
+    },
+    /// The new version of the rules failed to load or compile, the current database is kept.
+    Failed(anyhow::Error),
+    /// The database of the previous generation is released after the scanners stopped using it.
+    Retired {
+        /// The generation of the released database.
+        generation: usize,
+    },
+    /// The compiled database is discarded because the service was cancelled.
+    Cancelled,
+}
+
+/// A service compiling the new versions of the rules and publishing them through a `DatabaseHandle`.
+pub struct Recompiler<T> {
+    handle: Arc<DatabaseHandle<T>>,
+    source: Source,
+    interval: Duration,
+    cancelled: Arc<AtomicBool>,
+    retired: Vec<(usize, Arc<Database<T>>)>,
+    modified: Option<SystemTime>,
+}
+
+impl<T: Mode + Send + Sync + 'static> Recompiler<T> {
+    /// Constructs a service publishing the databases compiled from the source to the handle.
+    pub fn new<S: Into<Source>>(handle: Arc<DatabaseHandle<T>>, source: S) -> Self {
+        Recompiler {
+            handle,
+            source: source.into(),
+            interval: DEFAULT_POLL_INTERVAL,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            retired: vec![],
+            modified: None,
+        }
+    }
+
+    /// The interval of polling the source and the retired databases, 1 second by default.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Run the service in a background thread, the progress is reported to the callback.
+    pub fn spawn<F>(self, on_progress: F) -> RecompilerHandle
+    where
+        F: FnMut(Progress) + Send + 'static,
+    {
+        let cancelled = self.cancelled.clone();
+        let thread = thread::spawn(move || self.run(on_progress));
+
+        RecompilerHandle { cancelled, thread }
+    }
+
+    /// Run the service in the current thread until the source is disconnected.
+    pub fn run<F>(mut self, mut on_progress: F) -> Result<()>
+    where
+        F: FnMut(Progress),
+    {
+        while !self.cancelled.load(Ordering::Acquire) {
+            let patterns = match self.poll() {
+                Ok(Some(patterns)) => patterns,
+                Ok(None) => {
+                    self.retire(&mut on_progress);
+                    continue;
+                }
+                Err(err) if err.is::<RecvTimeoutError>() => break,
+                Err(err) => {
+                    on_progress(Progress::Failed(err));
+                    continue;
+                }
+            };
+
+            on_progress(Progress::Compiling {
+                patterns: patterns.len(),
+            });
+
+            let start = Instant::now();
+
+            match patterns.build::<T>() {
+                Ok(_) if self.cancelled.load(Ordering::Acquire) => on_progress(Progress::Cancelled),
+                Ok(db) => {
+                    let generation = self.handle.generation();
+                    let prev = self.handle.store(db);
+
+                    self.retired.push((generation, prev));
+
+                    on_progress(Progress::Published {
+                        generation: self.handle.generation(),
+                        elapsed: start.elapsed(),
+                    });
+                }
+                Err(err) => on_progress(Progress::Failed(err)),
+            }
+
+            self.retire(&mut on_progress);
+        }
+
+        Ok(())
+    }
+
+    /// Wait for a new version of the rules until the interval.
+    fn poll(&mut self) -> Result<Option<Patterns>> {
+        match self.source {
+            Source::File(ref path) => {
+                let modified = fs::metadata(path).and_then(|md| md.modified()).ok();
+
+                if modified.is_none() || modified == self.modified {
+                    thread::sleep(self.interval);
+
+                    Ok(None)
+                } else {
+                    self.modified = modified;
+
+                    Ok(Some(fs::read_to_string(path)?.parse()?))
+                }
+            }
+            Source::Channel(ref rx) => match rx.recv_timeout(self.interval) {
+                // only the latest version is compiled
+                Ok(patterns) => Ok(Some(rx.try_iter().last().unwrap_or(patterns))),
+                Err(RecvTimeoutError::Timeout) => Ok(None),
+                Err(err @ RecvTimeoutError::Disconnected) => Err(err.into()),
+            },
+        }
+    }
+
+    /// Release the retired databases which are not used by the scanners.
+    fn retire<F>(&mut self, on_progress: &mut F)
+    where
+        F: FnMut(Progress),
+    {
+        let mut released = vec![];
+
+        self.retired.retain(|(generation, db)| {
+            if Arc::strong_count(db) > 1 {
+                true
+            } else {
+                released.push(*generation);
+                false
+            }
+        });
+
+        for generation in released {
+            on_progress(Progress::Retired { generation });
+        }
+    }
+}
+
+/// The handle of a running `Recompiler` service.
+pub struct RecompilerHandle {
+    cancelled: Arc<AtomicBool>,
+    thread: JoinHandle<Result<()>>,
+}
+
+impl RecompilerHandle {
+    /// Cancel the service, the database compiling in progress will be discarded.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` if the service was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Wait for the service to stop.
+    pub fn join(self) -> Result<()> {
+        self.thread.join().map_err(|_| anyhow!("recompiler panicked"))?
+    }
+}
@@ -1,6 +1,8 @@
-use std::ops::Range;
+use std::ops::{Index, Range};
 use std::str::FromStr;
 use std::sync::Arc;
+#[cfg(feature = "chimera")]
+use std::sync::Mutex;
 use std::vec;
 
 use anyhow::{Error, Result};
@@ -100,9 +102,83 @@ impl<'t> DoubleEndedIterator for Matches<'t> {
     }
 }
 
+/// Captures represents a group of captured strings for a single match.
+///
+/// The 0th capture always corresponds to the entire match. Each subsequent
+/// index corresponds to the next capture group in the regex.
+///
+/// `'t` is the lifetime of the matched text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Captures<'t> {
+    text: &'t str,
+    groups: Vec<Option<Range<usize>>>,
+}
+
+impl<'t> Captures<'t> {
+    /// Returns the match associated with the capture group at index `i`.
+    ///
+    /// If `i` does not correspond to a capture group, or if the capture group did not participate in the match,
+    /// then `None` is returned.
+    pub fn get(&self, i: usize) -> Option<Match<'t>> {
+        self.groups
+            .get(i)
+            .and_then(|group| group.as_ref())
+            .map(|range| Match::new(self.text, range.start, range.end))
+    }
+
+    /// Returns the total number of capture groups, including the group for the entire match.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// An iterator that yields all capturing matches in the order in which they appear in the regex.
+    ///
+    /// If a particular capture group didn't participate in the match, then `None` is yielded for that capture.
+    pub fn iter<'c>(&'c self) -> impl Iterator<Item = Option<Match<'t>>> + 'c {
+        (0..self.groups.len()).map(move |i| self.get(i))
+    }
+}
+
+impl<'t> Index<usize> for Captures<'t> {
+    type Output = str;
+
+    /// Get a group by index.
+    ///
+    /// # Panics
+    ///
+    /// If there is no group at the given index.
+    fn index(&self, i: usize) -> &str {
+        self.get(i)
+            .map(|m| m.as_str())
+            .unwrap_or_else(|| panic!("no group at index '{}'", i))
+    }
+}
+
 /// A compiled regular expression for matching Unicode strings.
+///
+/// `Regex` mirrors the core API of the `regex` crate, which compiles a single pattern
+/// and allocates the scratch space on demand, so it could be adopted without the databases and scratches.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::Regex;
+/// let re = Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap();
+///
+/// assert!(re.is_match("released on 2020-01-01"));
+/// assert_eq!(re.find("released on 2020-01-01").unwrap().as_str(), "2020-01-01");
+/// assert_eq!(re.as_str(), r"\d{4}-\d{2}-\d{2}");
+/// ```
 #[derive(Clone)]
-pub struct Regex(pub(crate) Arc<BlockDatabase>);
+pub struct Regex {
+    pub(crate) db: Arc<BlockDatabase>,
+    expr: Arc<str>,
+    #[cfg(feature = "chimera")]
+    flags: Flags,
+    #[cfg(feature = "chimera")]
+    groups: Arc<Mutex<Option<std::result::Result<Arc<crate::chimera::Database>, String>>>>,
+}
 
 impl FromStr for Regex {
     type Err = Error;
@@ -124,9 +200,22 @@ impl Regex {
     }
 
     pub(crate) fn with_flags<S: Into<String>>(re: S, flags: Flags) -> Result<Regex> {
-        Pattern::with_flags(re, flags | Flags::SOM_LEFTMOST | Flags::UTF8)?
-            .build()
-            .map(|db| Regex(Arc::new(db)))
+        let expr = re.into();
+        let db = Pattern::with_flags(expr.clone(), flags | Flags::SOM_LEFTMOST | Flags::UTF8)?.build()?;
+
+        Ok(Regex {
+            db: Arc::new(db),
+            expr: expr.into(),
+            #[cfg(feature = "chimera")]
+            flags,
+            #[cfg(feature = "chimera")]
+            groups: Arc::default(),
+        })
+    }
+
+    /// Returns the original string of this regex.
+    pub fn as_str(&self) -> &str {
+        &self.expr
     }
 
     /// Returns true if and only if the regex matches the string given.
@@ -146,8 +235,8 @@ impl Regex {
     pub fn is_match(&self, text: &str) -> bool {
         let mut matched = false;
 
-        let s = self.0.alloc_scratch().unwrap();
-        let _ = self.0.scan(text, &s, |_, _, _, _| {
+        let s = self.db.alloc_scratch().unwrap();
+        let _ = self.db.scan(text, &s, |_, _, _, _| {
             matched = true;
 
             Matching::Terminate
@@ -174,16 +263,14 @@ impl Regex {
     pub fn find<'t>(&self, text: &'t str) -> Option<Match<'t>> {
         let mut matched = vec![];
 
-        let s = self.0.alloc_scratch().unwrap();
-        let _ = self.0.scan(text, &s, |_, from, to, _| {
+        let s = self.db.alloc_scratch().unwrap();
+        let _ = self.db.scan(text, &s, |_, from, to, _| {
             matched.push((from as usize, to as usize));
 
             Matching::Terminate
         });
 
-        matched
-            .first()
-            .map(|&(start, end)| Match::new(&text[start..end], start, end))
+        matched.first().map(|&(start, end)| Match::new(text, start, end))
     }
 
     /// Returns an iterator for each successive non-overlapping match in
@@ -205,8 +292,8 @@ impl Regex {
     pub fn find_iter<'t>(&self, text: &'t str) -> Matches<'t> {
         let mut matched = Vec::<Range<usize>>::new();
 
-        let s = self.0.alloc_scratch().unwrap();
-        let _ = self.0.scan(text, &s, |_, from, to, _| {
+        let s = self.db.alloc_scratch().unwrap();
+        let _ = self.db.scan(text, &s, |_, from, to, _| {
             let range = from as usize..to as usize;
 
             match matched.last() {
@@ -223,6 +310,86 @@ impl Regex {
         Matches(text, matched.into_iter())
     }
 
+    /// Returns the capture groups corresponding to the leftmost-first match in `text`.
+    /// Capture group `0` always corresponds to the entire match. If no match is found, then `None` is returned.
+    ///
+    /// Hyperscan doesn't support the capturing groups, so the regex is compiled into a Chimera database
+    /// on the first call, which confirms the matches with PCRE and reports the groups.
+    ///
+    /// Returns an error if the regex can't be compiled by Chimera, the failure is cached and returned
+    /// by the later calls without compiling it again, or if the scan fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::Regex;
+    /// let re = Regex::new(r"'([^']+)'\s+\((\d{4})\)").unwrap();
+    /// let text = "Not my favorite movie: 'Citizen Kane' (1941).";
+    /// let caps = re.captures(text).unwrap().unwrap();
+    ///
+    /// assert_eq!(caps.len(), 3);
+    /// assert_eq!(&caps[0], "'Citizen Kane' (1941)");
+    /// assert_eq!(caps.get(1).unwrap().as_str(), "Citizen Kane");
+    /// assert_eq!(caps.get(2).unwrap().range(), 39..43);
+    /// ```
+    #[cfg(feature = "chimera")]
+    pub fn captures<'t>(&self, text: &'t str) -> Result<Option<Captures<'t>>> {
+        use crate::chimera::{self, Builder as _, Capture, Mode};
+
+        let db = {
+            let mut groups = self.groups.lock().unwrap_or_else(|err| err.into_inner());
+
+            groups
+                .get_or_insert_with(|| {
+                    let mut flags = chimera::Flags::UTF8;
+
+                    for &(flag, ch_flag) in &[
+                        (Flags::CASELESS, chimera::Flags::CASELESS),
+                        (Flags::DOTALL, chimera::Flags::DOTALL),
+                        (Flags::MULTILINE, chimera::Flags::MULTILINE),
+                        (Flags::UCP, chimera::Flags::UCP),
+                    ] {
+                        if self.flags.contains(flag) {
+                            flags |= ch_flag;
+                        }
+                    }
+
+                    chimera::Pattern::with_flags(&*self.expr, flags)
+                        .for_platform(Mode::Groups, None, None)
+                        .map(Arc::new)
+                        .map_err(|err| err.to_string())
+                })
+                .clone()
+                .map_err(Error::msg)?
+        };
+
+        let mut captured = None;
+
+        let s = db.alloc_scratch()?;
+        let res = db.scan(
+            text,
+            &s,
+            |_, from, to, _, groups: Option<&[Capture]>| {
+                captured = Some(match groups {
+                    Some(groups) => groups
+                        .iter()
+                        .map(|group| Some(group.range()).filter(|_| group.is_active()))
+                        .collect(),
+                    None => vec![Some(from as usize..to as usize)],
+                });
+
+                chimera::Matching::Terminate
+            },
+            chimera::Matching::Skip,
+        );
+
+        match res {
+            // the scan is terminated by the first match
+            Ok(()) | Err(chimera::Error::ScanTerminated) => Ok(captured.map(|groups| Captures { text, groups })),
+            Err(err) => Err(err.into()),
+        }
+    }
+
     /// Returns an iterator of substrings of `text` delimited by a match of the
     /// regular expression. Namely, each element of the iterator corresponds to
     /// text that *isn't* matched by the regular expression.
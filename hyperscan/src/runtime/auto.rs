@@ -0,0 +1,78 @@
+use std::io::Read;
+
+use crate::common::DualDatabase;
+use crate::errors::Result;
+use crate::runtime::{read_chunk, MatchEventHandler, ScanOutcome, Scratch, ScratchRef, SCAN_BUF_SIZE};
+
+impl DualDatabase {
+    /// Allocate a scratch space which supports both of the databases.
+    pub fn alloc_scratch(&self) -> Result<Scratch> {
+        let mut s = self.block.alloc_scratch()?;
+
+        self.streaming.realloc_scratch(&mut s)?;
+
+        Ok(s)
+    }
+
+    /// Scan the data of the reader, chooses the database mode by the size of the data.
+    ///
+    /// The data is buffered and scanned in block mode if it is not larger than the block threshold,
+    /// otherwise it is scanned in streaming mode.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db = pattern! {"test"; SOM_LEFTMOST}.build_dual().unwrap().with_block_threshold(4);
+    /// let s = db.alloc_scratch().unwrap();
+    ///
+    /// for data in &["test", "some test data"] {
+    ///     let mut matches = vec![];
+    ///
+    ///     db.scan_read(data.as_bytes(), &s, |_, from, to, _| {
+    ///         matches.push((from, to));
+    ///         Matching::Continue
+    ///     }).unwrap();
+    ///
+    ///     assert_eq!(matches.len(), 1);
+    /// }
+    /// ```
+    pub fn scan_read<R, F>(&self, mut reader: R, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        R: Read,
+        F: MatchEventHandler,
+    {
+        let mut buf = Vec::with_capacity(self.block_threshold.min(SCAN_BUF_SIZE) + 1);
+
+        (&mut reader)
+            .take(self.block_threshold as u64 + 1)
+            .read_to_end(&mut buf)?;
+
+        if buf.len() <= self.block_threshold {
+            return self.block.scan(&buf, scratch, on_match_event);
+        }
+
+        let stream = self.streaming.open_stream()?;
+        let (callback, userdata) = unsafe { on_match_event.split() };
+
+        if stream.scan(&buf, scratch, (callback, userdata))?.is_terminated() {
+            return Ok(ScanOutcome::Terminated);
+        }
+
+        buf.resize(SCAN_BUF_SIZE, 0);
+
+        loop {
+            let len = read_chunk(&mut reader, &mut buf, false)?;
+
+            if len == 0 {
+                break;
+            }
+
+            if stream.scan(&buf[..len], scratch, (callback, userdata))?.is_terminated() {
+                return Ok(ScanOutcome::Terminated);
+            }
+        }
+
+        stream.close(scratch, (callback, userdata))
+    }
+}
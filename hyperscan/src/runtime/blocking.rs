@@ -0,0 +1,64 @@
+use std::panic;
+use std::sync::Arc;
+
+use crate::common::BlockDatabase;
+use crate::errors::{Error, Result};
+use crate::runtime::{Matching, Scratch, ScratchPool};
+
+impl ScratchPool {
+    /// Run a CPU-heavy closure with a scratch space checked out from the pool on `tokio::task::spawn_blocking`,
+    /// so the async runtime is not blocked by the scan.
+    ///
+    /// The panic of the closure is resumed, and `Error::Cancelled` is returned if the runtime is shutting down.
+    pub async fn spawn_blocking<F, R>(self: &Arc<Self>, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Scratch) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let pool = self.clone();
+
+        match tokio::task::spawn_blocking(move || f(&mut pool.get_owned()?)).await {
+            Ok(res) => res,
+            Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+            Err(_) => Err(Error::Cancelled),
+        }
+    }
+
+    /// Scan the block on `tokio::task::spawn_blocking` with a scratch space checked out from the pool,
+    /// returns the collected matches as `(id, from, to, flags)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use hyperscan::{prelude::*, ScratchPool};
+    /// let db: Arc<BlockDatabase> = Arc::new(pattern! {"test"; SOM_LEFTMOST}.build().unwrap());
+    /// let pool = Arc::new(ScratchPool::new(&db).unwrap());
+    ///
+    /// let matches = tokio_test::block_on(pool.scan_blocking(&db, "some test data")).unwrap();
+    ///
+    /// assert_eq!(matches, vec![(0, 5, 9, 0)]);
+    /// ```
+    pub async fn scan_blocking<T>(
+        self: &Arc<Self>,
+        db: &Arc<BlockDatabase>,
+        data: T,
+    ) -> Result<Vec<(u32, u64, u64, u32)>>
+    where
+        T: AsRef<[u8]> + Send + 'static,
+    {
+        let db = db.clone();
+
+        self.spawn_blocking(move |scratch| {
+            let mut matches = vec![];
+
+            let _ = db.scan(data, scratch, |id, from, to, flags| {
+                matches.push((id, from, to, flags));
+                Matching::Continue
+            })?;
+
+            Ok(matches)
+        })
+        .await
+    }
+}
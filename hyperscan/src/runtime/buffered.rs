@@ -0,0 +1,98 @@
+use std::vec::Drain;
+
+use crate::common::{DatabaseRef, Streaming};
+use crate::errors::Result;
+use crate::runtime::{Matching, ScratchRef, Stream, StreamRef};
+
+/// A stream which accumulates the matches as `(id, from, to)`, so they can be pulled between the writes
+/// instead of structuring the code around the callbacks.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::prelude::*;
+/// let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut st = db.open_buffered_stream().unwrap();
+///
+/// st.write("foo t", &s).unwrap();
+/// assert_eq!(st.drain_matches().count(), 0);
+///
+/// st.write("est bar test", &s).unwrap();
+/// assert_eq!(st.drain_matches().collect::<Vec<_>>(), vec![(0, 4, 8), (0, 13, 17)]);
+///
+/// assert!(st.close(&s).unwrap().is_empty());
+/// ```
+pub struct BufferedStream {
+    stream: Stream,
+    matches: Vec<(u32, u64, u64)>,
+}
+
+impl DatabaseRef<Streaming> {
+    /// Open and initialise a stream which accumulates the matches.
+    pub fn open_buffered_stream(&self) -> Result<BufferedStream> {
+        self.open_stream().map(BufferedStream::new)
+    }
+}
+
+impl BufferedStream {
+    /// Accumulate the matches of an opened stream.
+    pub fn new(stream: Stream) -> Self {
+        BufferedStream {
+            stream,
+            matches: vec![],
+        }
+    }
+
+    /// Returns the underlying stream.
+    pub fn stream(&self) -> &StreamRef {
+        &self.stream
+    }
+
+    /// Returns the number of the accumulated matches which have not been drained.
+    pub fn pending(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Write data to the stream, the matches are accumulated until drained.
+    pub fn write<T: AsRef<[u8]>>(&mut self, data: T, scratch: &ScratchRef) -> Result<()> {
+        let matches = &mut self.matches;
+
+        // the accumulating callback never terminates the scan
+        let _ = self.stream.scan(data, scratch, |id, from, to, _flags: u32| {
+            matches.push((id, from, to));
+            Matching::Continue
+        })?;
+
+        Ok(())
+    }
+
+    /// Returns an iterator over the matches accumulated since the last drain, in the order they were reported.
+    pub fn drain_matches(&mut self) -> Drain<'_, (u32, u64, u64)> {
+        self.matches.drain(..)
+    }
+
+    /// Reset the stream to an initial state, the end of data matches of the original data are accumulated.
+    pub fn reset(&mut self, scratch: &ScratchRef) -> Result<()> {
+        let matches = &mut self.matches;
+
+        let _ = self.stream.reset(scratch, |id, from, to, _flags: u32| {
+            matches.push((id, from, to));
+            Matching::Continue
+        })?;
+
+        Ok(())
+    }
+
+    /// Close the stream, returns the matches which have not been drained, including the end of data matches.
+    pub fn close(self, scratch: &ScratchRef) -> Result<Vec<(u32, u64, u64)>> {
+        let BufferedStream { stream, mut matches } = self;
+
+        let _ = stream.close(scratch, |id, from, to, _flags: u32| {
+            matches.push((id, from, to));
+            Matching::Continue
+        })?;
+
+        Ok(matches)
+    }
+}
@@ -0,0 +1,154 @@
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::common::{Block, DatabaseRef, Streaming};
+use crate::errors::{Error, Result};
+use crate::runtime::{read_chunk, Matching, ScanOutcome, ScratchRef, SCAN_BUF_SIZE};
+
+/// A token which can be triggered by another thread to cancel the scans in progress.
+///
+/// The scan is terminated at the next match callback, or between the streamed chunks,
+/// and returns `Error::Cancelled`. A block without any match is always scanned to the end,
+/// so a request timeout should be combined with the reasonable block sizes.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, CancellationToken, Error};
+/// let db: BlockDatabase = pattern! {"foo"}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let token = CancellationToken::new();
+/// let canceller = token.clone();
+/// let mut matches = 0;
+///
+/// // the token could be triggered by a timer or a shutdown handler in another thread
+/// let err = db.scan_cancellable("foo foo foo", &s, &token, |_, _, _, _| {
+///     matches += 1;
+///     canceller.cancel();
+///     Matching::Continue
+/// }).unwrap_err();
+///
+/// assert_eq!(err, Error::Cancelled);
+/// assert_eq!(matches, 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Constructs a new token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the scans using the token or its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release)
+    }
+
+    /// Returns `true` if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Returns `Error::Cancelled` if the token was cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Wrap a match event callback that terminates the scan once the token was cancelled.
+    pub fn wrap<'a, F>(&'a self, mut on_match_event: F) -> impl FnMut(u32, u64, u64, u32) -> Matching + 'a
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching + 'a,
+    {
+        move |id, from, to, flags| {
+            if self.is_cancelled() {
+                Matching::Terminate
+            } else {
+                on_match_event(id, from, to, flags)
+            }
+        }
+    }
+
+    /// Replace the termination of a cancelled scan with `Error::Cancelled`.
+    fn map_outcome(&self, res: Result<ScanOutcome>) -> Result<ScanOutcome> {
+        match res {
+            Ok(ScanOutcome::Terminated) if self.is_cancelled() => Err(Error::Cancelled),
+            res => res,
+        }
+    }
+}
+
+impl DatabaseRef<Block> {
+    /// The block regular expression scanner, which is terminated when the token is cancelled.
+    pub fn scan_cancellable<T, F>(
+        &self,
+        data: T,
+        scratch: &ScratchRef,
+        token: &CancellationToken,
+        on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: FnMut(u32, u64, u64, u32) -> Matching,
+    {
+        token.check()?;
+        token.map_outcome(self.scan(data, scratch, token.wrap(on_match_event)))
+    }
+}
+
+impl DatabaseRef<Streaming> {
+    /// Pattern matching takes place for stream-mode pattern databases,
+    /// which is terminated when the token is cancelled, the stream is closed without the end of data matches.
+    pub fn scan_cancellable<R, F>(
+        &self,
+        reader: &mut R,
+        scratch: &ScratchRef,
+        token: &CancellationToken,
+        on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        R: Read,
+        F: FnMut(u32, u64, u64, u32) -> Matching,
+    {
+        let stream = self.open_stream()?;
+        let mut buf = [0; SCAN_BUF_SIZE];
+        let mut callback = token.wrap(on_match_event);
+
+        let res = (|| -> Result<ScanOutcome> {
+            loop {
+                token.check()?;
+
+                let len = read_chunk(reader, &mut buf, false)?;
+
+                if len == 0 {
+                    return Ok(ScanOutcome::Completed);
+                }
+
+                if stream.scan(&buf[..len], scratch, &mut callback)?.is_terminated() {
+                    return Ok(ScanOutcome::Terminated);
+                }
+            }
+        })();
+
+        match token.map_outcome(res) {
+            Ok(ScanOutcome::Completed) => token.map_outcome(stream.close(scratch, &mut callback)),
+            Ok(ScanOutcome::Terminated) => {
+                stream.free()?;
+
+                Ok(ScanOutcome::Terminated)
+            }
+            Err(err) => {
+                stream.free()?;
+
+                Err(err)
+            }
+        }
+    }
+}
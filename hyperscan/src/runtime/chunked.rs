@@ -0,0 +1,149 @@
+use crate::common::{DatabaseRef, Streaming};
+use crate::errors::Result;
+use crate::runtime::{Match, Matching, ScanOutcome, ScratchRef, Stream, StreamRef};
+
+/// A match event located in the chunks written to a `StreamScanner`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChunkMatch {
+    /// The match with the offsets relative to the whole logical input.
+    pub matched: Match,
+    /// The index of the chunk which the match ends in.
+    pub chunk: usize,
+    /// The offset of the chunk relative to the whole logical input.
+    pub chunk_start: u64,
+}
+
+impl ChunkMatch {
+    /// Returns the end offset of the match relative to the start of the chunk.
+    pub fn end_in_chunk(&self) -> u64 {
+        self.matched.end - self.chunk_start
+    }
+
+    /// Returns the start offset of the match relative to the start of the chunk,
+    /// or `None` if the match starts in a previous chunk.
+    ///
+    /// The start offset is only meaningful for the patterns with `SOM_LEFTMOST` flag.
+    pub fn start_in_chunk(&self) -> Option<u64> {
+        self.matched.start.checked_sub(self.chunk_start)
+    }
+}
+
+/// A stream which keeps the bookkeeping of the chunks written to it,
+/// so the matches are located in both the whole logical input and the chunk.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::prelude::*;
+/// let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut st = db.open_stream_scanner().unwrap();
+/// let mut matches = vec![];
+///
+/// for chunk in &["foo te", "st bar", " test"] {
+///     st.scan(chunk, &s, |m| {
+///         matches.push((m.matched.start, m.matched.end, m.chunk, m.start_in_chunk(), m.end_in_chunk()));
+///         Matching::Continue
+///     })
+///     .unwrap();
+/// }
+///
+/// assert_eq!(st.offset(), 17);
+/// assert_eq!(st.chunks(), 3);
+///
+/// st.close(&s, |_| Matching::Continue).unwrap();
+///
+/// assert_eq!(matches, vec![(4, 8, 1, None, 2), (13, 17, 2, Some(1), 5)]);
+/// ```
+pub struct StreamScanner {
+    stream: Stream,
+    offset: u64,
+    chunks: usize,
+    chunk_start: u64,
+}
+
+impl DatabaseRef<Streaming> {
+    /// Open and initialise a stream which keeps the bookkeeping of the chunks written to it.
+    pub fn open_stream_scanner(&self) -> Result<StreamScanner> {
+        self.open_stream().map(StreamScanner::new)
+    }
+}
+
+impl StreamScanner {
+    /// Keep the bookkeeping of the chunks written to an opened stream.
+    pub fn new(stream: Stream) -> Self {
+        StreamScanner {
+            stream,
+            offset: 0,
+            chunks: 0,
+            chunk_start: 0,
+        }
+    }
+
+    /// Returns the underlying stream.
+    pub fn stream(&self) -> &StreamRef {
+        &self.stream
+    }
+
+    /// Returns the number of bytes written to the stream.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the number of chunks written to the stream.
+    pub fn chunks(&self) -> usize {
+        self.chunks
+    }
+
+    /// Write a chunk to the stream, the matches are located in the chunk.
+    pub fn scan<T, F>(&mut self, chunk: T, scratch: &ScratchRef, mut on_match: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: FnMut(ChunkMatch) -> Matching,
+    {
+        let data = chunk.as_ref();
+        let chunk = self.chunks;
+        let chunk_start = self.offset;
+
+        self.chunks += 1;
+        self.chunk_start = self.offset;
+        self.offset += data.len() as u64;
+
+        self.stream.scan(
+            data,
+            scratch,
+            Match::handler(|m| {
+                on_match(ChunkMatch {
+                    matched: m,
+                    chunk,
+                    chunk_start,
+                })
+            }),
+        )
+    }
+
+    /// Close the stream, the end of data matches are located in the last chunk.
+    pub fn close<F>(self, scratch: &ScratchRef, mut on_match: F) -> Result<ScanOutcome>
+    where
+        F: FnMut(ChunkMatch) -> Matching,
+    {
+        let StreamScanner {
+            stream,
+            chunks,
+            chunk_start,
+            ..
+        } = self;
+        let chunk = chunks.saturating_sub(1);
+
+        stream.close(
+            scratch,
+            Match::handler(|m| {
+                on_match(ChunkMatch {
+                    matched: m,
+                    chunk,
+                    chunk_start,
+                })
+            }),
+        )
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::common::{Block, DatabaseRef, Streaming};
+use crate::errors::Result;
+use crate::runtime::{Matching, Scratch};
+
+/// The differences of the matches of a rule between two rulesets.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RuleDiff {
+    /// The number of the matches reported by the old ruleset.
+    pub old: u64,
+    /// The number of the matches reported by the new ruleset.
+    pub new: u64,
+    /// The number of the matches only reported by the old ruleset.
+    pub only_old: u64,
+    /// The number of the matches only reported by the new ruleset.
+    pub only_new: u64,
+}
+
+impl RuleDiff {
+    /// Returns `true` if both rulesets reported the same matches of the rule.
+    pub fn is_identical(&self) -> bool {
+        self.only_old == 0 && self.only_new == 0
+    }
+}
+
+/// The report of the differences between two rulesets.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ComparisonReport {
+    inputs: u64,
+    differing_inputs: u64,
+    rules: BTreeMap<u32, RuleDiff>,
+}
+
+impl ComparisonReport {
+    /// Returns the number of the compared inputs, i.e. the blocks or the streams.
+    pub fn inputs(&self) -> u64 {
+        self.inputs
+    }
+
+    /// Returns the number of the inputs which the rulesets matched differently.
+    pub fn differing_inputs(&self) -> u64 {
+        self.differing_inputs
+    }
+
+    /// Returns `true` if both rulesets reported the same matches for all the inputs.
+    pub fn is_identical(&self) -> bool {
+        self.differing_inputs == 0
+    }
+
+    /// Returns the differences of the rule.
+    pub fn get(&self, id: u32) -> Option<&RuleDiff> {
+        self.rules.get(&id)
+    }
+
+    /// An iterator visiting the differences of all the matched rules in the order of the rule id.
+    pub fn rules(&self) -> impl Iterator<Item = (u32, &RuleDiff)> {
+        self.rules.iter().map(|(&id, diff)| (id, diff))
+    }
+
+    /// An iterator visiting the rules which were matched differently in the order of the rule id.
+    pub fn differences(&self) -> impl Iterator<Item = (u32, &RuleDiff)> {
+        self.rules().filter(|(_, diff)| !diff.is_identical())
+    }
+
+    fn record(&mut self, old: HashSet<(u32, u64, u64)>, new: HashSet<(u32, u64, u64)>) {
+        self.inputs += 1;
+
+        if old != new {
+            self.differing_inputs += 1;
+        }
+
+        for &(id, from, to) in &old {
+            let diff = self.rules.entry(id).or_default();
+
+            diff.old += 1;
+
+            if !new.contains(&(id, from, to)) {
+                diff.only_old += 1;
+            }
+        }
+
+        for &(id, from, to) in &new {
+            let diff = self.rules.entry(id).or_default();
+
+            diff.new += 1;
+
+            if !old.contains(&(id, from, to)) {
+                diff.only_new += 1;
+            }
+        }
+    }
+}
+
+/// Runs the old and the new rulesets over the same corpus or stream capture,
+/// and reports the differences of the matches per rule, to support the safe ruleset rollouts.
+///
+/// The matches are compared by the rule id and the offsets, without regard to the order they were reported.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, Comparison};
+/// let old: BlockDatabase = patterns!("foo", "bar").build().unwrap();
+/// let new: BlockDatabase = patterns!("foo", "ba[rz]").build().unwrap();
+/// let mut cmp = Comparison::new(&old, &new).unwrap();
+///
+/// for data in &["foo bar", "foo baz"] {
+///     cmp.scan(data).unwrap();
+/// }
+///
+/// let report = cmp.into_report();
+///
+/// assert_eq!(report.inputs(), 2);
+/// assert_eq!(report.differing_inputs(), 1);
+/// assert_eq!(report.differences().map(|(id, _)| id).collect::<Vec<_>>(), vec![1]);
+///
+/// let diff = report.get(1).unwrap();
+///
+/// assert_eq!((diff.old, diff.new, diff.only_old, diff.only_new), (1, 2, 0, 1));
+/// ```
+pub struct Comparison<'a, T> {
+    old: &'a DatabaseRef<T>,
+    new: &'a DatabaseRef<T>,
+    scratch: Scratch,
+    report: ComparisonReport,
+}
+
+impl<'a, T> Comparison<'a, T> {
+    /// Constructs a comparison of the rulesets with a scratch space for both of them.
+    pub fn new(old: &'a DatabaseRef<T>, new: &'a DatabaseRef<T>) -> Result<Self> {
+        let mut scratch = old.alloc_scratch()?;
+
+        new.realloc_scratch(&mut scratch)?;
+
+        Ok(Comparison {
+            old,
+            new,
+            scratch,
+            report: ComparisonReport::default(),
+        })
+    }
+
+    /// Returns the report of the compared inputs so far.
+    pub fn report(&self) -> &ComparisonReport {
+        &self.report
+    }
+
+    /// Consumes the comparison and returns the report.
+    pub fn into_report(self) -> ComparisonReport {
+        self.report
+    }
+}
+
+/// Collects the matches into the set, it never terminates the scan so the outcomes of the scans are ignored.
+fn collect(matches: &mut HashSet<(u32, u64, u64)>) -> impl FnMut(u32, u64, u64, u32) -> Matching + '_ {
+    move |id, from, to, _flags| {
+        matches.insert((id, from, to));
+
+        Matching::Continue
+    }
+}
+
+impl Comparison<'_, Block> {
+    /// Scan a block of the corpus with both rulesets.
+    pub fn scan<D: AsRef<[u8]>>(&mut self, data: D) -> Result<()> {
+        let data = data.as_ref();
+        let mut old = HashSet::new();
+        let mut new = HashSet::new();
+
+        let _ = self.old.scan(data, &self.scratch, collect(&mut old))?;
+        let _ = self.new.scan(data, &self.scratch, collect(&mut new))?;
+
+        self.report.record(old, new);
+
+        Ok(())
+    }
+}
+
+impl Comparison<'_, Streaming> {
+    /// Scan a stream capture with both rulesets, the chunks are written to the streams in order.
+    pub fn scan_stream<I, D>(&mut self, chunks: I) -> Result<()>
+    where
+        I: IntoIterator<Item = D>,
+        D: AsRef<[u8]>,
+    {
+        let mut old = HashSet::new();
+        let mut new = HashSet::new();
+
+        {
+            let old_stream = self.old.open_stream()?;
+            let new_stream = self.new.open_stream()?;
+            let mut on_old = collect(&mut old);
+            let mut on_new = collect(&mut new);
+
+            for chunk in chunks {
+                let chunk = chunk.as_ref();
+
+                let _ = old_stream.scan(chunk, &self.scratch, &mut on_old)?;
+                let _ = new_stream.scan(chunk, &self.scratch, &mut on_new)?;
+            }
+
+            let _ = old_stream.close(&self.scratch, &mut on_old)?;
+            let _ = new_stream.close(&self.scratch, &mut on_new)?;
+        }
+
+        self.report.record(old, new);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use libc::{c_int, c_uint, c_ulonglong, c_void};
+
+use crate::closure;
+use crate::common::{Block, DatabaseRef, Vectored};
+use crate::errors::Result;
+use crate::ffi;
+use crate::runtime::{Match, MatchEventHandler, Matching, ScanOutcome, ScratchRef, StreamRef};
+
+/// The user context with the callback which mutates it.
+struct Context<'a, C> {
+    ctx: &'a mut C,
+    on_match: fn(&mut C, Match) -> Matching,
+}
+
+unsafe extern "C" fn on_match_event<C>(
+    id: c_uint,
+    from: c_ulonglong,
+    to: c_ulonglong,
+    flags: c_uint,
+    ptr: *mut c_void,
+) -> c_int {
+    let context = &mut *(ptr as *mut Context<'_, C>);
+    let m = Match {
+        id,
+        start: from,
+        end: to,
+        flags,
+    };
+
+    closure::catch_unwind(|| (context.on_match)(context.ctx, m)) as c_int
+}
+
+impl<C> MatchEventHandler for Context<'_, C> {
+    unsafe fn split(&mut self) -> (ffi::match_event_handler, *mut c_void) {
+        (Some(on_match_event::<C>), self as *mut _ as *mut _)
+    }
+}
+
+impl DatabaseRef<Block> {
+    /// The block regular expression scanner with an explicit user context,
+    /// which is passed to the callback with the match, instead of being captured by a closure.
+    ///
+    /// The userdata passed to Hyperscan wraps the context with the callback, it is not the context itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, Match};
+    /// #[derive(Default)]
+    /// struct Stats {
+    ///     matches: usize,
+    ///     last_end: u64,
+    /// }
+    ///
+    /// fn on_match(stats: &mut Stats, m: Match) -> Matching {
+    ///     stats.matches += 1;
+    ///     stats.last_end = m.end;
+    ///     Matching::Continue
+    /// }
+    ///
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let mut stats = Stats::default();
+    ///
+    /// db.scan_with("foo test bar test", &s, &mut stats, on_match).unwrap();
+    ///
+    /// assert_eq!(stats.matches, 2);
+    /// assert_eq!(stats.last_end, 17);
+    /// ```
+    pub fn scan_with<T, C>(
+        &self,
+        data: T,
+        scratch: &ScratchRef,
+        ctx: &mut C,
+        on_match: fn(&mut C, Match) -> Matching,
+    ) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.scan(data, scratch, Context { ctx, on_match })
+    }
+}
+
+impl DatabaseRef<Vectored> {
+    /// The vectored regular expression scanner with an explicit user context,
+    /// which is passed to the callback with the match, instead of being captured by a closure.
+    pub fn scan_with<I, T, C>(
+        &self,
+        data: I,
+        scratch: &ScratchRef,
+        ctx: &mut C,
+        on_match: fn(&mut C, Match) -> Matching,
+    ) -> Result<ScanOutcome>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        self.scan(data, scratch, Context { ctx, on_match })
+    }
+}
+
+impl StreamRef {
+    /// Write data to the opened stream with an explicit user context,
+    /// which is passed to the callback with the match, instead of being captured by a closure.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, Match};
+    /// let db: StreamingDatabase = pattern! {"test"}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let st = db.open_stream().unwrap();
+    /// let mut ends = vec![];
+    ///
+    /// for data in &["foo te", "st bar"] {
+    ///     st.scan_with(data, &s, &mut ends, |ends: &mut Vec<u64>, m| {
+    ///         ends.push(m.end);
+    ///         Matching::Continue
+    ///     })
+    ///     .unwrap();
+    /// }
+    ///
+    /// assert_eq!(ends, vec![8]);
+    /// ```
+    pub fn scan_with<T, C>(
+        &self,
+        data: T,
+        scratch: &ScratchRef,
+        ctx: &mut C,
+        on_match: fn(&mut C, Match) -> Matching,
+    ) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.scan(data, scratch, Context { ctx, on_match })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::runtime::ScanOutcome;
+
+    #[test]
+    fn test_vectored_scan_with() {
+        let db: VectoredDatabase = "test".parse().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let data = vec!["foo te", "st bar", " test"];
+        let mut ends = vec![];
+
+        let outcome = db
+            .scan_with(&data, &s, &mut ends, |ends: &mut Vec<u64>, m| {
+                ends.push(m.end);
+                Matching::Continue
+            })
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Completed);
+        assert_eq!(ends, vec![8, 17]);
+
+        ends.clear();
+
+        let outcome = db
+            .scan_with(&["test", "test"], &s, &mut ends, |ends: &mut Vec<u64>, m| {
+                ends.push(m.end);
+                Matching::Terminate
+            })
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Terminated);
+        assert_eq!(ends, vec![4]);
+    }
+}
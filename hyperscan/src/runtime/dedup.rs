@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+
+use crate::runtime::Matching;
+
+/// Emulate the `SINGLEMATCH` flag at runtime without recompiling the database.
+///
+/// Only the first match of each pattern will be delivered to the wrapped callback,
+/// the following matches of the same pattern are silently dropped until `SingleMatch::reset` is called.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, SingleMatch};
+/// let db: BlockDatabase = patterns!("foo", "bar").build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut single = SingleMatch::new();
+/// let mut matches = vec![];
+///
+/// db.scan("foo bar foo bar", &s, single.wrap(|id, _, to, _| {
+///     matches.push((id, to));
+///     Matching::Continue
+/// })).unwrap();
+///
+/// assert_eq!(matches, vec![(0, 3), (1, 7)]);
+/// assert!(single.is_matched(0));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SingleMatch {
+    matched: HashSet<u32>,
+}
+
+impl SingleMatch {
+    /// Constructs a new, empty `SingleMatch`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the matched patterns, should be called before scanning a new block or stream.
+    pub fn reset(&mut self) {
+        self.matched.clear()
+    }
+
+    /// Returns `true` if the pattern has been matched since the last reset.
+    pub fn is_matched(&self, id: u32) -> bool {
+        self.matched.contains(&id)
+    }
+
+    /// An iterator visiting the ids of matched patterns in arbitrary order.
+    pub fn matched(&self) -> impl Iterator<Item = u32> + '_ {
+        self.matched.iter().copied()
+    }
+
+    /// Wrap a match event callback that only receives the first match of each pattern.
+    pub fn wrap<'a, F>(&'a mut self, mut on_match_event: F) -> impl FnMut(u32, u64, u64, u32) -> Matching + 'a
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching + 'a,
+    {
+        move |id, from, to, flags| {
+            if self.matched.insert(id) {
+                on_match_event(id, from, to, flags)
+            } else {
+                Matching::Continue
+            }
+        }
+    }
+}
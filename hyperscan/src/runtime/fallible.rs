@@ -0,0 +1,116 @@
+use crate::common::{Block, DatabaseRef, Vectored};
+use crate::errors::{Error, Result};
+use crate::runtime::{Matching, ScanOutcome, ScratchRef, StreamRef};
+
+/// Run the scan with an infallible callback, which terminates the scan and keeps the error
+/// once the fallible callback fails.
+fn try_scan<F, E, S>(mut on_match_event: F, scan: S) -> Result<ScanOutcome, E>
+where
+    F: FnMut(u32, u64, u64, u32) -> Result<Matching, E>,
+    E: From<Error>,
+    S: FnOnce(&mut dyn FnMut(u32, u64, u64, u32) -> Matching) -> Result<ScanOutcome>,
+{
+    let mut failed = None;
+
+    let outcome = scan(&mut |id, from, to, flags| match on_match_event(id, from, to, flags) {
+        Ok(matching) => matching,
+        Err(err) => {
+            failed = Some(err);
+
+            Matching::Terminate
+        }
+    })?;
+
+    match failed {
+        Some(err) => Err(err),
+        None => Ok(outcome),
+    }
+}
+
+impl DatabaseRef<Block> {
+    /// The block regular expression scanner with a fallible callback.
+    ///
+    /// The scan is terminated once the callback returns an error, which is returned to the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// #[derive(Debug, PartialEq)]
+    /// enum AppError {
+    ///     Forbidden(u64),
+    ///     Hyperscan(hyperscan::Error),
+    /// }
+    ///
+    /// impl From<hyperscan::Error> for AppError {
+    ///     fn from(err: hyperscan::Error) -> Self {
+    ///         AppError::Hyperscan(err)
+    ///     }
+    /// }
+    ///
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let mut matches = 0;
+    ///
+    /// let err = db.try_scan("foo test bar test", &s, |_, _, to, _| {
+    ///     matches += 1;
+    ///
+    ///     Err(AppError::Forbidden(to))
+    /// }).unwrap_err();
+    ///
+    /// assert_eq!(err, AppError::Forbidden(8));
+    /// assert_eq!(matches, 1);
+    /// ```
+    pub fn try_scan<T, F, E>(&self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome, E>
+    where
+        T: AsRef<[u8]>,
+        F: FnMut(u32, u64, u64, u32) -> Result<Matching, E>,
+        E: From<Error>,
+    {
+        try_scan(on_match_event, |callback| self.scan(data, scratch, callback))
+    }
+}
+
+impl DatabaseRef<Vectored> {
+    /// The vectored regular expression scanner with a fallible callback.
+    ///
+    /// The scan is terminated once the callback returns an error, which is returned to the caller.
+    pub fn try_scan<I, T, F, E>(&self, data: I, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome, E>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+        F: FnMut(u32, u64, u64, u32) -> Result<Matching, E>,
+        E: From<Error>,
+    {
+        try_scan(on_match_event, |callback| self.scan(data, scratch, callback))
+    }
+}
+
+impl StreamRef {
+    /// Write data to the opened stream with a fallible callback.
+    ///
+    /// The scan is terminated once the callback returns an error, which is returned to the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: StreamingDatabase = pattern! {"test"}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let st = db.open_stream().unwrap();
+    ///
+    /// let res: anyhow::Result<_> = st.try_scan("foo test", &s, |id, _, _, _| {
+    ///     anyhow::bail!("pattern {} is forbidden", id)
+    /// });
+    ///
+    /// assert_eq!(res.unwrap_err().to_string(), "pattern 0 is forbidden");
+    /// ```
+    pub fn try_scan<T, F, E>(&self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome, E>
+    where
+        T: AsRef<[u8]>,
+        F: FnMut(u32, u64, u64, u32) -> Result<Matching, E>,
+        E: From<Error>,
+    {
+        try_scan(on_match_event, |callback| self.scan(data, scratch, callback))
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::runtime::Matching;
+
+const BITS: usize = 64;
+
+/// An enable/disable bitmap keyed by the pattern id, which suppresses the matches of the disabled patterns
+/// at runtime without recompiling the database.
+///
+/// The bitmap is updated atomically, so it can be shared by the scanning threads with an `Arc`,
+/// and a misbehaving rule can be muted instantly while the database is rebuilt in the background.
+/// The patterns with an id out of the capacity are always enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, PatternFilter};
+/// let db: BlockDatabase = patterns!("foo", "bar").build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let filter = PatternFilter::new(2);
+/// let mut matches = vec![];
+///
+/// filter.disable(0);
+///
+/// db.scan("foo bar", &s, filter.wrap(|id, _, to, _| {
+///     matches.push((id, to));
+///     Matching::Continue
+/// })).unwrap();
+///
+/// assert_eq!(matches, vec![(1, 7)]);
+/// assert!(!filter.is_enabled(0));
+/// assert_eq!(filter.disabled().collect::<Vec<_>>(), vec![0]);
+/// ```
+pub struct PatternFilter {
+    disabled: Box<[AtomicU64]>,
+}
+
+impl fmt::Debug for PatternFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.disabled()).finish()
+    }
+}
+
+impl PatternFilter {
+    /// Constructs a filter with all the patterns enabled, for the pattern ids less than `capacity`.
+    pub fn new(capacity: usize) -> Self {
+        PatternFilter {
+            disabled: (0..(capacity + BITS - 1) / BITS).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    /// Returns the number of the pattern ids can be disabled.
+    pub fn capacity(&self) -> usize {
+        self.disabled.len() * BITS
+    }
+
+    /// Enable the pattern.
+    pub fn enable(&self, id: u32) {
+        if let Some((word, mask)) = self.locate(id) {
+            word.fetch_and(!mask, Ordering::Release);
+        }
+    }
+
+    /// Disable the pattern, returns `false` if the id is out of the capacity.
+    pub fn disable(&self, id: u32) -> bool {
+        if let Some((word, mask)) = self.locate(id) {
+            word.fetch_or(mask, Ordering::Release);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Enable all the patterns.
+    pub fn enable_all(&self) {
+        for word in self.disabled.iter() {
+            word.store(0, Ordering::Release);
+        }
+    }
+
+    /// Returns `true` if the matches of the pattern are delivered.
+    pub fn is_enabled(&self, id: u32) -> bool {
+        self.locate(id)
+            .map_or(true, |(word, mask)| word.load(Ordering::Acquire) & mask == 0)
+    }
+
+    /// An iterator visiting the ids of the disabled patterns in ascending order.
+    pub fn disabled(&self) -> impl Iterator<Item = u32> + '_ {
+        self.disabled.iter().enumerate().flat_map(|(i, word)| {
+            let bits = word.load(Ordering::Acquire);
+
+            (0..BITS)
+                .filter(move |n| bits & (1 << n) != 0)
+                .map(move |n| (i * BITS + n) as u32)
+        })
+    }
+
+    /// Wrap a match event callback that only receives the matches of the enabled patterns.
+    pub fn wrap<'a, F>(&'a self, mut on_match_event: F) -> impl FnMut(u32, u64, u64, u32) -> Matching + 'a
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching + 'a,
+    {
+        move |id, from, to, flags| {
+            if self.is_enabled(id) {
+                on_match_event(id, from, to, flags)
+            } else {
+                Matching::Continue
+            }
+        }
+    }
+
+    fn locate(&self, id: u32) -> Option<(&AtomicU64, u64)> {
+        let id = id as usize;
+
+        self.disabled.get(id / BITS).map(|word| (word, 1 << (id % BITS)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_filter() {
+        let filter = PatternFilter::new(100);
+
+        assert_eq!(filter.capacity(), 128);
+        assert!(filter.disable(3));
+        assert!(filter.disable(70));
+        assert!(!filter.disable(128));
+        assert!(filter.is_enabled(128));
+        assert_eq!(filter.disabled().collect::<Vec<_>>(), vec![3, 70]);
+
+        filter.enable(3);
+
+        assert!(filter.is_enabled(3));
+        assert!(!filter.is_enabled(70));
+
+        filter.enable_all();
+
+        assert_eq!(filter.disabled().count(), 0);
+    }
+}
@@ -0,0 +1,231 @@
+use std::fs::{self, File};
+use std::io::{Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::common::{DatabaseRef, Streaming};
+use crate::errors::Result;
+use crate::ffi;
+use crate::runtime::{read_chunk, MatchEventHandler, ScanOutcome, ScratchRef, Stream, SCAN_BUF_SIZE};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A streaming scanner following a growing file like `tail -f`.
+///
+/// The stream is kept open while the file is growing, the match offsets are relative to the start of the stream.
+/// When the file is truncated or rotated (the path refers to a new file), the stream is reset,
+/// the end of data matches are reported, and the new content is scanned from the beginning.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::fs::{self, OpenOptions};
+/// # use std::io::Write;
+/// # use hyperscan::{prelude::*, FollowScanner};
+/// let path = std::env::temp_dir().join(format!("hyperscan-follow-{}.log", std::process::id()));
+/// fs::write(&path, "foo te").unwrap();
+///
+/// let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut scanner = FollowScanner::open(&db, &path).unwrap();
+/// let mut matches = vec![];
+///
+/// let mut callback = |_, from, to, _| {
+///     matches.push((from, to));
+///     Matching::Continue
+/// };
+///
+/// assert_eq!(scanner.poll(&s, &mut callback).unwrap(), 6);
+///
+/// OpenOptions::new().append(true).open(&path).unwrap().write_all(b"st bar").unwrap();
+/// assert_eq!(scanner.poll(&s, &mut callback).unwrap(), 6);
+///
+/// fs::write(&path, "test").unwrap();
+/// assert_eq!(scanner.poll(&s, &mut callback).unwrap(), 4);
+///
+/// scanner.close(&s, callback).unwrap();
+/// fs::remove_file(&path).unwrap();
+///
+/// assert_eq!(matches, vec![(4, 8), (0, 4)]);
+/// ```
+pub struct FollowScanner {
+    path: PathBuf,
+    file: File,
+    stream: Stream,
+    pos: u64,
+    interval: Duration,
+    buf: Box<[u8]>,
+    terminated: bool,
+}
+
+impl FollowScanner {
+    /// Open the file and follow it from the beginning.
+    pub fn open<P: AsRef<Path>>(db: &DatabaseRef<Streaming>, path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let file = File::open(&path)?;
+        let stream = db.open_stream()?;
+
+        Ok(FollowScanner {
+            path,
+            file,
+            stream,
+            pos: 0,
+            interval: DEFAULT_POLL_INTERVAL,
+            buf: vec![0; SCAN_BUF_SIZE].into_boxed_slice(),
+            terminated: false,
+        })
+    }
+
+    /// Skip the existing content of the file, only the data appended later will be scanned.
+    pub fn skip_to_end(mut self) -> Result<Self> {
+        self.pos = self.file.seek(SeekFrom::End(0))?;
+        Ok(self)
+    }
+
+    /// The interval of polling the file when there is no new data.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Returns the path of the followed file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the offset of the followed file which has been scanned.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Returns `true` if the scanning was terminated by the callback,
+    /// the following data is not scanned until the stream is reset by a truncation or rotation of the file.
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Scan the data appended since the last poll, returns the number of bytes scanned.
+    ///
+    /// The stream is reset if the file was truncated or rotated.
+    pub fn poll<F>(&mut self, scratch: &ScratchRef, mut on_match_event: F) -> Result<usize>
+    where
+        F: MatchEventHandler,
+    {
+        let handler = unsafe { on_match_event.split() };
+
+        if self.file.metadata()?.len() < self.pos {
+            self.terminated = self.stream.reset(scratch, handler)?.is_terminated();
+            self.pos = self.file.seek(SeekFrom::Start(0))?;
+        }
+
+        let mut scanned = self.drain(scratch, handler)?;
+
+        if self.is_rotated() {
+            if let Ok(file) = File::open(&self.path) {
+                // scan the data written to the old file before it was rotated
+                scanned += self.drain(scratch, handler)?;
+
+                self.terminated = self.stream.reset(scratch, handler)?.is_terminated();
+                self.file = file;
+                self.pos = 0;
+
+                scanned += self.drain(scratch, handler)?;
+            }
+        }
+
+        Ok(scanned)
+    }
+
+    /// Keep following the file until the scanning is terminated by the callback, or failed.
+    pub fn follow<F>(&mut self, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    where
+        F: MatchEventHandler,
+    {
+        let handler = unsafe { on_match_event.split() };
+
+        loop {
+            let scanned = self.poll(scratch, handler)?;
+
+            if self.terminated {
+                return Ok(());
+            }
+            if scanned == 0 {
+                thread::sleep(self.interval);
+            }
+        }
+    }
+
+    /// Close the stream, the end of data matches are reported.
+    pub fn close<F>(self, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        F: MatchEventHandler,
+    {
+        self.stream.close(scratch, on_match_event)
+    }
+
+    fn drain(&mut self, scratch: &ScratchRef, handler: (ffi::match_event_handler, *mut libc::c_void)) -> Result<usize> {
+        let mut scanned = 0;
+
+        while !self.terminated {
+            let len = read_chunk(&mut self.file, &mut self.buf, false)?;
+
+            if len == 0 {
+                break;
+            }
+
+            let outcome = self.stream.scan(&self.buf[..len], scratch, handler)?;
+
+            self.pos += len as u64;
+            self.terminated = outcome == ScanOutcome::Terminated;
+            scanned += len;
+        }
+
+        Ok(scanned)
+    }
+
+    #[cfg(unix)]
+    fn is_rotated(&self) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        match (fs::metadata(&self.path), self.file.metadata()) {
+            (Ok(new), Ok(old)) => new.dev() != old.dev() || new.ino() != old.ino(),
+            _ => false,
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn is_rotated(&self) -> bool {
+        fs::metadata(&self.path).map_or(false, |md| md.len() < self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_follow_terminated() {
+        let path = std::env::temp_dir().join(format!("hyperscan-follow-terminated-{}.log", std::process::id()));
+        fs::write(&path, "foo test bar test").unwrap();
+
+        let db: StreamingDatabase = pattern! {"test"}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let mut scanner = FollowScanner::open(&db, &path).unwrap();
+        let mut matches = 0;
+
+        scanner
+            .follow(&s, |_, _, _, _| {
+                matches += 1;
+                Matching::Terminate
+            })
+            .unwrap();
+
+        assert!(scanner.is_terminated());
+        assert_eq!(matches, 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::common::DatabaseRef;
+use crate::errors::Result;
+use crate::runtime::{Matching, Scan, ScratchRef};
+
+/// Accumulates the number of matches per pattern id across scans.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, MatchHistogram};
+/// let db: BlockDatabase = patterns!("foo", "bar").build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut histogram = MatchHistogram::new();
+///
+/// for data in &["foo bar foo", "foo"] {
+///     db.scan(data, &s, histogram.wrap(|_, _, _, _| Matching::Continue)).unwrap();
+/// }
+///
+/// assert_eq!(histogram.get(0), 3);
+/// assert_eq!(histogram.get(1), 1);
+/// assert_eq!(histogram.total(), 4);
+/// assert_eq!(histogram.noisiest(1), vec![(0, 3)]);
+///
+/// let snapshot = histogram.take();
+///
+/// assert_eq!(snapshot.len(), 2);
+/// assert_eq!(histogram.total(), 0);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MatchHistogram {
+    counts: HashMap<u32, u64>,
+}
+
+impl MatchHistogram {
+    /// Constructs a new, empty `MatchHistogram`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of matches of the pattern.
+    pub fn get(&self, id: u32) -> u64 {
+        self.counts.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of matches of all patterns.
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    /// Returns the `n` patterns with the most matches, in descending order.
+    pub fn noisiest(&self, n: usize) -> Vec<(u32, u64)> {
+        let mut counts = self.counts.iter().map(|(&id, &count)| (id, count)).collect::<Vec<_>>();
+
+        counts.sort_by(|(lid, lhs), (rid, rhs)| rhs.cmp(lhs).then(lid.cmp(rid)));
+        counts.truncate(n);
+        counts
+    }
+
+    /// Returns a copy of the current histogram.
+    pub fn snapshot(&self) -> HashMap<u32, u64> {
+        self.counts.clone()
+    }
+
+    /// Returns the current histogram and resets the counters.
+    pub fn take(&mut self) -> HashMap<u32, u64> {
+        std::mem::take(&mut self.counts)
+    }
+
+    /// Resets the counters.
+    pub fn reset(&mut self) {
+        self.counts.clear()
+    }
+
+    /// Record a match of the pattern.
+    pub fn record(&mut self, id: u32) {
+        *self.counts.entry(id).or_default() += 1;
+    }
+
+    /// Wrap a match event callback that counts every match before delivering it.
+    pub fn wrap<'a, F>(&'a mut self, mut on_match_event: F) -> impl FnMut(u32, u64, u64, u32) -> Matching + 'a
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching + 'a,
+    {
+        move |id, from, to, flags| {
+            self.record(id);
+
+            on_match_event(id, from, to, flags)
+        }
+    }
+}
+
+impl<T> DatabaseRef<T>
+where
+    DatabaseRef<T>: Scan,
+{
+    /// Scan the data and count the matches per pattern id, without a callback.
+    ///
+    /// The data of the streaming database is scanned as a whole stream.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{patterns, prelude::*};
+    /// let db: BlockDatabase = patterns!("foo", "bar").build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let counts = db.count("foo bar foo", &s).unwrap();
+    ///
+    /// assert_eq!(counts.get(0), 2);
+    /// assert_eq!(counts.get(1), 1);
+    /// assert_eq!(counts.total(), 3);
+    /// ```
+    pub fn count<D: AsRef<[u8]>>(&self, data: D, scratch: &ScratchRef) -> Result<MatchHistogram> {
+        let mut histogram = MatchHistogram::new();
+
+        Scan::scan(self, data, scratch, |id, _, _, _| {
+            histogram.record(id);
+            Matching::Continue
+        })?;
+
+        Ok(histogram)
+    }
+}
@@ -0,0 +1,156 @@
+use std::ops::Range;
+use std::vec;
+
+use crate::common::{Block, DatabaseRef};
+use crate::errors::Result;
+use crate::runtime::{Matching, ScratchRef};
+
+/// A match event reported by the scan.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Match {
+    /// The id of the matched pattern.
+    pub id: u32,
+    /// The start offset of the match, only meaningful for the patterns with `SOM_LEFTMOST` flag.
+    pub start: u64,
+    /// The end offset of the match.
+    pub end: u64,
+    /// The flags of the match event.
+    pub flags: u32,
+}
+
+impl Match {
+    /// Returns the range of the match in the scanned data.
+    pub fn range(&self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+
+    /// Adapt a callback taking a `Match` to a match event handler,
+    /// instead of the positional arguments which are easy to swap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, Match};
+    /// let db: BlockDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let data = "foo test bar";
+    /// let mut matched = vec![];
+    ///
+    /// db.scan(data, &s, Match::handler(|m| {
+    ///     matched.push(&data[m.range()]);
+    ///     Matching::Continue
+    /// })).unwrap();
+    ///
+    /// assert_eq!(matched, vec!["test"]);
+    /// ```
+    pub fn handler<F>(mut on_match: F) -> impl FnMut(u32, u64, u64, u32) -> Matching
+    where
+        F: FnMut(Match) -> Matching,
+    {
+        move |id, start, end, flags| on_match(Match { id, start, end, flags })
+    }
+}
+
+impl From<Match> for Range<usize> {
+    fn from(m: Match) -> Self {
+        m.range()
+    }
+}
+
+impl DatabaseRef<Block> {
+    /// Scan the data and collect all the matches in the order they were reported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, Match};
+    /// let db: BlockDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    ///
+    /// assert_eq!(
+    ///     db.scan_matches("foo test bar", &s).unwrap(),
+    ///     vec![Match { id: 0, start: 4, end: 8, flags: 0 }]
+    /// );
+    /// ```
+    pub fn scan_matches<T: AsRef<[u8]>>(&self, data: T, scratch: &ScratchRef) -> Result<Vec<Match>> {
+        let mut matches = vec![];
+
+        let _ = self.scan(
+            data,
+            scratch,
+            Match::handler(|m| {
+                matches.push(m);
+                Matching::Continue
+            }),
+        )?;
+
+        Ok(matches)
+    }
+
+    /// Returns an iterator over all the matches of the data in the order they were reported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: BlockDatabase = pattern! {"a+"; SOM_LEFTMOST}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    ///
+    /// let ends = db.find_iter("baaab", &s).unwrap().map(|m| m.end).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(ends, vec![2, 3, 4]);
+    /// ```
+    pub fn find_iter<T: AsRef<[u8]>>(&self, data: T, scratch: &ScratchRef) -> Result<vec::IntoIter<Match>> {
+        self.scan_matches(data, scratch).map(Vec::into_iter)
+    }
+
+    /// Returns `true` if any pattern matches the data, the scan is terminated on the first match.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    ///
+    /// assert!(db.is_match("foo test bar", &s).unwrap());
+    /// assert!(!db.is_match("foo bar", &s).unwrap());
+    /// ```
+    pub fn is_match<T: AsRef<[u8]>>(&self, data: T, scratch: &ScratchRef) -> Result<bool> {
+        self.scan(data, scratch, Matching::Terminate)
+            .map(|outcome| outcome.is_terminated())
+    }
+
+    /// Returns the first match reported by the scan, the scan is terminated on the first match.
+    ///
+    /// The matches are reported in the order of their end offsets,
+    /// so it's the match which ends first rather than the leftmost one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, Match};
+    /// let db: BlockDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    ///
+    /// assert_eq!(
+    ///     db.find_first("foo test bar test", &s).unwrap(),
+    ///     Some(Match { id: 0, start: 4, end: 8, flags: 0 })
+    /// );
+    /// assert_eq!(db.find_first("foo bar", &s).unwrap(), None);
+    /// ```
+    pub fn find_first<T: AsRef<[u8]>>(&self, data: T, scratch: &ScratchRef) -> Result<Option<Match>> {
+        let mut first = None;
+
+        let _ = self.scan(
+            data,
+            scratch,
+            Match::handler(|m| {
+                first = Some(m);
+                Matching::Terminate
+            }),
+        )?;
+
+        Ok(first)
+    }
+}
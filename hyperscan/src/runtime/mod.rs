@@ -1,11 +1,79 @@
-mod closure;
+mod auto;
+#[cfg(feature = "blocking")]
+mod blocking;
+mod buffered;
+mod cancel;
+mod chunked;
+mod compare;
+mod context;
+mod dedup;
+mod fallible;
+mod filter;
+mod follow;
+mod histogram;
+mod matches;
+mod options;
 #[cfg(feature = "pattern")]
 mod pattern;
+mod pool;
+mod prefilter;
+mod prepared;
+mod record;
+mod reorder;
+mod retry;
+mod ring;
 mod scan;
+mod scanner;
+mod scoped;
 mod scratch;
+#[cfg(feature = "zeroize")]
+pub(crate) mod scrub;
+mod selftest;
+mod session;
+#[cfg(feature = "parallel")]
+mod shard;
+mod sink;
+mod stats;
 mod stream;
+mod suppress;
+mod throttle;
+mod tracked;
+mod validate;
+mod width;
 
-pub use self::closure::split_closure;
-pub use self::scan::{MatchEventHandler, Matching};
+pub use self::buffered::BufferedStream;
+pub use self::cancel::CancellationToken;
+pub use self::chunked::{ChunkMatch, StreamScanner};
+pub use self::compare::{Comparison, ComparisonReport, RuleDiff};
+pub use self::dedup::SingleMatch;
+pub use self::filter::PatternFilter;
+pub use self::follow::FollowScanner;
+pub use self::histogram::MatchHistogram;
+pub use self::matches::Match;
+pub use self::options::ScanOptions;
+pub use self::pool::{OwnedScratch, PooledScratch, ScratchPool};
+pub use self::prefilter::Prefiltered;
+pub use self::prepared::PreparedCallback;
+pub use self::record::{Event as RecordedEvent, Recorder, Replayer};
+pub use self::reorder::Reorder;
+pub use self::retry::{clear_alloc_failure_hook, set_alloc_failure_hook};
+pub use self::ring::RingBuffer;
+pub use self::scan::{
+    matched_bytes, MatchEventHandler, Matching, RawMatchEventHandler, ScanOutcome, VectoredBuffer, MATCH_AT_EOD,
+};
+pub(crate) use self::scan::{read_chunk, SCAN_BUF_SIZE};
+pub use self::scanner::Scan;
+pub use self::scoped::ScopedStream;
 pub use self::scratch::{Scratch, ScratchRef};
+pub use self::selftest::SelfTest;
+pub use self::session::SessionTable;
+#[cfg(feature = "parallel")]
+pub use self::shard::Sharded;
+pub use self::sink::MatchSink;
+pub use self::stats::{Distribution, MatchStats, PatternStats};
 pub use self::stream::{Stream, StreamRef};
+pub use self::suppress::{Suppression, Suppressor, Window};
+pub use self::throttle::{Throttle, Throttled};
+pub use self::tracked::{StreamStats, TrackedStream};
+pub use self::validate::Validators;
+pub use self::width::MinWidth;
@@ -0,0 +1,112 @@
+use std::io::Read;
+
+use crate::common::{DatabaseRef, Streaming};
+use crate::errors::Result;
+use crate::runtime::{read_chunk, MatchEventHandler, ScanOutcome, ScratchRef, SCAN_BUF_SIZE};
+
+/// The options of scanning a reader with a stream-mode database.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::Cursor;
+/// # use hyperscan::{prelude::*, ScanOptions};
+/// let db: StreamingDatabase = pattern! { "a+"; SOM_LEFTMOST }.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let options = ScanOptions::new().with_chunk_size(1 << 20).with_fill(true);
+/// let mut matches = vec![];
+///
+/// db.scan_with_options(&mut Cursor::new("xxbaaab"), options, &s, |_, from, to, _| {
+///     matches.push((from, to));
+///
+///     Matching::Continue
+/// })
+/// .unwrap();
+///
+/// assert_eq!(matches, vec![(3, 4), (3, 5), (3, 6)]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScanOptions {
+    chunk_size: usize,
+    fill: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            chunk_size: SCAN_BUF_SIZE,
+            fill: false,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Constructs the default options, scanning each read of at most 4096 bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the size of the buffer which the data is read into and scanned from.
+    ///
+    /// A larger chunk reduces the per-call overhead of scanning a large input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chunk size is zero.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be positive");
+
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Set whether to fill the buffer before scanning it, instead of scanning each short read.
+    pub fn with_fill(mut self, fill: bool) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Returns the size of the buffer.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns `true` if the buffer is filled before scanning it.
+    pub fn fill(&self) -> bool {
+        self.fill
+    }
+}
+
+impl DatabaseRef<Streaming> {
+    /// Pattern matching takes place for stream-mode pattern databases with the options of reading.
+    pub fn scan_with_options<R, F>(
+        &self,
+        reader: &mut R,
+        options: ScanOptions,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        R: Read,
+        F: MatchEventHandler,
+    {
+        let stream = self.open_stream()?;
+        let mut buf = vec![0; options.chunk_size];
+
+        let (callback, userdata) = unsafe { on_match_event.split() };
+
+        loop {
+            let len = read_chunk(reader, &mut buf, options.fill)?;
+
+            if len == 0 {
+                break;
+            }
+
+            if stream.scan(&buf[..len], scratch, (callback, userdata))?.is_terminated() {
+                return Ok(ScanOutcome::Terminated);
+            }
+        }
+
+        stream.close(scratch, (callback, userdata))
+    }
+}
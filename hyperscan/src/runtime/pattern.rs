@@ -13,33 +13,34 @@ impl<'a> pattern::Pattern<'a> for compile::Pattern {
         let scratch = db.alloc_scratch().expect("alloc scratch");
         let mut matches = Vec::new();
 
-        db.scan(haystack, &scratch, |_, from, to, _| {
-            let from = from as usize;
-            let to = to as usize;
+        let _ = db
+            .scan(haystack, &scratch, |_, from, to, _| {
+                let from = from as usize;
+                let to = to as usize;
 
-            match matches.last() {
-                Some(&SearchStep::Match(start, end)) => {
-                    if start == from && end < to {
-                        // only the non-overlapping match should be return
-                        *matches.last_mut().unwrap() = SearchStep::Match(from, to);
-                    } else {
-                        if end < from {
-                            matches.push(SearchStep::Reject(end, from))
-                        }
+                match matches.last() {
+                    Some(&SearchStep::Match(start, end)) => {
+                        if start == from && end < to {
+                            // only the non-overlapping match should be return
+                            *matches.last_mut().unwrap() = SearchStep::Match(from, to);
+                        } else {
+                            if end < from {
+                                matches.push(SearchStep::Reject(end, from))
+                            }
 
-                        matches.push(SearchStep::Match(from, to))
+                            matches.push(SearchStep::Match(from, to))
+                        }
                     }
+                    None => {
+                        matches.push(SearchStep::Reject(0, from));
+                        matches.push(SearchStep::Match(from, to));
+                    }
+                    _ => matches.push(SearchStep::Match(from, to)),
                 }
-                None => {
-                    matches.push(SearchStep::Reject(0, from));
-                    matches.push(SearchStep::Match(from, to));
-                }
-                _ => matches.push(SearchStep::Match(from, to)),
-            }
 
-            Matching::Continue
-        })
-        .expect("scan");
+                Matching::Continue
+            })
+            .expect("scan");
 
         match matches.last() {
             Some(&SearchStep::Match(_, end)) if end < haystack.len() => {
@@ -0,0 +1,270 @@
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::common::DatabaseRef;
+use crate::errors::Result;
+use crate::runtime::Scratch;
+
+const DEFAULT_MAX_IDLE: usize = 64;
+
+/// A pool of scratch spaces handed out to the scanning tasks, and returned to the pool when they are dropped.
+///
+/// The scratch space is `Send` but not `Sync`, each spawned task or worker checks out its own scratch,
+/// so the concurrently polled futures never share a scratch space.
+/// The pool is runtime agnostic, `ScratchPool::get_owned()` returns a `'static` guard for `tokio::spawn`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use std::thread;
+/// # use hyperscan::{prelude::*, ScratchPool};
+/// let db: Arc<BlockDatabase> = Arc::new(pattern! {"test"}.build().unwrap());
+/// let pool = Arc::new(ScratchPool::new(&db).unwrap());
+///
+/// let handles = (0..4)
+///     .map(|_| {
+///         let db = db.clone();
+///         let pool = pool.clone();
+///
+///         thread::spawn(move || {
+///             let s = pool.get_owned().unwrap();
+///             let mut n = 0;
+///
+///             db.scan("some test data", &s, |_, _, _, _| {
+///                 n += 1;
+///                 Matching::Continue
+///             }).unwrap();
+///
+///             n
+///         })
+///     })
+///     .collect::<Vec<_>>();
+///
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), 1);
+/// }
+///
+/// assert!(pool.idle() > 0);
+/// ```
+pub struct ScratchPool {
+    prototype: Mutex<Scratch>,
+    idle: Mutex<Vec<Scratch>>,
+    generation: AtomicUsize,
+    max_idle: usize,
+}
+
+impl fmt::Debug for ScratchPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScratchPool")
+            .field("idle", &self.idle())
+            .field("max_idle", &self.max_idle)
+            .finish()
+    }
+}
+
+impl ScratchPool {
+    /// Constructs a pool of the scratch spaces for the database.
+    pub fn new<T>(db: &DatabaseRef<T>) -> Result<Self> {
+        Ok(ScratchPool {
+            prototype: Mutex::new(db.alloc_scratch()?),
+            idle: Mutex::new(vec![]),
+            generation: AtomicUsize::new(0),
+            max_idle: DEFAULT_MAX_IDLE,
+        })
+    }
+
+    /// Set the maximum number of the idle scratch spaces kept in the pool, 64 by default.
+    pub fn max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Returns the number of the idle scratch spaces in the pool.
+    pub fn idle(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+
+    /// Release the idle scratch spaces, returns the number of the released scratch spaces.
+    pub fn shrink(&self) -> usize {
+        self.idle.lock().unwrap().drain(..).count()
+    }
+
+    /// Grow the scratch spaces to support another database, e.g. when the database is reloaded.
+    ///
+    /// The idle scratch spaces are released, and the scratch spaces in use are not returned to the pool.
+    pub fn grow<T>(&self, db: &DatabaseRef<T>) -> Result<()> {
+        let mut prototype = self.prototype.lock().unwrap();
+
+        db.realloc_scratch(&mut prototype)?;
+
+        let mut idle = self.idle.lock().unwrap();
+
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        idle.clear();
+
+        Ok(())
+    }
+
+    /// Check out a scratch space, which is returned to the pool when the guard is dropped.
+    pub fn get(&self) -> Result<PooledScratch<'_>> {
+        let (scratch, generation) = self.checkout()?;
+
+        Ok(PooledScratch {
+            pool: self,
+            scratch: ManuallyDrop::new(scratch),
+            generation,
+        })
+    }
+
+    /// Check out a scratch space with a `'static` guard, which can be moved into a spawned task.
+    pub fn get_owned(self: &Arc<Self>) -> Result<OwnedScratch> {
+        let (scratch, generation) = self.checkout()?;
+
+        Ok(OwnedScratch {
+            pool: self.clone(),
+            scratch: ManuallyDrop::new(scratch),
+            generation,
+        })
+    }
+
+    fn checkout(&self) -> Result<(Scratch, usize)> {
+        {
+            // the generation is bumped with the idle scratch spaces locked, so they always match
+            let mut idle = self.idle.lock().unwrap();
+
+            if let Some(scratch) = idle.pop() {
+                return Ok((scratch, self.generation.load(Ordering::Acquire)));
+            }
+        }
+
+        // the prototype is only locked to clone a new scratch space, so it isn't reallocated meanwhile
+        let prototype = self.prototype.lock().unwrap();
+        let generation = self.generation.load(Ordering::Acquire);
+
+        prototype.try_clone().map(|scratch| (scratch, generation))
+    }
+
+    fn checkin(&self, scratch: Scratch, generation: usize) {
+        let mut idle = self.idle.lock().unwrap();
+
+        // the scratch checked out before the pool was grown is too small for the new database
+        if generation == self.generation.load(Ordering::Acquire) && idle.len() < self.max_idle {
+            idle.push(scratch);
+        }
+    }
+}
+
+macro_rules! impl_guard {
+    ($guard:ty) => {
+        impl Deref for $guard {
+            type Target = Scratch;
+
+            fn deref(&self) -> &Self::Target {
+                &self.scratch
+            }
+        }
+
+        impl DerefMut for $guard {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.scratch
+            }
+        }
+
+        impl Drop for $guard {
+            fn drop(&mut self) {
+                let scratch = unsafe { ManuallyDrop::take(&mut self.scratch) };
+
+                self.pool.checkin(scratch, self.generation);
+            }
+        }
+    };
+}
+
+/// A scratch space checked out from the `ScratchPool`.
+pub struct PooledScratch<'a> {
+    pool: &'a ScratchPool,
+    scratch: ManuallyDrop<Scratch>,
+    generation: usize,
+}
+
+impl_guard!(PooledScratch<'_>);
+
+/// A scratch space checked out from the shared `ScratchPool`, which can be moved into a spawned task.
+pub struct OwnedScratch {
+    pool: Arc<ScratchPool>,
+    scratch: ManuallyDrop<Scratch>,
+    generation: usize,
+}
+
+impl_guard!(OwnedScratch);
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::runtime::ScanOutcome;
+
+    use super::*;
+
+    #[test]
+    fn test_checkout() {
+        let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+        let pool = ScratchPool::new(&db).unwrap().max_idle(1);
+
+        assert_eq!(pool.idle(), 0);
+
+        {
+            let s1 = pool.get().unwrap();
+            let s2 = pool.get().unwrap();
+
+            assert_eq!(db.scan("test", &s1, ()).unwrap(), ScanOutcome::Completed);
+            assert_eq!(db.scan("test", &s2, ()).unwrap(), ScanOutcome::Completed);
+        }
+
+        assert_eq!(pool.idle(), 1);
+
+        let s = pool.get().unwrap();
+
+        assert_eq!(pool.idle(), 0);
+
+        drop(s);
+
+        assert_eq!(pool.idle(), 1);
+        assert_eq!(pool.shrink(), 1);
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn test_grow() {
+        let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+        let pool = Arc::new(ScratchPool::new(&db).unwrap());
+
+        let old = pool.get_owned().unwrap();
+
+        drop(pool.get().unwrap());
+
+        assert_eq!(pool.idle(), 1);
+
+        let db2: VectoredDatabase = "foobar".parse().unwrap();
+
+        pool.grow(&db2).unwrap();
+
+        assert_eq!(pool.idle(), 0);
+
+        drop(old);
+
+        assert_eq!(pool.idle(), 0);
+
+        let s = pool.get_owned().unwrap();
+
+        assert!(s.size().unwrap() >= db2.alloc_scratch().unwrap().size().unwrap());
+        assert_eq!(db2.scan(&["foo", "bar"], &s, ()).unwrap(), ScanOutcome::Completed);
+
+        drop(s);
+
+        assert_eq!(pool.idle(), 1);
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::common::BlockDatabase;
+#[cfg(all(feature = "compile", feature = "literal"))]
+use crate::compile::{Builder, Patterns};
+#[cfg(all(feature = "compile", feature = "literal"))]
+use crate::errors::Error;
+use crate::errors::Result;
+use crate::runtime::{MatchEventHandler, Matching, ScanOutcome, Scratch, ScratchRef};
+
+/// A block mode database gated by a cheap literal database,
+/// the full database only scans the data which contains any required literal of the patterns.
+///
+/// Most inputs of some workloads match nothing, the literal database rejects them
+/// much faster than the full database, e.g. the complex patterns sharing a rare keyword.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, Prefiltered};
+/// let patterns = patterns!(r"token=[0-9a-f]{8}", r"password:\s*\S+");
+/// let db = Prefiltered::build(&patterns).unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut matches = vec![];
+///
+/// assert!(db.is_gated());
+///
+/// for data in &["nothing to see here", "password: hunter2"] {
+///     db.scan(data, &s, |id, _, _, _| {
+///         matches.push(id);
+///         Matching::Continue
+///     }).unwrap();
+/// }
+///
+/// assert_eq!(matches, vec![1]);
+/// assert_eq!(db.skipped(), 1);
+/// ```
+pub struct Prefiltered {
+    db: BlockDatabase,
+    gate: Option<BlockDatabase>,
+    skipped: AtomicU64,
+}
+
+impl Prefiltered {
+    /// Constructs a gated database, the data is always scanned by the full database without a gate.
+    ///
+    /// The gate must match some data if the full database matches it, otherwise the matches will be missed.
+    pub fn new(db: BlockDatabase, gate: Option<BlockDatabase>) -> Self {
+        Prefiltered {
+            db,
+            gate,
+            skipped: AtomicU64::new(0),
+        }
+    }
+
+    /// Compile the patterns and the gate of their required literals.
+    ///
+    /// The gate is omitted if any pattern has no required literal, see `Patterns::required_literals()`.
+    #[cfg(all(feature = "compile", feature = "literal"))]
+    pub fn build(patterns: &Patterns) -> Result<Self> {
+        let db = patterns.build().map_err(Error::from_build)?;
+        let gate = patterns
+            .required_literals()
+            .map(|literals| literals.build())
+            .transpose()
+            .map_err(Error::from_build)?;
+
+        Ok(Self::new(db, gate))
+    }
+
+    /// Returns `true` if the database is gated.
+    pub fn is_gated(&self) -> bool {
+        self.gate.is_some()
+    }
+
+    /// Returns the full database.
+    pub fn database(&self) -> &BlockDatabase {
+        &self.db
+    }
+
+    /// Returns the number of the scans which were rejected by the gate.
+    pub fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// Allocate a scratch space for both the full database and the gate.
+    pub fn alloc_scratch(&self) -> Result<Scratch> {
+        let mut s = self.db.alloc_scratch()?;
+
+        if let Some(ref gate) = self.gate {
+            gate.realloc_scratch(&mut s)?;
+        }
+
+        Ok(s)
+    }
+
+    /// Returns `true` if the data passes the gate, which should be scanned by the full database.
+    pub fn passes<T: AsRef<[u8]>>(&self, data: T, scratch: &ScratchRef) -> Result<bool> {
+        let gate = match self.gate {
+            Some(ref gate) => gate,
+            None => return Ok(true),
+        };
+
+        gate.scan(data, scratch, Matching::Terminate)
+            .map(|outcome| outcome.is_terminated())
+    }
+
+    /// The block regular expression scanner, skips the data rejected by the gate.
+    pub fn scan<T, F>(&self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let data = data.as_ref();
+
+        if self.passes(data, scratch)? {
+            self.db.scan(data, scratch, on_match_event)
+        } else {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+
+            Ok(ScanOutcome::Completed)
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::ffi;
+use crate::runtime::{MatchEventHandler, Matching};
+
+/// A match event callback which is split into the C callback and userdata once,
+/// and reused across many scan calls on the same thread.
+///
+/// The closure is mutably borrowed by the prepared callback, and it is `Copy`,
+/// so it can be passed to each call without splitting the closure again.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, PreparedCallback};
+/// let db: BlockDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut matches = vec![];
+///
+/// let mut callback = |_, from, to, _| {
+///     matches.push(from..to);
+///     Matching::Continue
+/// };
+/// let prepared = PreparedCallback::new(&mut callback);
+///
+/// for data in &["some test", "test data"] {
+///     db.scan(data, &s, prepared).unwrap();
+/// }
+///
+/// assert_eq!(matches, vec![5..9, 0..4]);
+/// ```
+pub struct PreparedCallback<'a, F> {
+    callback: ffi::match_event_handler,
+    userdata: *mut libc::c_void,
+    _closure: PhantomData<&'a mut F>,
+}
+
+impl<F> Clone for PreparedCallback<'_, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for PreparedCallback<'_, F> {}
+
+impl<F> fmt::Debug for PreparedCallback<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedCallback")
+            .field("userdata", &self.userdata)
+            .finish()
+    }
+}
+
+impl<'a, F> PreparedCallback<'a, F>
+where
+    F: FnMut(u32, u64, u64, u32) -> Matching,
+{
+    /// Prepare the closure for the scan calls.
+    pub fn new(closure: &'a mut F) -> Self {
+        let (callback, userdata) = unsafe { closure.split() };
+
+        PreparedCallback {
+            callback,
+            userdata,
+            _closure: PhantomData,
+        }
+    }
+}
+
+impl<F> MatchEventHandler for PreparedCallback<'_, F> {
+    unsafe fn split(&mut self) -> (ffi::match_event_handler, *mut libc::c_void) {
+        (self.callback, self.userdata)
+    }
+}
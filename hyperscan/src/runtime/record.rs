@@ -0,0 +1,512 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::common::{Block, DatabaseRef, Streaming, Vectored};
+use crate::errors::{Error, Result};
+use crate::runtime::{MatchEventHandler, ScanOutcome, ScratchRef, Stream, StreamRef};
+
+const MAGIC: &[u8; 6] = b"HSREC\x01";
+
+/// A scan call captured by the `Recorder`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A block mode scan of the data.
+    Block(Vec<u8>),
+    /// A vectored mode scan of the chunks.
+    Vectored(Vec<Vec<u8>>),
+    /// A stream was opened with the id.
+    Open(u32),
+    /// The data was written to the stream.
+    Write(u32, Vec<u8>),
+    /// The stream was reset.
+    Reset(u32),
+    /// The stream was closed.
+    Close(u32),
+}
+
+impl Event {
+    const BLOCK: u8 = 1;
+    const VECTORED: u8 = 2;
+    const OPEN: u8 = 3;
+    const WRITE: u8 = 4;
+    const RESET: u8 = 5;
+    const CLOSE: u8 = 6;
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            Event::Block(data) => {
+                w.write_all(&[Self::BLOCK])?;
+                write_bytes(w, data)
+            }
+            Event::Vectored(chunks) => {
+                w.write_all(&[Self::VECTORED])?;
+                w.write_all(&(chunks.len() as u32).to_le_bytes())?;
+                chunks.iter().try_for_each(|chunk| write_bytes(w, chunk))
+            }
+            Event::Open(id) => {
+                w.write_all(&[Self::OPEN])?;
+                w.write_all(&id.to_le_bytes())
+            }
+            Event::Write(id, data) => {
+                w.write_all(&[Self::WRITE])?;
+                w.write_all(&id.to_le_bytes())?;
+                write_bytes(w, data)
+            }
+            Event::Reset(id) => {
+                w.write_all(&[Self::RESET])?;
+                w.write_all(&id.to_le_bytes())
+            }
+            Event::Close(id) => {
+                w.write_all(&[Self::CLOSE])?;
+                w.write_all(&id.to_le_bytes())
+            }
+        }
+    }
+
+    fn read_from<R: Read>(r: &mut R) -> Result<Option<Self>> {
+        let mut tag = [0];
+
+        if r.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(match tag[0] {
+            Self::BLOCK => Event::Block(read_bytes(r)?),
+            Self::VECTORED => {
+                let n = read_u32(r)?;
+
+                Event::Vectored((0..n).map(|_| read_bytes(r)).collect::<io::Result<_>>()?)
+            }
+            Self::OPEN => Event::Open(read_u32(r)?),
+            Self::WRITE => Event::Write(read_u32(r)?, read_bytes(r)?),
+            Self::RESET => Event::Reset(read_u32(r)?),
+            Self::CLOSE => Event::Close(read_u32(r)?),
+            tag => return Err(invalid_data(format!("unexpected event tag: {}", tag))),
+        }))
+    }
+}
+
+/// The error of a malformed recording, which is reported as `Error::Io` with the `InvalidData` kind.
+fn invalid_data<E>(err: E) -> Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, err).into()
+}
+
+fn write_bytes<W: Write>(w: &mut W, data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u64).to_le_bytes())?;
+    w.write_all(data)
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_bytes<R: Read>(r: &mut R) -> io::Result<Vec<u8>> {
+    let mut len = [0; 8];
+    r.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len);
+    // read incrementally, so a corrupted length can't allocate the memory upfront
+    let mut data = vec![];
+    r.by_ref().take(len).read_to_end(&mut data)?;
+    if data.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(data)
+}
+
+/// Records the exact sequence of scan calls, so they can be reproduced later by the `Replayer`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, Recorder, Replayer};
+/// let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut recorder = Recorder::new(vec![]).unwrap();
+///
+/// let (id, st) = recorder.open_stream(&db).unwrap();
+/// recorder.scan_stream(id, &st, "foo t", &s, Matching::Continue).unwrap();
+/// recorder.scan_stream(id, &st, "est bar", &s, Matching::Continue).unwrap();
+/// recorder.close_stream(id, st, &s, Matching::Continue).unwrap();
+///
+/// let log = recorder.into_inner().unwrap();
+/// let mut matches = vec![];
+///
+/// Replayer::new(&log[..]).unwrap().replay_streaming(&db, &s, |_, from, to, _| {
+///     matches.push(from..to);
+///     Matching::Continue
+/// }).unwrap();
+///
+/// assert_eq!(matches, vec![4..8]);
+/// ```
+pub struct Recorder<W: Write> {
+    w: W,
+    next_stream_id: u32,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Create a recording file.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Recorder::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Constructs a recorder which writes the scan calls to the writer.
+    pub fn new(mut w: W) -> Result<Self> {
+        w.write_all(MAGIC)?;
+
+        Ok(Recorder { w, next_stream_id: 0 })
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.w.flush()?;
+
+        Ok(self.w)
+    }
+
+    /// Record an event.
+    pub fn record(&mut self, event: &Event) -> Result<()> {
+        event.write_to(&mut self.w).map_err(Into::into)
+    }
+
+    /// Record and perform a block mode scan.
+    pub fn scan<T, F>(
+        &mut self,
+        db: &DatabaseRef<Block>,
+        data: T,
+        scratch: &ScratchRef,
+        on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let data = data.as_ref();
+
+        self.record(&Event::Block(data.to_vec()))?;
+
+        db.scan(data, scratch, on_match_event)
+    }
+
+    /// Record and perform a vectored mode scan.
+    pub fn scan_vectored<I, T, F>(
+        &mut self,
+        db: &DatabaseRef<Vectored>,
+        data: I,
+        scratch: &ScratchRef,
+        on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let data = data.into_iter().collect::<Vec<_>>();
+
+        self.record(&Event::Vectored(data.iter().map(|buf| buf.as_ref().to_vec()).collect()))?;
+
+        db.scan(&data, scratch, on_match_event)
+    }
+
+    /// Record and open a stream, returns the stream with its recorded id.
+    pub fn open_stream(&mut self, db: &DatabaseRef<Streaming>) -> Result<(u32, Stream)> {
+        let stream = db.open_stream()?;
+        let id = self.next_stream_id;
+
+        self.next_stream_id += 1;
+        self.record(&Event::Open(id))?;
+
+        Ok((id, stream))
+    }
+
+    /// Record and write data to the stream.
+    pub fn scan_stream<T, F>(
+        &mut self,
+        id: u32,
+        stream: &StreamRef,
+        data: T,
+        scratch: &ScratchRef,
+        on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let data = data.as_ref();
+
+        self.record(&Event::Write(id, data.to_vec()))?;
+
+        stream.scan(data, scratch, on_match_event)
+    }
+
+    /// Record and reset the stream.
+    pub fn reset_stream<F>(
+        &mut self,
+        id: u32,
+        stream: &StreamRef,
+        scratch: &ScratchRef,
+        on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: MatchEventHandler,
+    {
+        self.record(&Event::Reset(id))?;
+
+        stream.reset(scratch, on_match_event)
+    }
+
+    /// Record and close the stream.
+    pub fn close_stream<F>(
+        &mut self,
+        id: u32,
+        stream: Stream,
+        scratch: &ScratchRef,
+        on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: MatchEventHandler,
+    {
+        self.record(&Event::Close(id))?;
+
+        stream.close(scratch, on_match_event)
+    }
+}
+
+/// Reproduces the scan calls captured by the `Recorder`.
+pub struct Replayer<R: Read> {
+    r: R,
+}
+
+impl Replayer<BufReader<File>> {
+    /// Open a recording file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Replayer::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> Iterator for Replayer<R> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Event::read_from(&mut self.r).transpose()
+    }
+}
+
+impl<R: Read> Replayer<R> {
+    /// Constructs a replayer which reads the scan calls from the reader.
+    pub fn new(mut r: R) -> Result<Self> {
+        let mut magic = [0; 6];
+
+        r.read_exact(&mut magic)?;
+
+        if &magic != MAGIC {
+            return Err(invalid_data("invalid recording file"));
+        }
+
+        Ok(Replayer { r })
+    }
+
+    /// Replay the recorded block mode scans against the database.
+    ///
+    /// The replay is stopped when the callback terminates a scan.
+    pub fn replay_block<F>(
+        self,
+        db: &DatabaseRef<Block>,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: MatchEventHandler,
+    {
+        let (callback, userdata) = unsafe { on_match_event.split() };
+
+        for event in self {
+            match event? {
+                Event::Block(data) => {
+                    if db.scan(data, scratch, (callback, userdata))?.is_terminated() {
+                        return Ok(ScanOutcome::Terminated);
+                    }
+                }
+                event => return Err(invalid_data(format!("unexpected event for block mode: {:?}", event))),
+            }
+        }
+
+        Ok(ScanOutcome::Completed)
+    }
+
+    /// Replay the recorded vectored mode scans against the database.
+    ///
+    /// The replay is stopped when the callback terminates a scan.
+    pub fn replay_vectored<F>(
+        self,
+        db: &DatabaseRef<Vectored>,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: MatchEventHandler,
+    {
+        let (callback, userdata) = unsafe { on_match_event.split() };
+
+        for event in self {
+            match event? {
+                Event::Vectored(chunks) => {
+                    if db.scan(&chunks, scratch, (callback, userdata))?.is_terminated() {
+                        return Ok(ScanOutcome::Terminated);
+                    }
+                }
+                event => return Err(invalid_data(format!("unexpected event for vectored mode: {:?}", event))),
+            }
+        }
+
+        Ok(ScanOutcome::Completed)
+    }
+
+    /// Replay the recorded stream operations against the database.
+    ///
+    /// The streams which are still open at the end of the recording will be closed in the order they were opened.
+    /// The replay is stopped when the callback terminates a stream operation, the open streams are freed.
+    pub fn replay_streaming<F>(
+        self,
+        db: &DatabaseRef<Streaming>,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: MatchEventHandler,
+    {
+        let (callback, userdata) = unsafe { on_match_event.split() };
+        let mut streams = BTreeMap::new();
+
+        for event in self {
+            let outcome = match event? {
+                Event::Open(id) => {
+                    streams.insert(id, db.open_stream()?);
+
+                    ScanOutcome::Completed
+                }
+                Event::Write(id, data) => streams
+                    .get(&id)
+                    .ok_or_else(|| invalid_data(format!("stream {} not opened", id)))?
+                    .scan(data, scratch, (callback, userdata))?,
+                Event::Reset(id) => streams
+                    .get(&id)
+                    .ok_or_else(|| invalid_data(format!("stream {} not opened", id)))?
+                    .reset(scratch, (callback, userdata))?,
+                Event::Close(id) => streams
+                    .remove(&id)
+                    .ok_or_else(|| invalid_data(format!("stream {} not opened", id)))?
+                    .close(scratch, (callback, userdata))?,
+                event => {
+                    return Err(invalid_data(format!(
+                        "unexpected event for streaming mode: {:?}",
+                        event
+                    )))
+                }
+            };
+
+            if outcome.is_terminated() {
+                return Ok(ScanOutcome::Terminated);
+            }
+        }
+
+        for (_, stream) in streams {
+            if stream.close(scratch, (callback, userdata))?.is_terminated() {
+                return Ok(ScanOutcome::Terminated);
+            }
+        }
+
+        Ok(ScanOutcome::Completed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_record_replay() {
+        let block: BlockDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+        let vectored: VectoredDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+        let streaming: StreamingDatabase = pattern! {"test$"; SOM_LEFTMOST}.build().unwrap();
+        let mut s = block.alloc_scratch().unwrap();
+        vectored.realloc_scratch(&mut s).unwrap();
+        streaming.realloc_scratch(&mut s).unwrap();
+        let mut recorder = Recorder::new(vec![]).unwrap();
+
+        let _ = recorder.scan(&block, "foo test", &s, Matching::Continue).unwrap();
+        let _ = recorder
+            .scan_vectored(&vectored, &["foo te", "st"], &s, Matching::Continue)
+            .unwrap();
+
+        let (first, st0) = recorder.open_stream(&streaming).unwrap();
+        let (second, st1) = recorder.open_stream(&streaming).unwrap();
+
+        let _ = recorder
+            .scan_stream(second, &st1, "a test", &s, Matching::Continue)
+            .unwrap();
+        let _ = recorder
+            .scan_stream(first, &st0, "test", &s, Matching::Continue)
+            .unwrap();
+        let _ = recorder.reset_stream(second, &st1, &s, Matching::Continue).unwrap();
+
+        let log = recorder.into_inner().unwrap();
+        let events = Replayer::new(&log[..]).unwrap().collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                Event::Block(b"foo test".to_vec()),
+                Event::Vectored(vec![b"foo te".to_vec(), b"st".to_vec()]),
+                Event::Open(0),
+                Event::Open(1),
+                Event::Write(1, b"a test".to_vec()),
+                Event::Write(0, b"test".to_vec()),
+                Event::Reset(1),
+            ]
+        );
+
+        let mut matches = vec![];
+        let mut recorder = Recorder::new(vec![]).unwrap();
+
+        for event in &events[2..] {
+            recorder.record(event).unwrap();
+        }
+        recorder.record(&Event::Write(1, b"another test".to_vec())).unwrap();
+
+        let log = recorder.into_inner().unwrap();
+        let outcome = Replayer::new(&log[..])
+            .unwrap()
+            .replay_streaming(&streaming, &s, |_, from, to, _| {
+                matches.push((from, to));
+                Matching::Continue
+            })
+            .unwrap();
+
+        // the end of data matches of the reset, then of the open streams closed in the order they were opened
+        assert_eq!(outcome, ScanOutcome::Completed);
+        assert_eq!(matches, vec![(2, 6), (0, 4), (8, 12)]);
+    }
+
+    #[test]
+    fn test_replay_corrupted() {
+        let mut log = MAGIC.to_vec();
+
+        log.push(Event::BLOCK);
+        log.extend_from_slice(&u64::MAX.to_le_bytes());
+        log.extend_from_slice(b"test");
+
+        assert!(Replayer::new(&log[..]).unwrap().next().unwrap().is_err());
+        assert!(Replayer::new(&b"HSREC\x02"[..]).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::runtime::Matching;
+
+const DEFAULT_CAPACITY: usize = 64;
+
+/// A bounded buffer which reorders the match events by the end offset before delivering them.
+///
+/// The patterns with assertions may produce the matches out of order, see `ExprInfo::unordered_matches()`.
+/// The buffered events are delivered in the order of the end offset when the buffer is full or flushed,
+/// the events with the same end offset are delivered in the order they were reported.
+///
+/// An event which arrives after a later event has been delivered can't be reordered anymore,
+/// it is delivered as soon as possible and counted as a late event.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, Reorder};
+/// let patterns = patterns!("foo", r"\bbar\b");
+/// let db: BlockDatabase = patterns.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut matches = vec![];
+///
+/// let mut reorder = Reorder::new(|id, _, to, _| {
+///     matches.push((id, to));
+///     Matching::Continue
+/// });
+///
+/// db.scan("foo bar foo", &s, reorder.callback()).unwrap();
+/// reorder.flush();
+///
+/// assert_eq!(reorder.late(), 0);
+/// drop(reorder);
+///
+/// assert_eq!(matches, vec![(0, 3), (1, 7), (0, 11)]);
+/// ```
+pub struct Reorder<F> {
+    on_match_event: F,
+    pending: BinaryHeap<Reverse<(u64, u64, u32, u64, u32)>>,
+    capacity: usize,
+    seq: u64,
+    delivered: Option<u64>,
+    late: usize,
+}
+
+impl<F> Reorder<F>
+where
+    F: FnMut(u32, u64, u64, u32) -> Matching,
+{
+    /// Constructs a reordering buffer which holds up to 64 match events.
+    pub fn new(on_match_event: F) -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY, on_match_event)
+    }
+
+    /// Constructs a reordering buffer which holds up to `capacity` match events.
+    pub fn with_capacity(capacity: usize, on_match_event: F) -> Self {
+        Reorder {
+            on_match_event,
+            pending: BinaryHeap::with_capacity(capacity + 1),
+            capacity,
+            seq: 0,
+            delivered: None,
+            late: 0,
+        }
+    }
+
+    /// Returns the number of the buffered match events.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the number of the match events which were delivered out of order.
+    pub fn late(&self) -> usize {
+        self.late
+    }
+
+    /// Buffer a match event, the earliest buffered event is delivered if the buffer is full.
+    pub fn push(&mut self, id: u32, from: u64, to: u64, flags: u32) -> Matching {
+        self.pending.push(Reverse((to, self.seq, id, from, flags)));
+        self.seq += 1;
+
+        if self.pending.len() > self.capacity {
+            self.deliver()
+        } else {
+            Matching::Continue
+        }
+    }
+
+    /// Returns a match event callback which buffers the events.
+    pub fn callback(&mut self) -> impl FnMut(u32, u64, u64, u32) -> Matching + '_ {
+        move |id, from, to, flags| self.push(id, from, to, flags)
+    }
+
+    /// Deliver all the buffered match events, should be called after scanning a block or closing a stream.
+    ///
+    /// The remaining events are dropped if the callback returns `Matching::Terminate`.
+    pub fn flush(&mut self) -> Matching {
+        while !self.pending.is_empty() {
+            if self.deliver() == Matching::Terminate {
+                self.pending.clear();
+
+                return Matching::Terminate;
+            }
+        }
+
+        Matching::Continue
+    }
+
+    /// Consumes the buffer and returns the wrapped callback, the buffered events are dropped.
+    pub fn into_inner(self) -> F {
+        self.on_match_event
+    }
+
+    fn deliver(&mut self) -> Matching {
+        match self.pending.pop() {
+            Some(Reverse((to, _, id, from, flags))) => {
+                if self.delivered.map_or(false, |delivered| to < delivered) {
+                    self.late += 1;
+                } else {
+                    self.delivered = Some(to);
+                }
+
+                (self.on_match_event)(id, from, to, flags)
+            }
+            None => Matching::Continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorder() {
+        let mut matches = vec![];
+        let mut reorder = Reorder::with_capacity(2, |id, _, to, _| {
+            matches.push((id, to));
+            Matching::Continue
+        });
+
+        assert_eq!(reorder.push(0, 0, 5, 0), Matching::Continue);
+        assert_eq!(reorder.push(1, 0, 3, 0), Matching::Continue);
+        assert_eq!(reorder.push(2, 0, 5, 0), Matching::Continue);
+        assert_eq!(reorder.pending(), 2);
+        assert_eq!(reorder.push(3, 0, 1, 0), Matching::Continue);
+        assert_eq!(reorder.flush(), Matching::Continue);
+        assert_eq!(reorder.late(), 1);
+
+        drop(reorder);
+
+        assert_eq!(matches, vec![(1, 3), (3, 1), (0, 5), (2, 5)]);
+    }
+}
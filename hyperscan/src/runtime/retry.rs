@@ -0,0 +1,62 @@
+//! Recover from the allocation failures of the scratch space and stream state.
+use std::sync::{Arc, RwLock};
+
+use crate::errors::{Error, Result};
+
+type Hook = Arc<dyn Fn(&Error) -> bool + Send + Sync>;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// Set a hook which is called when allocating a scratch space, opening or expanding a stream fails
+/// with `Error::NoMem` or `Error::BadAlloc`.
+///
+/// The hook may release memory, e.g. evict the compressed streams or shrink the scratch pools,
+/// and returns `true` to retry the allocation once.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use hyperscan::{prelude::*, clear_alloc_failure_hook, set_alloc_failure_hook, ScratchPool};
+/// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+/// let pool = Arc::new(ScratchPool::new(&db).unwrap());
+///
+/// set_alloc_failure_hook({
+///     let pool = pool.clone();
+///
+///     move |_err| pool.shrink() > 0
+/// });
+///
+/// db.alloc_scratch().unwrap();
+///
+/// clear_alloc_failure_hook();
+/// ```
+pub fn set_alloc_failure_hook<F>(hook: F)
+where
+    F: Fn(&Error) -> bool + Send + Sync + 'static,
+{
+    *HOOK.write().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(hook));
+}
+
+/// Remove the hook of the allocation failures.
+pub fn clear_alloc_failure_hook() {
+    *HOOK.write().unwrap_or_else(|err| err.into_inner()) = None;
+}
+
+/// Call the allocation, and retry it once if it fails and the hook released memory.
+pub(crate) fn alloc<T, F>(mut f: F) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+{
+    match f() {
+        Err(err) if err.is_alloc_failure() => {
+            let hook = HOOK.read().unwrap_or_else(|err| err.into_inner()).clone();
+
+            match hook {
+                Some(hook) if hook(&err) => f(),
+                _ => Err(err),
+            }
+        }
+        res => res,
+    }
+}
@@ -0,0 +1,141 @@
+use crate::common::{DatabaseRef, Vectored};
+use crate::errors::Result;
+use crate::runtime::{Matching, ScanOutcome, ScratchRef, StreamRef};
+
+/// The contents of a circular buffer, which may wrap around the end of the storage,
+/// e.g. a telemetry ring buffer or a packet capture ring.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, RingBuffer};
+/// let db: VectoredDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+///
+/// // the oldest byte at index 6 was written at the absolute offset 100
+/// let ring = RingBuffer::new(b"st bar foo te", 6, 13).offset(100);
+/// let mut matches = vec![];
+///
+/// assert_eq!(ring.as_slices(), (&b" foo te"[..], &b"st bar"[..]));
+///
+/// db.scan_ring(&ring, &s, |_, from, to, _| {
+///     matches.push(from..to);
+///     Matching::Continue
+/// })
+/// .unwrap();
+///
+/// assert_eq!(matches, vec![105..109]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct RingBuffer<'a> {
+    buf: &'a [u8],
+    head: usize,
+    len: usize,
+    offset: u64,
+}
+
+impl<'a> RingBuffer<'a> {
+    /// Constructs a view of the `len` bytes in the storage starting from the oldest byte at `head`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `head` or `len` is out of the storage.
+    pub fn new(buf: &'a [u8], head: usize, len: usize) -> Self {
+        assert!(head < buf.len().max(1), "head out of the ring buffer");
+        assert!(len <= buf.len(), "length out of the ring buffer");
+
+        RingBuffer {
+            buf,
+            head,
+            len,
+            offset: 0,
+        }
+    }
+
+    /// Set the absolute offset of the oldest byte, which is added to the offsets of the matches.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns the number of bytes in the ring buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the ring buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the older and the newer segments of the contents, the newer one is empty if it doesn't wrap.
+    pub fn as_slices(&self) -> (&'a [u8], &'a [u8]) {
+        let end = self.head + self.len;
+
+        if end <= self.buf.len() {
+            (&self.buf[self.head..end], &[])
+        } else {
+            (&self.buf[self.head..], &self.buf[..end - self.buf.len()])
+        }
+    }
+}
+
+impl DatabaseRef<Vectored> {
+    /// Scan the contents of a ring buffer as a two-segment vectored data,
+    /// the offsets of the matches are shifted with the absolute offset of the ring buffer.
+    pub fn scan_ring<F>(
+        &self,
+        ring: &RingBuffer<'_>,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching,
+    {
+        let (older, newer) = ring.as_slices();
+        let offset = ring.offset;
+
+        self.scan([older, newer], scratch, |id, from, to, flags| {
+            on_match_event(id, offset + from, offset + to, flags)
+        })
+    }
+}
+
+impl StreamRef {
+    /// Write the contents of a ring buffer to the opened stream as two segments.
+    ///
+    /// The offsets of the matches are relative to the start of the stream,
+    /// so the ring buffer should only contain the data which has not been written to the stream.
+    pub fn scan_ring<F>(
+        &self,
+        ring: &RingBuffer<'_>,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching,
+    {
+        let (older, newer) = ring.as_slices();
+
+        if self.scan(older, scratch, &mut on_match_event)?.is_terminated() {
+            return Ok(ScanOutcome::Terminated);
+        }
+
+        self.scan(newer, scratch, on_match_event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_slices() {
+        let buf = b"0123456789";
+
+        assert_eq!(RingBuffer::new(buf, 2, 5).as_slices(), (&b"23456"[..], &b""[..]));
+        assert_eq!(RingBuffer::new(buf, 8, 10).as_slices(), (&b"89"[..], &b"01234567"[..]));
+        assert_eq!(RingBuffer::new(buf, 9, 0).as_slices(), (&b""[..], &b""[..]));
+        assert!(RingBuffer::new(b"", 0, 0).is_empty());
+    }
+}
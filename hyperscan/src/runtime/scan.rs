@@ -1,19 +1,27 @@
-use std::io::Read;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::{BufRead, ErrorKind, Read};
 use std::mem;
+use std::ops::Range;
 use std::ptr;
 
-use anyhow::Result;
 use foreign_types::ForeignTypeRef;
 use libc::{c_char, c_uint};
+use smallvec::SmallVec;
 
+use crate::closure::{self, split_closure};
 use crate::common::{Block, DatabaseRef, Streaming, Vectored};
-use crate::errors::AsResult;
+use crate::diagnostics;
+use crate::errors::{AsResult, Result};
 use crate::ffi;
-use crate::runtime::{split_closure, ScratchRef, StreamRef};
+use crate::runtime::{ScanOptions, ScratchRef, StreamRef};
 
 #[cfg(feature = "async")]
 use futures::io::{AsyncRead, AsyncReadExt};
 
+/// The chunk count of the vectored data which is scanned without the heap allocation.
+const INLINE_CHUNKS: usize = 16;
+
 /// Indicating whether or not matching should continue on the target data.
 #[repr(i32)]
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -30,6 +38,41 @@ impl Default for Matching {
     }
 }
 
+/// The outcome of a scan which didn't fail.
+#[must_use = "the scan may be terminated by the match event callback"]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScanOutcome {
+    /// All the data was scanned.
+    Completed,
+    /// The scan was terminated early by the match event callback returning `Matching::Terminate`.
+    Terminated,
+}
+
+impl ScanOutcome {
+    /// Returns `true` if the scan was terminated by the match event callback.
+    pub fn is_terminated(&self) -> bool {
+        *self == ScanOutcome::Terminated
+    }
+
+    /// Returns `true` if all the data was scanned.
+    pub fn is_completed(&self) -> bool {
+        *self == ScanOutcome::Completed
+    }
+
+    #[track_caller]
+    pub(crate) fn from_code(code: ffi::hs_error_t) -> Result<Self> {
+        if code == ffi::HS_SCAN_TERMINATED {
+            Ok(ScanOutcome::Terminated)
+        } else {
+            code.map(|_| ScanOutcome::Completed)
+        }
+    }
+}
+
+/// The flag of a match event reported at the end of data, i.e. when a stream is closed or reset,
+/// rather than when the data is written to the stream.
+pub const MATCH_AT_EOD: u32 = 1 << 31;
+
 /// Definition of the match event callback function type.
 ///
 /// A callback function matching the defined type must be provided by the
@@ -50,6 +93,9 @@ impl Default for Matching {
 /// example, scanning a different database in a new stream and with new scratch
 /// space), but reusing data structures like stream state and/or scratch space
 /// will produce undefined behavior.
+///
+/// If the callback function panics, the scan is terminated and the panic is resumed
+/// once the scan call returns, instead of unwinding across the FFI boundary.
 pub trait MatchEventHandler {
     /// Split the match event handler to callback and userdata.
     ///
@@ -92,11 +138,24 @@ where
     }
 }
 
+/// The raw C match event callback, which receives the pattern id, the start and end offsets,
+/// the flags and the context pointer, and returns non-zero to terminate the scanning.
+pub type RawMatchEventHandler = unsafe extern "C" fn(
+    id: c_uint,
+    from: libc::c_ulonglong,
+    to: libc::c_ulonglong,
+    flags: c_uint,
+    context: *mut libc::c_void,
+) -> libc::c_int;
+
 impl DatabaseRef<Block> {
     /// The block (non-streaming) regular expression scanner.
     ///
     /// This is the function call in which the actual pattern matching takes place for block-mode pattern databases.
     ///
+    /// An empty data is a valid input, only the patterns which match the empty data, e.g. with `ALLOWEMPTY`,
+    /// are reported.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -112,7 +171,18 @@ impl DatabaseRef<Block> {
     ///
     /// assert_eq!(matches, vec![4..8]);
     /// ```
-    pub fn scan<T, F>(&self, data: T, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    ///
+    /// Returning `Matching::Terminate` from the callback stops the scan with `ScanOutcome::Terminated`.
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, ScanOutcome};
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    ///
+    /// assert_eq!(db.scan("foo test bar", &s, Matching::Terminate).unwrap(), ScanOutcome::Terminated);
+    /// assert_eq!(db.scan("foo bar", &s, Matching::Terminate).unwrap(), ScanOutcome::Completed);
+    /// ```
+    pub fn scan<T, F>(&self, data: T, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
     where
         T: AsRef<[u8]>,
         F: MatchEventHandler,
@@ -122,18 +192,98 @@ impl DatabaseRef<Block> {
         unsafe {
             let (callback, userdata) = on_match_event.split();
 
-            ffi::hs_scan(
-                self.as_ptr(),
-                data.as_ptr() as *const c_char,
-                data.len() as u32,
-                0,
-                scratch.as_ptr(),
-                callback,
-                userdata,
-            )
-            .ok()
+            let code = diagnostics::scan("block", data.len(), || {
+                ffi::hs_scan(
+                    self.as_ptr(),
+                    data.as_ptr() as *const c_char,
+                    data.len() as u32,
+                    0,
+                    scratch.as_ptr(),
+                    callback,
+                    userdata,
+                )
+            });
+
+            closure::resume_unwind();
+
+            ScanOutcome::from_code(code)
         }
     }
+
+    /// The block regular expression scanner calling the raw C callback with the context pointer,
+    /// which bypasses the Rust closure machinery.
+    ///
+    /// # Safety
+    ///
+    /// The callback must not unwind, and the context must be valid for the callback during the scan.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// unsafe extern "C" fn on_match(_: u32, _: u64, _: u64, _: u32, ctx: *mut libc::c_void) -> libc::c_int {
+    ///     *(ctx as *mut usize) += 1;
+    ///     0
+    /// }
+    ///
+    /// let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let mut count = 0usize;
+    ///
+    /// unsafe {
+    ///     db.scan_raw("test a test", &s, on_match, &mut count as *mut _ as *mut _).unwrap();
+    /// }
+    ///
+    /// assert_eq!(count, 2);
+    /// ```
+    pub unsafe fn scan_raw<T>(
+        &self,
+        data: T,
+        scratch: &ScratchRef,
+        on_match_event: RawMatchEventHandler,
+        context: *mut libc::c_void,
+    ) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.scan(data, scratch, (Some(on_match_event), context))
+    }
+
+    /// The block regular expression scanner which passes the matched bytes to the callback.
+    ///
+    /// The matched range is sliced from the input data by the scanner,
+    /// the pattern should be compiled with `SOM_LEFTMOST` flag to get the start of match,
+    /// otherwise the matched bytes will start from the beginning of the data.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: BlockDatabase = pattern! {"test"; CASELESS | SOM_LEFTMOST}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let mut matches = vec![];
+    ///
+    /// db.scan_map("foo TEST bar", &s, |id, matched| {
+    ///     matches.push((id, matched.to_vec()));
+    ///     Matching::Continue
+    /// }).unwrap();
+    ///
+    /// assert_eq!(matches, vec![(0, b"TEST".to_vec())]);
+    /// ```
+    pub fn scan_map<T, F>(&self, data: T, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: FnMut(u32, &[u8]) -> Matching,
+    {
+        let data = data.as_ref();
+
+        self.scan(data, scratch, |id, from, to, _| {
+            let from = from as usize;
+            let to = to as usize;
+
+            on_match_event(id, data.get(from..to).unwrap_or_default())
+        })
+    }
 }
 
 impl DatabaseRef<Vectored> {
@@ -141,6 +291,9 @@ impl DatabaseRef<Vectored> {
     ///
     /// This is the function call in which the actual pattern matching takes place for vectoring-mode pattern databases.
     ///
+    /// An empty vector of data is a valid input, only the patterns which match the empty data,
+    /// e.g. with `ALLOWEMPTY`, are reported.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -157,51 +310,328 @@ impl DatabaseRef<Vectored> {
     ///
     /// assert_eq!(matches, vec![3..7]);
     /// ```
-    pub fn scan<I, T, F>(&self, data: I, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    pub fn scan<I, T, F>(&self, data: I, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
     where
         I: IntoIterator<Item = T>,
         T: AsRef<[u8]>,
         F: MatchEventHandler,
     {
-        let (ptrs, lens): (Vec<_>, Vec<_>) = data
-            .into_iter()
-            .map(|buf| {
-                let buf = buf.as_ref();
+        // keep the owned chunks alive during the scan, the typical chunk counts are stored on the stack
+        let data = data.into_iter().collect::<SmallVec<[T; INLINE_CHUNKS]>>();
+        let ptrs = data
+            .iter()
+            .map(|buf| buf.as_ref().as_ptr() as *const c_char)
+            .collect::<SmallVec<[_; INLINE_CHUNKS]>>();
+        let lens = data
+            .iter()
+            .map(|buf| buf.as_ref().len() as c_uint)
+            .collect::<SmallVec<[_; INLINE_CHUNKS]>>();
 
-                (buf.as_ptr() as *const i8, buf.len() as c_uint)
-            })
-            .unzip();
+        unsafe { self.scan_vector(&ptrs, &lens, scratch, on_match_event) }
+    }
 
-        unsafe {
-            let (callback, userdata) = on_match_event.split();
+    /// The vectored regular expression scanner calling the raw C callback with the context pointer.
+    ///
+    /// # Safety
+    ///
+    /// The callback must not unwind, and the context must be valid for the callback during the scan.
+    pub unsafe fn scan_raw<I, T>(
+        &self,
+        data: I,
+        scratch: &ScratchRef,
+        on_match_event: RawMatchEventHandler,
+        context: *mut libc::c_void,
+    ) -> Result<ScanOutcome>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        self.scan(data, scratch, (Some(on_match_event), context))
+    }
 
+    /// The vectored regular expression scanner reusing the chunk buffer of the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, VectoredBuffer};
+    /// let db: VectoredDatabase = pattern!{"test"; CASELESS|SOM_LEFTMOST}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let mut buf = VectoredBuffer::new();
+    ///
+    /// let mut matches = vec![];
+    ///
+    /// for packet in &[["foo", "test", "bar"], ["te", "st", "!"]] {
+    ///     db.scan_with_buffer(packet, &mut buf, &s, |id, from, to, _| {
+    ///         matches.push(from..to);
+    ///         Matching::Continue
+    ///     }).unwrap();
+    /// }
+    ///
+    /// assert_eq!(matches, vec![3..7, 0..4]);
+    /// ```
+    pub fn scan_with_buffer<T, F>(
+        &self,
+        data: &[T],
+        buf: &mut VectoredBuffer,
+        scratch: &ScratchRef,
+        on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        buf.ptrs.clear();
+        buf.lens.clear();
+
+        for chunk in data {
+            let chunk = chunk.as_ref();
+
+            buf.ptrs.push(chunk.as_ptr() as *const c_char);
+            buf.lens.push(chunk.len() as c_uint);
+        }
+
+        let res = unsafe { self.scan_vector(&buf.ptrs, &buf.lens, scratch, on_match_event) };
+
+        // don't keep the dangling pointers after the scan
+        buf.ptrs.clear();
+
+        res
+    }
+
+    /// The vectored regular expression scanner of the raw buffers, e.g. the packet buffers of DPDK or AF_XDP,
+    /// which can't be expressed as slices without copying.
+    ///
+    /// # Safety
+    ///
+    /// Each `(ptr, len)` pair must point to `len` bytes which are readable and not mutated during the scan,
+    /// the pointer may be dangling only if `len` is zero, and `len` must not exceed `u32::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: VectoredDatabase = pattern!{"test"; SOM_LEFTMOST}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let (foo, bar) = (b"foo te", b"st bar");
+    ///
+    /// let mut matches = vec![];
+    ///
+    /// unsafe {
+    ///     db.scan_raw_parts(&[(foo.as_ptr(), foo.len()), (bar.as_ptr(), bar.len())], &s, |_, from, to, _| {
+    ///         matches.push(from..to);
+    ///         Matching::Continue
+    ///     }).unwrap();
+    /// }
+    ///
+    /// assert_eq!(matches, vec![4..8]);
+    /// ```
+    pub unsafe fn scan_raw_parts<F>(
+        &self,
+        parts: &[(*const u8, usize)],
+        scratch: &ScratchRef,
+        on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: MatchEventHandler,
+    {
+        let ptrs = parts
+            .iter()
+            .map(|&(ptr, _)| ptr as *const c_char)
+            .collect::<SmallVec<[_; INLINE_CHUNKS]>>();
+        let lens = parts
+            .iter()
+            .map(|&(_, len)| len as c_uint)
+            .collect::<SmallVec<[_; INLINE_CHUNKS]>>();
+
+        self.scan_vector(&ptrs, &lens, scratch, on_match_event)
+    }
+
+    unsafe fn scan_vector<F>(
+        &self,
+        ptrs: &[*const c_char],
+        lens: &[c_uint],
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: MatchEventHandler,
+    {
+        let (callback, userdata) = on_match_event.split();
+
+        let code = diagnostics::scan("vectored", lens.iter().map(|&len| len as usize).sum(), || {
             ffi::hs_scan_vector(
                 self.as_ptr(),
-                ptrs.as_slice().as_ptr() as *const *const c_char,
-                lens.as_slice().as_ptr() as *const _,
+                ptrs.as_ptr(),
+                lens.as_ptr(),
                 ptrs.len() as u32,
                 0,
                 scratch.as_ptr(),
                 callback,
                 userdata,
             )
-            .ok()
+        });
+
+        closure::resume_unwind();
+
+        ScanOutcome::from_code(code)
+    }
+}
+
+/// A reusable buffer of the chunk pointers and lengths for the vectored scanning.
+///
+/// The buffer grows to the largest chunk count it has scanned,
+/// so the scanning of the following data doesn't allocate on the heap.
+#[derive(Debug, Default)]
+pub struct VectoredBuffer {
+    ptrs: Vec<*const c_char>,
+    lens: Vec<c_uint>,
+}
+
+unsafe impl Send for VectoredBuffer {}
+
+impl VectoredBuffer {
+    /// Constructs an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a buffer for the chunk count.
+    pub fn with_capacity(capacity: usize) -> Self {
+        VectoredBuffer {
+            ptrs: Vec::with_capacity(capacity),
+            lens: Vec::with_capacity(capacity),
         }
     }
+
+    /// Returns the number of the chunks the buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.ptrs.capacity().min(self.lens.capacity())
+    }
 }
 
-const SCAN_BUF_SIZE: usize = 4096;
+/// Reconstruct the matched bytes of a vectored match from the scanned chunks,
+/// which borrows the bytes within one chunk, or assembles the bytes spanning the chunks.
+///
+/// Returns `None` if the range is out of the chunks.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::borrow::Cow;
+/// # use hyperscan::{prelude::*, matched_bytes};
+/// let db: VectoredDatabase = pattern!{"te+st"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let chunks = ["foo te", "e", "st bar test"];
+/// let mut matches = vec![];
+///
+/// db.scan(&chunks, &s, |_, from, to, _| {
+///     matches.push(matched_bytes(&chunks, from..to).unwrap());
+///     Matching::Continue
+/// }).unwrap();
+///
+/// assert_eq!(matches, vec![Cow::Owned(b"teest".to_vec()), Cow::Borrowed(&b"test"[..])]);
+/// ```
+pub fn matched_bytes<T: AsRef<[u8]>>(chunks: &[T], range: Range<u64>) -> Option<Cow<'_, [u8]>> {
+    let (from, to) = (usize::try_from(range.start).ok()?, usize::try_from(range.end).ok()?);
+
+    if from > to {
+        return None;
+    }
+
+    let mut off = 0;
+    let mut bytes: Option<Vec<u8>> = None;
+
+    for chunk in chunks {
+        let chunk = chunk.as_ref();
+        let start = off;
+
+        off += chunk.len();
+
+        // skip the chunks before the match, the empty match at the end of a chunk is borrowed from it
+        if off < from || (off == from && from < to) {
+            continue;
+        }
+
+        let part = &chunk[from.max(start) - start..to.min(off) - start];
+
+        match bytes {
+            None if to <= off => return Some(Cow::Borrowed(part)),
+            None => bytes = Some(part.to_vec()),
+            Some(ref mut bytes) => bytes.extend_from_slice(part),
+        }
+
+        if to <= off {
+            break;
+        }
+    }
+
+    bytes.filter(|_| to <= off).map(Cow::Owned)
+}
+
+/// The size of the buffer which a reader is read into and scanned from.
+pub(crate) const SCAN_BUF_SIZE: usize = 4096;
+
+/// Read a chunk into the buffer, returns the length of the chunk or zero at the end of the reader.
+///
+/// The interrupted reads are retried, and the buffer is filled before returning if `fill` is set.
+pub(crate) fn read_chunk<R: Read + ?Sized>(reader: &mut R, buf: &mut [u8], fill: bool) -> Result<usize> {
+    let mut off = 0;
+
+    while off < buf.len() {
+        match reader.read(&mut buf[off..]) {
+            Ok(0) => break,
+            Ok(len) if !fill => return Ok(len),
+            Ok(len) => off += len,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(off)
+}
+
+/// Write the chunks read into the buffer to the stream until the end of the reader, then close the stream.
+///
+/// The read is an expression of `io::Result<usize>`, so the loop is shared by the blocking and async readers.
+/// The handler is split only inside the synchronous calls, so it isn't held across an `.await`.
+#[cfg(any(feature = "async", feature = "tokio"))]
+macro_rules! scan_chunks {
+    ($stream:ident, $buf:ident, $read:expr, $scratch:expr, $on_match_event:ident) => {{
+        let handler = &mut $on_match_event;
+
+        loop {
+            let len = match $read {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            if $stream
+                .scan(&$buf[..len], $scratch, unsafe { handler.split() })?
+                .is_terminated()
+            {
+                return Ok(ScanOutcome::Terminated);
+            }
+        }
+
+        $stream.close($scratch, $on_match_event)
+    }};
+}
 
 impl DatabaseRef<Streaming> {
     /// Pattern matching takes place for stream-mode pattern databases.
     ///
+    /// The reader is scanned in the chunks of at most 4096 bytes, the errors of the reader are returned.
+    ///
     /// # Examples
     ///
     /// ```rust
     /// # use std::io::Cursor;
     /// # use hyperscan::prelude::*;
-    /// const SCAN_BUF_SIZE: usize = 4096;
-    /// let mut buf = String::from_utf8(vec![b'x'; SCAN_BUF_SIZE - 2]).unwrap();
+    /// // the match spans the first two chunks
+    /// let mut buf = String::from_utf8(vec![b'x'; 4094]).unwrap();
     ///
     /// buf.push_str("baaab");
     ///
@@ -219,26 +649,123 @@ impl DatabaseRef<Streaming> {
     ///
     /// assert_eq!(matches, vec![(4095, 4096), (4095, 4097), (4095, 4098)]);
     /// ```
-    pub fn scan<R, F>(&self, reader: &mut R, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    pub fn scan<R, F>(&self, reader: &mut R, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
     where
         R: Read,
         F: MatchEventHandler,
+    {
+        self.scan_with_options(reader, ScanOptions::default(), scratch, on_match_event)
+    }
+
+    /// Pattern matching takes place for stream-mode pattern databases using BufRead.
+    ///
+    /// The data is scanned directly from the internal buffer of the reader,
+    /// without copying it into an intermediate buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::BufReader;
+    /// # use hyperscan::prelude::*;
+    /// let db: StreamingDatabase = pattern! { "a+"; SOM_LEFTMOST }.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let mut reader = BufReader::with_capacity(4, &b"xxbaaab"[..]);
+    /// let mut matches = vec![];
+    ///
+    /// db.scan_buf_read(&mut reader, &s, |_, from, to, _| {
+    ///     matches.push((from, to));
+    ///
+    ///     Matching::Continue
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(matches, vec![(3, 4), (3, 5), (3, 6)]);
+    /// ```
+    pub fn scan_buf_read<R, F>(
+        &self,
+        reader: &mut R,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        R: BufRead,
+        F: MatchEventHandler,
     {
         let stream = self.open_stream()?;
-        let mut buf = [0; SCAN_BUF_SIZE];
 
         let (callback, userdata) = unsafe { on_match_event.split() };
 
-        while let Ok(len) = reader.read(&mut buf[..]) {
-            if len == 0 {
-                break;
+        loop {
+            let buf = match reader.fill_buf() {
+                Ok([]) => break,
+                Ok(buf) => buf,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let len = buf.len();
+
+            if stream.scan(buf, scratch, (callback, userdata))?.is_terminated() {
+                return Ok(ScanOutcome::Terminated);
             }
 
-            stream.scan(&buf[..len], scratch, (callback, userdata))?;
+            reader.consume(len);
+        }
+
+        stream.close(scratch, (callback, userdata))
+    }
+
+    /// Pattern matching takes place for a sequence of readers as a logical concatenation,
+    /// e.g. the rotated log segments, the matches may span the boundaries of the readers.
+    ///
+    /// The offsets of the matches are relative to the start of the first reader.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Cursor;
+    /// # use hyperscan::prelude::*;
+    /// let db: StreamingDatabase = pattern! { "test"; SOM_LEFTMOST }.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let segments = vec![Cursor::new("foo te"), Cursor::new(""), Cursor::new("st bar")];
+    /// let mut matches = vec![];
+    ///
+    /// db.scan_all(segments, &s, |_, from, to, _| {
+    ///     matches.push((from, to));
+    ///
+    ///     Matching::Continue
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(matches, vec![(4, 8)]);
+    /// ```
+    pub fn scan_all<I, R, F>(&self, readers: I, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        I: IntoIterator<Item = R>,
+        R: Read,
+        F: MatchEventHandler,
+    {
+        let stream = self.open_stream()?;
+        let mut buf = [0; SCAN_BUF_SIZE];
+
+        let (callback, userdata) = unsafe { on_match_event.split() };
+
+        for mut reader in readers {
+            loop {
+                let len = read_chunk(&mut reader, &mut buf, false)?;
+
+                if len == 0 {
+                    break;
+                }
+
+                if stream.scan(&buf[..len], scratch, (callback, userdata))?.is_terminated() {
+                    return Ok(ScanOutcome::Terminated);
+                }
+            }
         }
 
         stream.close(scratch, (callback, userdata))
     }
+
     /// Pattern matching takes place for stream-mode pattern databases using AsyncRead.
     ///
     /// # Examples
@@ -247,8 +774,8 @@ impl DatabaseRef<Streaming> {
     /// # use futures::io::Cursor;
     /// # use hyperscan::prelude::*;
     /// # use tokio_test;
-    /// const SCAN_BUF_SIZE: usize = 4096;
-    /// let mut buf = String::from_utf8(vec![b'x'; SCAN_BUF_SIZE - 2]).unwrap();
+    /// // the match spans the first two chunks
+    /// let mut buf = String::from_utf8(vec![b'x'; 4094]).unwrap();
     ///
     /// buf.push_str("baaab");
     ///
@@ -268,7 +795,12 @@ impl DatabaseRef<Streaming> {
     /// assert_eq!(matches, vec![(4095, 4096), (4095, 4097), (4095, 4098)]);
     /// ```
     #[cfg(feature = "async")]
-    pub async fn async_scan<R, F>(&self, reader: &mut R, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    pub async fn async_scan<R, F>(
+        &self,
+        reader: &mut R,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
     where
         R: AsyncRead + Unpin,
         F: MatchEventHandler,
@@ -276,17 +808,56 @@ impl DatabaseRef<Streaming> {
         let stream = self.open_stream()?;
         let mut buf = [0; SCAN_BUF_SIZE];
 
-        let (callback, userdata) = unsafe { on_match_event.split() };
-
-        while let Ok(len) = reader.read(&mut buf[..]).await {
-            if len == 0 {
-                break;
-            }
+        scan_chunks!(stream, buf, reader.read(&mut buf[..]).await, scratch, on_match_event)
+    }
 
-            stream.scan(&buf[..len], scratch, (callback, userdata))?;
-        }
+    /// Pattern matching takes place for stream-mode pattern databases using `tokio::io::AsyncRead`.
+    ///
+    /// The scratch space is borrowed mutably, so the future is `Send` if the reader and the callback are,
+    /// and it can be spawned on a multi-threaded runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: StreamingDatabase = pattern! { "a+"; SOM_LEFTMOST }.build().unwrap();
+    /// let mut s = db.alloc_scratch().unwrap();
+    /// let mut reader = &b"xxbaaab"[..];
+    /// let mut matches = vec![];
+    ///
+    /// tokio_test::block_on(async {
+    ///     db.scan_async(&mut reader, &mut s, |_, from, to, _| {
+    ///         matches.push((from, to));
+    ///
+    ///         Matching::Continue
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// });
+    ///
+    /// assert_eq!(matches, vec![(3, 4), (3, 5), (3, 6)]);
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn scan_async<R, F>(
+        &self,
+        reader: &mut R,
+        scratch: &mut ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+        F: MatchEventHandler,
+    {
+        let stream = self.open_stream()?;
+        let mut buf = [0; SCAN_BUF_SIZE];
 
-        stream.close(scratch, (callback, userdata))
+        scan_chunks!(
+            stream,
+            buf,
+            tokio::io::AsyncReadExt::read(reader, &mut buf[..]).await,
+            scratch,
+            on_match_event
+        )
     }
 }
 
@@ -296,6 +867,8 @@ impl StreamRef {
     /// This is the function call in which the actual pattern matching takes place as data is written to the stream.
     /// Matches will be returned via the `on_match_event` callback supplied.
     ///
+    /// An empty data is a valid input, which doesn't advance the stream offset.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -322,7 +895,7 @@ impl StreamRef {
     ///
     /// assert_eq!(matches, vec![(4, 8)]);
     /// ```
-    pub fn scan<T, F>(&self, data: T, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    pub fn scan<T, F>(&self, data: T, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
     where
         T: AsRef<[u8]>,
         F: MatchEventHandler,
@@ -332,16 +905,276 @@ impl StreamRef {
         unsafe {
             let (callback, userdata) = on_match_event.split();
 
-            ffi::hs_scan_stream(
-                self.as_ptr(),
-                data.as_ptr() as *const c_char,
-                data.len() as u32,
-                0,
-                scratch.as_ptr(),
-                callback,
-                userdata,
-            )
-            .ok()
+            let code = diagnostics::scan("stream", data.len(), || {
+                ffi::hs_scan_stream(
+                    self.as_ptr(),
+                    data.as_ptr() as *const c_char,
+                    data.len() as u32,
+                    0,
+                    scratch.as_ptr(),
+                    callback,
+                    userdata,
+                )
+            });
+
+            closure::resume_unwind();
+
+            ScanOutcome::from_code(code)
+        }
+    }
+
+    /// Write data to the opened stream calling the raw C callback with the context pointer.
+    ///
+    /// # Safety
+    ///
+    /// The callback must not unwind, and the context must be valid for the callback during the scan.
+    pub unsafe fn scan_raw<T>(
+        &self,
+        data: T,
+        scratch: &ScratchRef,
+        on_match_event: RawMatchEventHandler,
+        context: *mut libc::c_void,
+    ) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.scan(data, scratch, (Some(on_match_event), context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use crate::prelude::*;
+    use crate::runtime::ScanOutcome;
+
+    use super::read_chunk;
+
+    #[test]
+    fn test_scan_empty() {
+        let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+
+        assert_eq!(
+            db.scan("", &s, |_, _, _, _| -> Matching { panic!("unexpected match") })
+                .unwrap(),
+            ScanOutcome::Completed
+        );
+
+        let db: BlockDatabase = pattern! {"a*"; ALLOWEMPTY}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let mut matches = vec![];
+
+        let outcome = db
+            .scan(Vec::<u8>::new(), &s, |_, from, to, _| {
+                matches.push(from..to);
+                Matching::Continue
+            })
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Completed);
+
+        assert_eq!(matches, vec![0..0]);
+
+        let db: VectoredDatabase = pattern! {"test"}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+
+        assert_eq!(
+            db.scan(Vec::<&[u8]>::new(), &s, |_, _, _, _| -> Matching {
+                panic!("unexpected match")
+            })
+            .unwrap(),
+            ScanOutcome::Completed
+        );
+
+        let db: StreamingDatabase = pattern! {"test"}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let st = db.open_stream().unwrap();
+
+        assert_eq!(
+            st.scan("", &s, |_, _, _, _| -> Matching { panic!("unexpected match") })
+                .unwrap(),
+            ScanOutcome::Completed
+        );
+        assert_eq!(
+            st.close(&s, |_, _, _, _| -> Matching { panic!("unexpected match") })
+                .unwrap(),
+            ScanOutcome::Completed
+        );
+    }
+
+    #[test]
+    fn test_scan_panic() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let db: BlockDatabase = pattern! {"test"}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let mut matches = 0;
+
+        let err = panic::catch_unwind(AssertUnwindSafe(|| {
+            db.scan("test test", &s, |_, _, _, _| -> Matching {
+                matches += 1;
+                panic!("boom")
+            })
+        }))
+        .unwrap_err();
+
+        assert_eq!(err.downcast_ref::<&str>(), Some(&"boom"));
+        assert_eq!(matches, 1);
+
+        let outcome = db
+            .scan("test", &s, |_, _, _, _| {
+                matches += 1;
+                Matching::Continue
+            })
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Completed);
+        assert_eq!(matches, 2);
+    }
+
+    #[test]
+    fn test_reset_stream() {
+        use crate::MATCH_AT_EOD;
+
+        let db: StreamingDatabase = pattern! {"test$"; SOM_LEFTMOST}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let st = db.open_stream().unwrap();
+        let mut matches = vec![];
+
+        let mut callback = |_, from, to, flags| {
+            matches.push((from..to, flags & MATCH_AT_EOD != 0));
+            Matching::Continue
+        };
+
+        assert_eq!(st.scan("foo te", &s, &mut callback).unwrap(), ScanOutcome::Completed);
+        assert_eq!(st.scan("st", &s, &mut callback).unwrap(), ScanOutcome::Completed);
+        assert_eq!(st.reset(&s, &mut callback).unwrap(), ScanOutcome::Completed);
+
+        // the state of the previous connection is discarded, so a match can't span the reset
+        assert_eq!(st.scan("te", &s, &mut callback).unwrap(), ScanOutcome::Completed);
+        assert_eq!(st.reset(&s, &mut callback).unwrap(), ScanOutcome::Completed);
+
+        assert_eq!(st.scan("st", &s, &mut callback).unwrap(), ScanOutcome::Completed);
+        assert_eq!(st.scan("test", &s, &mut callback).unwrap(), ScanOutcome::Completed);
+        assert_eq!(st.close(&s, &mut callback).unwrap(), ScanOutcome::Completed);
+
+        assert_eq!(matches, vec![(4..8, true), (2..6, true)]);
+    }
+
+    #[test]
+    fn test_scan_buf_read() {
+        use std::io::{BufRead, BufReader};
+
+        let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+
+        // every `fill_buf` returns a single byte, so the match spans the boundaries of the slices
+        let mut reader = BufReader::with_capacity(1, &b"foo test bar"[..]);
+        let mut matches = vec![];
+
+        let outcome = db
+            .scan_buf_read(&mut reader, &s, |_, from, to, _| {
+                matches.push(from..to);
+                Matching::Continue
+            })
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Completed);
+        assert_eq!(matches, vec![4..8]);
+        assert!(reader.fill_buf().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_matched_bytes() {
+        use std::borrow::Cow;
+
+        use crate::runtime::matched_bytes;
+
+        let chunks = ["ab", "", "cd", "e"];
+
+        assert!(matches!(matched_bytes(&chunks, 2..4), Some(Cow::Borrowed(b"cd"))));
+        assert!(matches!(matched_bytes(&chunks, 2..2), Some(Cow::Borrowed(b""))));
+        assert_eq!(matched_bytes(&chunks, 1..5).unwrap().as_ref(), b"bcde");
+        assert!(matches!(matched_bytes(&chunks, 1..5), Some(Cow::Owned(_))));
+        assert_eq!(matched_bytes(&chunks, 0..5).unwrap().as_ref(), b"abcde");
+        assert_eq!(matched_bytes(&chunks, 4..6), None);
+        assert_eq!(matched_bytes(&chunks, 3..2), None);
+    }
+
+    /// A reader which returns at most one byte on each read.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(1);
+
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+
+            Ok(len)
         }
     }
+
+    /// A reader which fails after the data.
+    struct Broken<'a>(&'a [u8]);
+
+    impl Read for Broken<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.read(buf)? {
+                0 => Err(io::ErrorKind::BrokenPipe.into()),
+                len => Ok(len),
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_chunk() {
+        let mut buf = [0; 4];
+
+        assert_eq!(read_chunk(&mut Trickle(b"foobar"), &mut buf, false).unwrap(), 1);
+
+        let mut reader = Trickle(b"foobar");
+
+        assert_eq!(read_chunk(&mut reader, &mut buf, true).unwrap(), 4);
+        assert_eq!(&buf, b"foob");
+        assert_eq!(read_chunk(&mut reader, &mut buf, true).unwrap(), 2);
+        assert_eq!(read_chunk(&mut reader, &mut buf, true).unwrap(), 0);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_scan_async_send() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let db: StreamingDatabase = pattern! {"test"}.build().unwrap();
+        let mut s = db.alloc_scratch().unwrap();
+        let mut reader = &b"foo test"[..];
+        let mut matches = 0;
+
+        let scan = db.scan_async(&mut reader, &mut s, |_, _, _, _| {
+            matches += 1;
+            Matching::Continue
+        });
+
+        assert_send(&scan);
+        assert_eq!(tokio_test::block_on(scan).unwrap(), ScanOutcome::Completed);
+        assert_eq!(matches, 1);
+    }
+
+    #[test]
+    fn test_scan_read_error() {
+        let db: StreamingDatabase = pattern! {"test"}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let mut matches = 0;
+
+        let res = db.scan(&mut Broken(b"foo test"), &s, |_, _, _, _| {
+            matches += 1;
+            Matching::Continue
+        });
+
+        assert!(res.is_err());
+        assert_eq!(matches, 1);
+    }
 }
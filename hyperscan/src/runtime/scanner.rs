@@ -0,0 +1,113 @@
+use crate::common::{Block, Database, DatabaseRef, Streaming, Vectored};
+use crate::errors::Result;
+use crate::runtime::{MatchEventHandler, ScanOutcome, ScratchRef, StreamRef};
+
+/// The common scan entry point of the block, vectored and streaming scanners,
+/// so the generic code can be written once for all the modes.
+///
+/// The inherent `scan` methods of the databases take precedence over this trait,
+/// call it with `Scan::scan` where the inherent method has a different signature, e.g. the streaming database.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, Scan};
+/// fn count<S: Scan + ?Sized>(scanner: &S, data: &str, scratch: &Scratch) -> usize {
+///     let mut matches = 0;
+///
+///     Scan::scan(scanner, data, scratch, |_, _, _, _| {
+///         matches += 1;
+///         Matching::Continue
+///     })
+///     .unwrap();
+///
+///     matches
+/// }
+///
+/// let block: BlockDatabase = pattern! {"test"}.build().unwrap();
+/// let s = block.alloc_scratch().unwrap();
+/// assert_eq!(count(&block, "test a test", &s), 2);
+///
+/// let vectored: VectoredDatabase = pattern! {"test"}.build().unwrap();
+/// let s = vectored.alloc_scratch().unwrap();
+/// assert_eq!(count(&vectored, "test a test", &s), 2);
+///
+/// let streaming: StreamingDatabase = pattern! {"test"}.build().unwrap();
+/// let s = streaming.alloc_scratch().unwrap();
+/// assert_eq!(count(&streaming, "test a test", &s), 2);
+///
+/// let st = streaming.open_stream().unwrap();
+/// assert_eq!(count(&*st, "test a te", &s), 1);
+/// assert_eq!(count(&*st, "st", &s), 1);
+/// ```
+pub trait Scan {
+    /// Scan the data and call the match event handler for each match.
+    fn scan<T, F>(&self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler;
+}
+
+impl Scan for DatabaseRef<Block> {
+    fn scan<T, F>(&self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        DatabaseRef::<Block>::scan(self, data, scratch, on_match_event)
+    }
+}
+
+/// The data is scanned as a single block of the vector.
+impl Scan for DatabaseRef<Vectored> {
+    fn scan<T, F>(&self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        DatabaseRef::<Vectored>::scan(self, &[data.as_ref()], scratch, on_match_event)
+    }
+}
+
+/// The data is scanned as a whole stream, which is opened, written and closed in turn.
+impl Scan for DatabaseRef<Streaming> {
+    fn scan<T, F>(&self, data: T, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let stream = self.open_stream()?;
+
+        let (callback, userdata) = unsafe { on_match_event.split() };
+
+        if stream.scan(data, scratch, (callback, userdata))?.is_terminated() {
+            return Ok(ScanOutcome::Terminated);
+        }
+
+        stream.close(scratch, (callback, userdata))
+    }
+}
+
+/// The data is written to the opened stream.
+impl Scan for StreamRef {
+    fn scan<T, F>(&self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        StreamRef::scan(self, data, scratch, on_match_event)
+    }
+}
+
+impl<M> Scan for Database<M>
+where
+    DatabaseRef<M>: Scan,
+{
+    fn scan<T, F>(&self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        Scan::scan(&**self, data, scratch, on_match_event)
+    }
+}
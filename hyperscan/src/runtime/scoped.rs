@@ -0,0 +1,132 @@
+use std::borrow::Borrow;
+use std::ops::Deref;
+use std::thread;
+
+use crate::common::{DatabaseRef, Streaming};
+use crate::errors::Result;
+use crate::runtime::{MatchEventHandler, ScanOutcome, ScratchRef, Stream, StreamRef};
+
+/// A stream which owns its scratch space and match event handler,
+/// and closes itself when dropped, so the end of data matches are never lost.
+///
+/// The errors of closing the stream on drop are ignored, use `ScopedStream::finish` to handle them.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, MATCH_AT_EOD};
+/// let db: StreamingDatabase = pattern! {"test$"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut matches = vec![];
+///
+/// {
+///     let mut st = db
+///         .open_scoped_stream(&s, |_, from, to, flags| {
+///             matches.push((from, to, flags & MATCH_AT_EOD != 0));
+///             Matching::Continue
+///         })
+///         .unwrap();
+///
+///     st.scan("foo t").unwrap();
+///     st.scan("est").unwrap();
+/// }
+///
+/// assert_eq!(matches, vec![(4, 8, true)]);
+/// ```
+pub struct ScopedStream<S, F>
+where
+    S: Deref,
+    S::Target: Borrow<ScratchRef>,
+    F: MatchEventHandler,
+{
+    stream: Option<Stream>,
+    scratch: S,
+    on_match_event: F,
+}
+
+impl DatabaseRef<Streaming> {
+    /// Open a stream which owns the scratch space and the match event handler, and closes itself when dropped.
+    pub fn open_scoped_stream<S, F>(&self, scratch: S, on_match_event: F) -> Result<ScopedStream<S, F>>
+    where
+        S: Deref,
+        S::Target: Borrow<ScratchRef>,
+        F: MatchEventHandler,
+    {
+        self.open_stream()
+            .map(|stream| ScopedStream::new(stream, scratch, on_match_event))
+    }
+}
+
+impl<S, F> ScopedStream<S, F>
+where
+    S: Deref,
+    S::Target: Borrow<ScratchRef>,
+    F: MatchEventHandler,
+{
+    /// Take the ownership of an opened stream.
+    pub fn new(stream: Stream, scratch: S, on_match_event: F) -> Self {
+        ScopedStream {
+            stream: Some(stream),
+            scratch,
+            on_match_event,
+        }
+    }
+
+    /// Returns the underlying stream.
+    pub fn stream(&self) -> &StreamRef {
+        self.stream.as_ref().expect("stream")
+    }
+
+    /// Returns the owned scratch space.
+    pub fn scratch(&self) -> &ScratchRef {
+        Borrow::<ScratchRef>::borrow(&*self.scratch)
+    }
+
+    /// Write data to the stream, the matches are delivered to the owned handler.
+    pub fn scan<T: AsRef<[u8]>>(&mut self, data: T) -> Result<ScanOutcome> {
+        let (callback, userdata) = unsafe { self.on_match_event.split() };
+
+        self.stream().scan(data, self.scratch(), (callback, userdata))
+    }
+
+    /// Reset the stream to an initial state, the end of data matches are delivered to the owned handler.
+    pub fn reset(&mut self) -> Result<ScanOutcome> {
+        let (callback, userdata) = unsafe { self.on_match_event.split() };
+
+        self.stream().reset(self.scratch(), (callback, userdata))
+    }
+
+    /// Close the stream, delivering the end of data matches, and returns the outcome of closing it.
+    pub fn finish(mut self) -> Result<ScanOutcome> {
+        self.close()
+    }
+
+    fn close(&mut self) -> Result<ScanOutcome> {
+        match self.stream.take() {
+            Some(stream) => {
+                let (callback, userdata) = unsafe { self.on_match_event.split() };
+
+                stream.close(self.scratch(), (callback, userdata))
+            }
+            None => Ok(ScanOutcome::Completed),
+        }
+    }
+}
+
+impl<S, F> Drop for ScopedStream<S, F>
+where
+    S: Deref,
+    S::Target: Borrow<ScratchRef>,
+    F: MatchEventHandler,
+{
+    fn drop(&mut self) {
+        if thread::panicking() {
+            // don't call the handler while unwinding, a second panic would abort the process.
+            if let Some(stream) = self.stream.take() {
+                let _ = stream.free();
+            }
+        } else {
+            let _ = self.close();
+        }
+    }
+}
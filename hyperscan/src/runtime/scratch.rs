@@ -1,16 +1,22 @@
-use std::mem::MaybeUninit;
+use std::mem::{ManuallyDrop, MaybeUninit};
 use std::ptr::NonNull;
 
-use anyhow::Result;
 use foreign_types::{foreign_type, ForeignType, ForeignTypeRef};
 
 use crate::common::DatabaseRef;
-use crate::errors::AsResult;
+use crate::diagnostics;
+use crate::errors::{AsResult, Result};
 use crate::ffi;
+use crate::runtime::retry;
 
 foreign_type! {
     /// A large enough region of scratch space to support a given database.
-    pub unsafe type Scratch {
+    ///
+    /// The scratch space can be moved to another thread, but it isn't `Sync`,
+    /// so it can't be shared by the concurrent scans of the different threads.
+    // SAFETY: the scratch space is plain memory allocated by Hyperscan, which isn't bound to the allocating thread,
+    // Hyperscan only requires that it isn't used by more than one scan at a time, which `Send` without `Sync` keeps.
+    pub unsafe type Scratch: Send {
         type CType = ffi::hs_scratch_t;
 
         fn drop = free_scratch;
@@ -19,7 +25,8 @@ foreign_type! {
 }
 
 unsafe fn free_scratch(s: *mut ffi::hs_scratch_t) {
-    ffi::hs_free_scratch(s).expect("free scratch");
+    // best effort, the failure is reported by the diagnostics, use `Scratch::free` to handle it.
+    let _ = ffi::hs_free_scratch(s).ok();
 }
 
 unsafe fn clone_scratch(s: *mut ffi::hs_scratch_t) -> *mut ffi::hs_scratch_t {
@@ -35,15 +42,33 @@ impl Scratch {
     /// or concurrent caller, is required.
     ///
     unsafe fn alloc<T>(db: &DatabaseRef<T>) -> Result<Scratch> {
-        let mut s = MaybeUninit::zeroed();
-        ffi::hs_alloc_scratch(db.as_ptr(), s.as_mut_ptr()).map(|_| Scratch::from_ptr(s.assume_init()))
+        #[cfg(feature = "zeroize")]
+        crate::runtime::scrub::install();
+
+        retry::alloc(|| {
+            let mut s = MaybeUninit::zeroed();
+            ffi::hs_alloc_scratch(db.as_ptr(), s.as_mut_ptr()).map(|_| {
+                let s = Scratch::from_ptr(s.assume_init());
+                diagnostics::scratch(&s);
+                s
+            })
+        })
+    }
+
+    /// Free a scratch block previously allocated, returns the error instead of ignoring it on drop.
+    pub fn free(self) -> Result<()> {
+        let s = ManuallyDrop::new(self);
+
+        unsafe { ffi::hs_free_scratch(s.as_ptr()).ok() }
     }
 
     /// Reallocate a "scratch" space for use by Hyperscan.
     unsafe fn realloc<T>(&mut self, db: &DatabaseRef<T>) -> Result<()> {
         let mut p = self.as_ptr();
 
-        ffi::hs_alloc_scratch(db.as_ptr(), &mut p).map(|_| {
+        // Hyperscan may release the scratch and reset the pointer when the allocation fails,
+        // so the retry starts from the pointer left by the last attempt.
+        retry::alloc(|| ffi::hs_alloc_scratch(db.as_ptr(), &mut p).ok()).map(|_| {
             self.0 = NonNull::new_unchecked(p);
         })
     }
@@ -56,6 +81,15 @@ impl ScratchRef {
 
         unsafe { ffi::hs_scratch_size(self.as_ptr(), size.as_mut_ptr()).map(|_| size.assume_init()) }
     }
+
+    /// Clone the scratch space, returns the error instead of panicking when the allocation fails.
+    pub fn try_clone(&self) -> Result<Scratch> {
+        retry::alloc(|| unsafe {
+            let mut p = MaybeUninit::uninit();
+
+            ffi::hs_clone_scratch(self.as_ptr(), p.as_mut_ptr()).map(|_| Scratch::from_ptr(p.assume_init()))
+        })
+    }
 }
 
 impl<T> DatabaseRef<T> {
@@ -106,4 +140,64 @@ pub mod tests {
 
         assert!(s2.size().unwrap() > s.size().unwrap());
     }
+
+    #[cfg(not(feature = "zeroize"))]
+    #[test]
+    fn test_realloc_retry() {
+        use std::cell::Cell;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        use libc::c_void;
+
+        use crate::errors::AsResult;
+        use crate::ffi;
+        use crate::runtime::{clear_alloc_failure_hook, set_alloc_failure_hook};
+
+        thread_local! {
+            static FAIL: Cell<bool> = Cell::new(false);
+        }
+
+        // only the allocations of the current thread fail, the other tests are not affected
+        unsafe extern "C" fn failing_alloc(size: usize) -> *mut c_void {
+            if FAIL.with(Cell::get) {
+                std::ptr::null_mut()
+            } else {
+                libc::malloc(size)
+            }
+        }
+
+        unsafe extern "C" fn free(p: *mut c_void) {
+            libc::free(p)
+        }
+
+        let db: BlockDatabase = "test".parse().unwrap();
+        let mut s = db.alloc_scratch().unwrap();
+        let db2: VectoredDatabase = "foobar".parse().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        set_alloc_failure_hook({
+            let calls = calls.clone();
+
+            move |err| {
+                assert!(err.is_alloc_failure());
+
+                calls.fetch_add(1, Ordering::SeqCst);
+                FAIL.with(|fail| fail.set(false));
+                true
+            }
+        });
+
+        unsafe { ffi::hs_set_scratch_allocator(Some(failing_alloc), Some(free)).expect("set scratch allocator") };
+        FAIL.with(|fail| fail.set(true));
+
+        let res = db2.realloc_scratch(&mut s).map(|s| s.size().unwrap());
+
+        FAIL.with(|fail| fail.set(false));
+        unsafe { ffi::hs_set_scratch_allocator(None, None).expect("reset scratch allocator") };
+        clear_alloc_failure_hook();
+
+        assert!(res.unwrap() > SCRATCH_SIZE);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
 }
@@ -0,0 +1,70 @@
+//! Scrub the scratch and stream state memory when it is released.
+//!
+//! When the `zeroize` feature is enabled, the scratch and stream allocators of Hyperscan are replaced
+//! before the first scratch or stream is allocated, the memory will be zeroized before returning to the system.
+use std::alloc::{self, Layout};
+use std::ptr;
+use std::slice;
+use std::sync::Once;
+
+use libc::c_void;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::errors::{AsResult, Result};
+use crate::ffi;
+use crate::runtime::StreamRef;
+
+/// The header keeps the size of allocation and the alignment of the returned memory.
+const HEADER_SIZE: usize = 64;
+
+unsafe extern "C" fn zeroizing_alloc(size: usize) -> *mut c_void {
+    let layout = match Layout::from_size_align(HEADER_SIZE + size, HEADER_SIZE) {
+        Ok(layout) => layout,
+        Err(_) => return ptr::null_mut(),
+    };
+    let p = alloc::alloc(layout);
+
+    if p.is_null() {
+        return ptr::null_mut();
+    }
+
+    p.cast::<usize>().write(size);
+    p.add(HEADER_SIZE).cast()
+}
+
+unsafe extern "C" fn zeroizing_free(p: *mut c_void) {
+    if p.is_null() {
+        return;
+    }
+
+    let p = p.cast::<u8>().sub(HEADER_SIZE);
+    let size = HEADER_SIZE + p.cast::<usize>().read();
+
+    slice::from_raw_parts_mut(p, size).zeroize();
+    alloc::dealloc(p, Layout::from_size_align_unchecked(size, HEADER_SIZE));
+}
+
+/// Install the zeroizing allocators for the scratch and stream state.
+///
+/// The allocators must be installed before any scratch or stream is allocated,
+/// it will be done automatically when allocating scratch, opening or expanding stream.
+pub(crate) fn install() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| unsafe {
+        ffi::hs_set_scratch_allocator(Some(zeroizing_alloc), Some(zeroizing_free)).expect("set scratch allocator");
+        ffi::hs_set_stream_allocator(Some(zeroizing_alloc), Some(zeroizing_free)).expect("set stream allocator");
+    })
+}
+
+impl StreamRef {
+    /// Creates a compressed representation of the provided stream in a buffer which will be zeroized on drop.
+    pub fn compress_zeroizing(&self) -> Result<Zeroizing<Vec<u8>>> {
+        let mut buf = Zeroizing::new(vec![0; self.compressed_size()?]);
+        let len = self.compress(&mut buf)?;
+
+        buf.truncate(len);
+
+        Ok(buf)
+    }
+}
@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use crate::common::{Block, DatabaseRef, Mode, Streaming, Vectored};
+use crate::errors::Result;
+use crate::runtime::{Matching, ScratchRef};
+
+/// The report of a startup self-test of a database with a scratch space.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelfTest {
+    /// The name of compiled mode of the database.
+    pub mode: &'static str,
+    /// The size of the database in bytes.
+    pub database_size: usize,
+    /// The size of the scratch space in bytes.
+    pub scratch_size: usize,
+    /// The size of the stream state in bytes, only for the streaming database.
+    pub stream_size: Option<usize>,
+    /// The number of the matches of the synthetic data.
+    pub matches: usize,
+    /// The time elapsed by the synthetic scan.
+    pub elapsed: Duration,
+}
+
+/// The synthetic data covers all the byte values.
+fn synthetic_data() -> Vec<u8> {
+    (0..=u8::MAX).collect()
+}
+
+fn self_test<T, F>(db: &DatabaseRef<T>, scratch: &ScratchRef, stream_size: Option<usize>, scan: F) -> Result<SelfTest>
+where
+    T: Mode + 'static,
+    F: FnOnce(&[u8], &mut dyn FnMut(u32, u64, u64, u32) -> Matching) -> Result<()>,
+{
+    let database_size = db.size()?;
+    let scratch_size = scratch.size()?;
+    let data = synthetic_data();
+    let mut matches = 0;
+
+    let start = Instant::now();
+
+    scan(&data, &mut |_, _, _, _| {
+        matches += 1;
+        Matching::Continue
+    })?;
+
+    Ok(SelfTest {
+        mode: db.name(),
+        database_size,
+        scratch_size,
+        stream_size,
+        matches,
+        elapsed: start.elapsed(),
+    })
+}
+
+impl DatabaseRef<Block> {
+    /// Run a tiny synthetic scan to validate that the database, scratch space and platform work together,
+    /// which catches the misconfigured deployments at startup before the real traffic arrives.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: BlockDatabase = pattern! {"[a-z]+"}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let report = db.self_test(&s).unwrap();
+    ///
+    /// assert_eq!(report.mode, "Block");
+    /// assert!(report.database_size > 0);
+    /// assert!(report.scratch_size > 0);
+    /// assert_eq!(report.stream_size, None);
+    /// assert_eq!(report.matches, 26);
+    /// ```
+    pub fn self_test(&self, scratch: &ScratchRef) -> Result<SelfTest> {
+        self_test(self, scratch, None, |data, on_match_event| {
+            self.scan(data, scratch, on_match_event).map(|_| ())
+        })
+    }
+}
+
+impl DatabaseRef<Vectored> {
+    /// Run a tiny synthetic vectored scan to validate that the database, scratch space and platform work together.
+    pub fn self_test(&self, scratch: &ScratchRef) -> Result<SelfTest> {
+        self_test(self, scratch, None, |data, on_match_event| {
+            let (head, tail) = data.split_at(data.len() / 2);
+
+            self.scan(&[head, tail], scratch, on_match_event).map(|_| ())
+        })
+    }
+}
+
+impl DatabaseRef<Streaming> {
+    /// Run a tiny synthetic open/write/close cycle of a stream to validate that the database, scratch space
+    /// and platform work together.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: StreamingDatabase = pattern! {"[a-z]+"}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let report = db.self_test(&s).unwrap();
+    ///
+    /// assert_eq!(report.mode, "Streaming");
+    /// assert!(report.stream_size.unwrap() > 0);
+    /// assert_eq!(report.matches, 26);
+    /// ```
+    pub fn self_test(&self, scratch: &ScratchRef) -> Result<SelfTest> {
+        let stream_size = self.stream_size()?;
+
+        self_test(self, scratch, Some(stream_size), |data, on_match_event| {
+            let stream = self.open_stream()?;
+            let (head, tail) = data.split_at(data.len() / 2);
+
+            // the counting callback never terminates the scan
+            let _ = stream.scan(head, scratch, &mut *on_match_event)?;
+            let _ = stream.scan(tail, scratch, &mut *on_match_event)?;
+            stream.close(scratch, on_match_event).map(|_| ())
+        })
+    }
+}
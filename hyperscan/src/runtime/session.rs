@@ -0,0 +1,389 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::{Entry, HashMap};
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::mem;
+
+use crate::common::{DatabaseRef, Streaming};
+use crate::errors::{Error, Result};
+use crate::runtime::{MatchEventHandler, Matching, ScanOutcome, ScratchRef, Stream, StreamRef};
+
+/// The streams of the sessions keyed by the session key, e.g. the addresses and ports of a connection.
+///
+/// A stream is opened on the first data of a session, and closed when the session is removed,
+/// the end of data matches are reported when the stream is closed.
+///
+/// With a memory budget, the stream states of the least recently used sessions are compressed
+/// when the open streams exceed the budget, and expanded transparently on the next data of the session.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, SessionTable};
+/// let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut sessions = SessionTable::new(&db).max_sessions(1000);
+/// let mut matches = vec![];
+///
+/// for (key, data) in &[("a", "foo te"), ("b", "te"), ("a", "st bar"), ("b", "xt")] {
+///     sessions
+///         .scan(*key, data, &s, |_, from, to, _| {
+///             matches.push((key.to_string(), from, to));
+///             Matching::Continue
+///         })
+///         .unwrap();
+/// }
+///
+/// assert_eq!(sessions.len(), 2);
+/// assert!(sessions.remove(&"a", &s, Matching::Continue).unwrap().is_some());
+/// sessions.flush(&s, |_, _, _, _, _| Matching::Continue).unwrap();
+/// assert!(sessions.is_empty());
+///
+/// assert_eq!(matches, vec![("a".to_string(), 4, 8)]);
+/// ```
+pub struct SessionTable<'a, K> {
+    db: &'a DatabaseRef<Streaming>,
+    sessions: HashMap<K, Session>,
+    lru: BTreeMap<u64, K>,
+    tick: u64,
+    max_sessions: Option<usize>,
+    memory_budget: Option<usize>,
+    stream_size: Option<usize>,
+    compressed: usize,
+}
+
+struct Session {
+    state: State,
+    tick: u64,
+}
+
+enum State {
+    Open(Stream),
+    Compressed(Vec<u8>),
+}
+
+impl<'a, K: Hash + Eq + Clone> SessionTable<'a, K> {
+    /// Constructs a session table opening the streams of the database.
+    pub fn new(db: &'a DatabaseRef<Streaming>) -> Self {
+        SessionTable {
+            db,
+            sessions: HashMap::new(),
+            lru: BTreeMap::new(),
+            tick: 0,
+            max_sessions: None,
+            memory_budget: None,
+            stream_size: None,
+            compressed: 0,
+        }
+    }
+
+    /// Set the maximum number of the open sessions, unlimited by default.
+    ///
+    /// The data of a new session is rejected with `Error::SessionLimit` when the table is full.
+    pub fn max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = Some(max_sessions);
+        self
+    }
+
+    /// Set the memory budget in bytes of the stream states, both the open and the compressed ones, unlimited by default.
+    ///
+    /// The stream states of the least recently used sessions are compressed when the budget is exceeded,
+    /// the most recently used session is always kept open.
+    pub fn memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+
+    /// Returns the number of the sessions with an uncompressed stream state.
+    pub fn open_streams(&self) -> usize {
+        self.lru.len()
+    }
+
+    /// Returns the total size of the compressed stream states.
+    pub fn compressed_size(&self) -> usize {
+        self.compressed
+    }
+
+    /// Returns the number of the open sessions.
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Returns `true` if there is no open session.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Returns `true` if the session is open.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.sessions.contains_key(key)
+    }
+
+    /// Returns the stream of an open session, or `None` if the stream state of the session is compressed.
+    pub fn get<Q>(&self, key: &Q) -> Option<&StreamRef>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.sessions.get(key) {
+            Some(Session {
+                state: State::Open(stream),
+                ..
+            }) => Some(stream),
+            _ => None,
+        }
+    }
+
+    /// Returns the keys of the open sessions.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.sessions.keys()
+    }
+
+    /// Write the data of a session to its stream, the stream is opened on the first data of the session.
+    pub fn scan<T, F>(&mut self, key: K, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let max_sessions = self.max_sessions;
+        let len = self.sessions.len();
+
+        self.tick += 1;
+
+        let session = match self.sessions.entry(key.clone()) {
+            Entry::Occupied(e) => {
+                let session = e.into_mut();
+
+                match session.state {
+                    State::Open(_) => {
+                        self.lru.remove(&session.tick);
+                    }
+                    State::Compressed(ref buf) => {
+                        let stream = self.db.expand_stream(buf)?;
+
+                        self.compressed -= buf.len();
+                        session.state = State::Open(stream);
+                    }
+                }
+
+                session
+            }
+            Entry::Vacant(_) if max_sessions.map_or(false, |max| len >= max) => {
+                return Err(Error::SessionLimit(len));
+            }
+            Entry::Vacant(e) => e.insert(Session {
+                state: State::Open(self.db.open_stream()?),
+                tick: self.tick,
+            }),
+        };
+
+        session.tick = self.tick;
+        self.lru.insert(self.tick, key);
+
+        let outcome = match session.state {
+            State::Open(ref stream) => stream.scan(data, scratch, on_match_event)?,
+            State::Compressed(_) => unreachable!(),
+        };
+
+        self.evict()?;
+
+        Ok(outcome)
+    }
+
+    /// Compress the stream states of the least recently used sessions until the stream states fit in the budget.
+    fn evict(&mut self) -> Result<()> {
+        let budget = match self.memory_budget {
+            Some(budget) => budget,
+            None => return Ok(()),
+        };
+        let stream_size = match self.stream_size {
+            Some(size) => size,
+            None => *self.stream_size.insert(self.db.stream_size()?),
+        };
+
+        while self.lru.len() > 1 && self.memory_usage(stream_size) > budget {
+            let tick = *self.lru.keys().next().expect("lru");
+            let key = self.lru.remove(&tick).expect("lru");
+            let session = self.sessions.get_mut(&key).expect("session");
+
+            let buf = match session.state {
+                State::Open(ref stream) => match stream.compress_to_vec() {
+                    Ok(buf) => buf,
+                    Err(err) => {
+                        // the session is still open, keep it tracked for the next eviction
+                        self.lru.insert(tick, key);
+
+                        return Err(err);
+                    }
+                },
+                State::Compressed(_) => continue,
+            };
+
+            self.compressed += buf.len();
+
+            if let State::Open(stream) = mem::replace(&mut session.state, State::Compressed(buf)) {
+                stream.free()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the total size of the open and the compressed stream states.
+    fn memory_usage(&self, stream_size: usize) -> usize {
+        self.lru
+            .len()
+            .saturating_mul(stream_size)
+            .saturating_add(self.compressed)
+    }
+
+    /// Remove a session and close its stream, returns the outcome of closing it or `None` if the session isn't open.
+    pub fn remove<Q, F>(&mut self, key: &Q, scratch: &ScratchRef, on_match_event: F) -> Result<Option<ScanOutcome>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: MatchEventHandler,
+    {
+        match self.sessions.remove(key) {
+            Some(session) => self.take_stream(session)?.close(scratch, on_match_event).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Release the bookkeeping of a removed session, and returns its stream expanded if it was compressed.
+    fn take_stream(&mut self, session: Session) -> Result<Stream> {
+        match session.state {
+            State::Open(stream) => {
+                self.lru.remove(&session.tick);
+
+                Ok(stream)
+            }
+            State::Compressed(buf) => {
+                self.compressed -= buf.len();
+
+                self.db.expand_stream(&buf)
+            }
+        }
+    }
+
+    /// Close all the open sessions, the matches are passed to the callback with the session key.
+    ///
+    /// Terminating the end of data matches of a session doesn't stop closing the other sessions.
+    pub fn flush<F>(&mut self, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    where
+        F: FnMut(&K, u32, u64, u64, u32) -> Matching,
+    {
+        let db = self.db;
+
+        self.lru.clear();
+        self.compressed = 0;
+        self.sessions.drain().try_for_each(|(key, session)| {
+            let stream = match session.state {
+                State::Open(stream) => stream,
+                State::Compressed(buf) => db.expand_stream(&buf)?,
+            };
+
+            stream
+                .close(scratch, |id, from, to, flags| on_match_event(&key, id, from, to, flags))
+                .map(|_| ())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_session_table() {
+        let db: StreamingDatabase = pattern! {"test$"; SOM_LEFTMOST}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let mut sessions = SessionTable::new(&db).max_sessions(2);
+
+        assert_eq!(sessions.scan(1, "foo te", &s, ()).unwrap(), ScanOutcome::Completed);
+        assert_eq!(sessions.scan(2, "te", &s, ()).unwrap(), ScanOutcome::Completed);
+
+        assert_eq!(sessions.scan(3, "test", &s, ()).unwrap_err(), Error::SessionLimit(2));
+        assert!(!sessions.contains(&3));
+
+        assert_eq!(sessions.scan(1, "st", &s, ()).unwrap(), ScanOutcome::Completed);
+
+        let mut closed = vec![];
+
+        assert_eq!(
+            sessions
+                .remove(&1, &s, |_, from, to, _| {
+                    closed.push((1, from, to));
+                    Matching::Continue
+                })
+                .unwrap(),
+            Some(ScanOutcome::Completed)
+        );
+        assert_eq!(sessions.remove(&1, &s, ()).unwrap(), None);
+
+        assert_eq!(sessions.scan(3, "test", &s, ()).unwrap(), ScanOutcome::Completed);
+        sessions
+            .flush(&s, |&key, _, from, to, _| {
+                closed.push((key, from, to));
+                Matching::Continue
+            })
+            .unwrap();
+
+        closed.sort_unstable();
+
+        assert!(sessions.is_empty());
+        assert_eq!(closed, vec![(1, 4, 8), (3, 0, 4)]);
+    }
+
+    #[test]
+    fn test_compress_sessions() {
+        let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let stream_size = db.stream_size().unwrap();
+        let budget = stream_size * 2;
+        let mut sessions = SessionTable::new(&db).memory_budget(budget);
+        let mut matches = vec![];
+
+        for key in 0..4 {
+            assert_eq!(sessions.scan(key, "foo te", &s, ()).unwrap(), ScanOutcome::Completed);
+        }
+
+        assert_eq!(sessions.len(), 4);
+        assert!(sessions.open_streams() <= 2);
+        assert!(sessions.compressed_size() > 0);
+        // the compressed stream states count against the budget as well
+        assert!(
+            sessions.open_streams() == 1
+                || sessions.open_streams() * stream_size + sessions.compressed_size() <= budget
+        );
+        assert!(sessions.get(&0).is_none());
+        assert!(sessions.get(&3).is_some());
+
+        for key in 0..4 {
+            let outcome = sessions
+                .scan(key, "st bar", &s, |_, from, to, _| {
+                    matches.push((key, from, to));
+                    Matching::Continue
+                })
+                .unwrap();
+
+            assert!(outcome.is_completed());
+        }
+
+        assert_eq!(matches, vec![(0, 4, 8), (1, 4, 8), (2, 4, 8), (3, 4, 8)]);
+        assert!(sessions.open_streams() <= 2);
+
+        assert!(sessions.remove(&0, &s, ()).unwrap().is_some());
+        sessions.flush(&s, |_, _, _, _, _| Matching::Continue).unwrap();
+
+        assert!(sessions.is_empty());
+        assert_eq!(sessions.open_streams(), 0);
+        assert_eq!(sessions.compressed_size(), 0);
+    }
+}
@@ -0,0 +1,191 @@
+use rayon::prelude::*;
+
+use crate::common::BlockDatabase;
+#[cfg(feature = "compile")]
+use crate::compile::{Builder, Pattern, Patterns};
+#[cfg(feature = "compile")]
+use crate::errors::Error;
+use crate::errors::Result;
+use crate::runtime::{Matching, ScanOutcome, Scratch};
+
+/// The block mode databases partitioned from a large pattern set,
+/// which are scanned in parallel against the same input.
+///
+/// Each shard owns its scratch space, the shards are scanned across the `rayon` thread pool,
+/// and the matches of all shards are merged in the order of the end offset.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, Sharded};
+/// let patterns = patterns!("foo", "bar", "baz");
+/// let mut sharded = Sharded::build(&patterns, 2).unwrap();
+/// let mut matches = vec![];
+///
+/// sharded.scan("baz bar foo", |id, from, to, _flags| {
+///     matches.push((id, to));
+///     Matching::Continue
+/// }).unwrap();
+///
+/// assert_eq!(matches, vec![(2, 3), (1, 7), (0, 11)]);
+/// ```
+pub struct Sharded {
+    shards: Vec<(BlockDatabase, Scratch)>,
+}
+
+impl Sharded {
+    /// Constructs a sharded scanner from the databases, allocating a scratch space for each of them.
+    pub fn new<I: IntoIterator<Item = BlockDatabase>>(databases: I) -> Result<Self> {
+        databases
+            .into_iter()
+            .map(|db| db.alloc_scratch().map(|s| (db, s)))
+            .collect::<Result<Vec<_>>>()
+            .map(|shards| Sharded { shards })
+    }
+
+    /// Partition the patterns into `n` shards with round robin, and compile each shard.
+    ///
+    /// The pattern without an explicit id is assigned with its index in the whole pattern set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    #[cfg(feature = "compile")]
+    pub fn build(patterns: &Patterns, n: usize) -> Result<Self> {
+        assert!(n > 0, "number of shards must be positive");
+
+        let mut parts: Vec<Vec<Pattern>> = vec![vec![]; n.min(patterns.len()).max(1)];
+        let len = parts.len();
+
+        for (i, pattern) in patterns.iter().enumerate() {
+            let mut pattern = pattern.clone();
+            pattern.id.get_or_insert(i);
+            parts[i % len].push(pattern);
+        }
+
+        parts
+            .into_iter()
+            .map(|part| Patterns::from(part).build().map_err(Error::from_build))
+            .collect::<Result<Vec<_>>>()
+            .and_then(Self::new)
+    }
+
+    /// Returns the number of shards.
+    pub fn len(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns `true` if there is no shard.
+    pub fn is_empty(&self) -> bool {
+        self.shards.is_empty()
+    }
+
+    /// Scan the data with all shards in parallel, the merged matches are passed to the callback.
+    ///
+    /// The matches of the different shards with the same end offset are reported in the order of the shards.
+    pub fn scan<T, F>(&mut self, data: T, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: FnMut(u32, u64, u64, u32) -> Matching,
+    {
+        let data = data.as_ref();
+
+        let shards = self
+            .shards
+            .par_iter_mut()
+            .map(|(db, scratch)| {
+                let mut matches = vec![];
+
+                db.scan(data, scratch, |id, from, to, flags| {
+                    matches.push((id, from, to, flags));
+                    Matching::Continue
+                })
+                .map(|_| matches)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut matches = shards.into_iter().flatten().collect::<Vec<_>>();
+
+        matches.sort_by_key(|&(_, _, to, _)| to);
+
+        for (id, from, to, flags) in matches {
+            if on_match_event(id, from, to, flags) == Matching::Terminate {
+                return Ok(ScanOutcome::Terminated);
+            }
+        }
+
+        Ok(ScanOutcome::Completed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_sharded() {
+        let patterns = patterns!("foo", "bar", "baz", "qux"; SOM_LEFTMOST);
+        let mut sharded = Sharded::build(&patterns, 3).unwrap();
+
+        assert_eq!(sharded.len(), 3);
+
+        let mut matches = vec![];
+
+        let outcome = sharded
+            .scan("qux baz bar foo", |id, from, to, _| {
+                matches.push((id, from, to));
+                Matching::Continue
+            })
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Completed);
+        assert_eq!(matches, vec![(3, 0, 3), (2, 4, 7), (1, 8, 11), (0, 12, 15)]);
+
+        let mut matches = vec![];
+
+        let outcome = sharded
+            .scan("qux baz bar foo", |id, _, _, _| {
+                matches.push(id);
+                Matching::Terminate
+            })
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Terminated);
+        assert_eq!(matches, vec![3]);
+    }
+
+    #[test]
+    fn test_sharded_more_shards_than_patterns() {
+        let patterns = patterns!("foo", "bar");
+        let sharded = Sharded::build(&patterns, 8).unwrap();
+
+        assert_eq!(sharded.len(), 2);
+        assert!(!sharded.is_empty());
+
+        assert!(matches!(
+            Sharded::build(&patterns!("foo", "a("), 2),
+            Err(Error::CompileError(_))
+        ));
+    }
+
+    #[test]
+    fn test_sharded_new() {
+        let foo: BlockDatabase = pattern! {"foo"; SOM_LEFTMOST}.build().unwrap();
+        let bar: BlockDatabase = pattern! {"bar"; SOM_LEFTMOST}.build().unwrap();
+        let mut sharded = Sharded::new(vec![foo, bar]).unwrap();
+        let mut matches = vec![];
+
+        sharded
+            .scan("bar foo", |id, from, to, _| {
+                matches.push((id, from, to));
+                Matching::Continue
+            })
+            .unwrap();
+
+        assert_eq!(matches, vec![(0, 0, 3), (0, 4, 7)]);
+
+        assert!(Sharded::new(vec![]).unwrap().is_empty());
+    }
+}
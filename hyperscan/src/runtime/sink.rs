@@ -0,0 +1,28 @@
+use std::path::Path;
+
+use crate::errors::Error;
+use crate::runtime::Matching;
+
+/// The receiver of the matches found when scanning files.
+///
+/// It is shared by the workers of the scanner, so the matches of the different files may be interleaved.
+pub trait MatchSink: Sync {
+    /// A match of the pattern `id` was found in the file, returns `Matching::Terminate` to skip the rest of the file.
+    fn on_match(&self, path: &Path, id: u32, from: u64, to: u64) -> Matching;
+
+    /// Failed to access or scan the file.
+    ///
+    /// The errors of walking or watching the directories are reported as `Error::Io`.
+    fn on_error(&self, path: Option<&Path>, err: &Error) {
+        let _ = (path, err);
+    }
+}
+
+impl<F> MatchSink for F
+where
+    F: Fn(&Path, u32, u64, u64) -> Matching + Sync,
+{
+    fn on_match(&self, path: &Path, id: u32, from: u64, to: u64) -> Matching {
+        self(path, id, from, to)
+    }
+}
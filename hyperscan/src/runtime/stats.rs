@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use crate::runtime::Matching;
+
+/// A distribution of values bucketed by powers of two.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Distribution {
+    count: u64,
+    sum: u64,
+    min: Option<u64>,
+    max: Option<u64>,
+    buckets: Vec<u64>,
+}
+
+impl Distribution {
+    /// Record a value.
+    pub fn record(&mut self, value: u64) {
+        let bucket = Self::bucket_of(value);
+
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum = self.sum.saturating_add(value);
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+    }
+
+    /// The number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The minimum recorded value.
+    pub fn min(&self) -> Option<u64> {
+        self.min
+    }
+
+    /// The maximum recorded value.
+    pub fn max(&self) -> Option<u64> {
+        self.max
+    }
+
+    /// The mean of recorded values.
+    pub fn mean(&self) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.sum as f64 / self.count as f64)
+        }
+    }
+
+    /// An iterator over the non-empty buckets as `(lower, upper, count)`,
+    /// each bucket counts the values in the range `[lower, upper)`.
+    pub fn buckets(&self) -> impl Iterator<Item = (u64, u64, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(i, &count)| match i {
+                0 => (0, 1, count),
+                64 => (1 << 63, u64::MAX, count),
+                _ => (1 << (i - 1), 1 << i, count),
+            })
+    }
+
+    fn bucket_of(value: u64) -> usize {
+        (64 - value.leading_zeros()) as usize
+    }
+}
+
+/// The match statistics of a pattern.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatternStats {
+    /// The distribution of the end offsets of matches.
+    pub offsets: Distribution,
+    /// The distribution of the length of matches, only available for `SOM_LEFTMOST` patterns.
+    pub lengths: Distribution,
+}
+
+/// Collects where in buffers or streams the matches occur.
+///
+/// The match length distribution is only meaningful for patterns compiled with `SOM_LEFTMOST` flag,
+/// so it must be enabled with `MatchStats::track_lengths`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, MatchStats};
+/// let db: BlockDatabase = pattern! {"a+"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut stats = MatchStats::new().track_lengths(true);
+///
+/// db.scan("baaab", &s, stats.wrap(|_, _, _, _| Matching::Continue)).unwrap();
+///
+/// let pattern = stats.get(0).unwrap();
+///
+/// assert_eq!(pattern.offsets.count(), 3);
+/// assert_eq!(pattern.offsets.min(), Some(2));
+/// assert_eq!(pattern.offsets.max(), Some(4));
+/// assert_eq!(pattern.lengths.max(), Some(3));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MatchStats {
+    track_lengths: bool,
+    patterns: HashMap<u32, PatternStats>,
+}
+
+impl MatchStats {
+    /// Constructs a new, empty `MatchStats`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable the match length distribution.
+    pub fn track_lengths(mut self, yes: bool) -> Self {
+        self.track_lengths = yes;
+        self
+    }
+
+    /// Returns the statistics of the pattern.
+    pub fn get(&self, id: u32) -> Option<&PatternStats> {
+        self.patterns.get(&id)
+    }
+
+    /// An iterator visiting the statistics of all matched patterns in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &PatternStats)> {
+        self.patterns.iter().map(|(&id, stats)| (id, stats))
+    }
+
+    /// Resets the statistics.
+    pub fn reset(&mut self) {
+        self.patterns.clear()
+    }
+
+    /// Record a match of the pattern.
+    pub fn record(&mut self, id: u32, from: u64, to: u64) {
+        let stats = self.patterns.entry(id).or_default();
+
+        stats.offsets.record(to);
+
+        if self.track_lengths {
+            stats.lengths.record(to.saturating_sub(from));
+        }
+    }
+
+    /// Wrap a match event callback that records every match before delivering it.
+    pub fn wrap<'a, F>(&'a mut self, mut on_match_event: F) -> impl FnMut(u32, u64, u64, u32) -> Matching + 'a
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching + 'a,
+    {
+        move |id, from, to, flags| {
+            self.record(id, from, to);
+
+            on_match_event(id, from, to, flags)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution() {
+        let mut d = Distribution::default();
+
+        assert_eq!(d.mean(), None);
+
+        for v in &[0, 1, 2, 3, 4, 100] {
+            d.record(*v);
+        }
+
+        assert_eq!(d.count(), 6);
+        assert_eq!(d.min(), Some(0));
+        assert_eq!(d.max(), Some(100));
+        assert_eq!(
+            d.buckets().collect::<Vec<_>>(),
+            vec![(0, 1, 1), (1, 2, 1), (2, 4, 2), (4, 8, 1), (64, 128, 1)]
+        );
+    }
+}
@@ -1,12 +1,13 @@
-use std::mem::MaybeUninit;
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ptr;
 
-use anyhow::Result;
 use foreign_types::{foreign_type, ForeignType, ForeignTypeRef};
 
-use crate::common::{DatabaseRef, Streaming};
-use crate::errors::AsResult;
+use crate::closure;
+use crate::common::{Capability, DatabaseRef, Streaming};
+use crate::errors::{AsResult, Result};
 use crate::ffi;
-use crate::runtime::{MatchEventHandler, ScratchRef};
+use crate::runtime::{retry, MatchEventHandler, ScanOutcome, ScratchRef, MATCH_AT_EOD};
 
 impl DatabaseRef<Streaming> {
     /// Provides the size of the stream state allocated by a single stream opened against the given database.
@@ -16,17 +17,59 @@ impl DatabaseRef<Streaming> {
         unsafe { ffi::hs_stream_size(self.as_ptr(), size.as_mut_ptr()).map(|_| size.assume_init()) }
     }
 
+    /// Provides the size of the stream state allocated by the given number of concurrent streams.
+    pub fn streams_size(&self, streams: usize) -> Result<usize> {
+        self.stream_size().map(|size| size.saturating_mul(streams))
+    }
+
+    /// Provides the memory required to scan the given number of concurrent streams with the given number
+    /// of scratch spaces, including the database itself, for the capacity planning.
+    ///
+    /// A scratch space is allocated temporarily to measure its size.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: StreamingDatabase = pattern! {"test"}.build().unwrap();
+    ///
+    /// let size = db.memory_required(100_000, 8).unwrap();
+    ///
+    /// assert!(size >= db.size().unwrap() + db.streams_size(100_000).unwrap());
+    /// ```
+    pub fn memory_required(&self, streams: usize, scratches: usize) -> Result<usize> {
+        let scratch_size = self.alloc_scratch()?.size()?;
+
+        Ok(self
+            .size()?
+            .saturating_add(self.streams_size(streams)?)
+            .saturating_add(scratch_size.saturating_mul(scratches)))
+    }
+
     /// Open and initialise a stream.
     pub fn open_stream(&self) -> Result<Stream> {
-        let mut s = MaybeUninit::uninit();
+        #[cfg(feature = "zeroize")]
+        crate::runtime::scrub::install();
 
-        unsafe { ffi::hs_open_stream(self.as_ptr(), 0, s.as_mut_ptr()).map(|_| Stream::from_ptr(s.assume_init())) }
+        let s = retry::alloc(|| {
+            let mut s = MaybeUninit::uninit();
+
+            unsafe { ffi::hs_open_stream(self.as_ptr(), 0, s.as_mut_ptr()).map(|_| Stream::from_ptr(s.assume_init())) }
+        })?;
+
+        #[cfg(feature = "prometheus")]
+        crate::metrics::stream_opened(self.as_ptr(), s.as_ptr(), || self.stream_size());
+
+        Ok(s)
     }
 }
 
 foreign_type! {
     /// A pattern matching state can be maintained across multiple blocks of target data
-    pub unsafe type Stream {
+    ///
+    /// The stream state is plain memory owned by the handle, so it can be moved to another thread,
+    /// but it must not be written concurrently.
+    pub unsafe type Stream: Send {
         type CType = ffi::hs_stream_t;
 
         fn drop = drop_stream;
@@ -34,20 +77,129 @@ foreign_type! {
     }
 }
 
-fn drop_stream(_s: *mut ffi::hs_stream_t) {}
+/// Close the stream without reporting the end of data matches.
+unsafe fn drop_stream(s: *mut ffi::hs_stream_t) {
+    #[cfg(feature = "prometheus")]
+    crate::metrics::stream_closed(s);
+
+    // best effort, the failure is reported by the diagnostics, use `Stream::free` to handle it.
+    let _ = ffi::hs_close_stream(s, ptr::null_mut(), None, ptr::null_mut()).ok();
+}
 
 /// Duplicate the given stream.
 ///
 /// The new stream will have the same state as the original including the current stream offset.
+///
+/// # Panics
+///
+/// Panics if the stream can't be copied, use `StreamRef::try_clone` to handle the error.
 unsafe fn clone_stream(s: *mut ffi::hs_stream_t) -> *mut ffi::hs_stream_t {
-    let mut p = MaybeUninit::uninit();
+    StreamRef::from_ptr(s).try_clone().expect("copy stream").into_ptr()
+}
+
+/// The match event handler which marks the end of data matches with `MATCH_AT_EOD`.
+///
+/// Hyperscan doesn't report the termination of the end of data matches by the callback,
+/// so it is recorded by the handler.
+struct AtEod {
+    callback: ffi::match_event_handler,
+    userdata: *mut libc::c_void,
+    terminated: bool,
+}
+
+impl AtEod {
+    unsafe fn wrap<F: MatchEventHandler>(on_match_event: &mut F) -> Self {
+        let (callback, userdata) = on_match_event.split();
 
-    ffi::hs_copy_stream(p.as_mut_ptr(), s).expect("copy stream");
+        AtEod {
+            callback,
+            userdata,
+            terminated: false,
+        }
+    }
+
+    fn split(&mut self) -> (ffi::match_event_handler, *mut libc::c_void) {
+        unsafe extern "C" fn trampoline(
+            id: u32,
+            from: u64,
+            to: u64,
+            flags: u32,
+            ctx: *mut ::libc::c_void,
+        ) -> ::libc::c_int {
+            let handler = &mut *ctx.cast::<AtEod>();
+            let code = handler.callback.map_or(0, |callback| {
+                callback(id, from, to, flags | MATCH_AT_EOD, handler.userdata)
+            });
+
+            handler.terminated |= code != 0;
+
+            code
+        }
+
+        match self.callback {
+            Some(_) => (Some(trampoline), self as *mut _ as *mut _),
+            None => (None, ptr::null_mut()),
+        }
+    }
 
-    p.assume_init()
+    /// Returns the outcome of the call reporting the end of data matches.
+    #[track_caller]
+    fn outcome(&self, code: ffi::hs_error_t) -> Result<ScanOutcome> {
+        closure::resume_unwind();
+
+        match ScanOutcome::from_code(code)? {
+            ScanOutcome::Completed if self.terminated => Ok(ScanOutcome::Terminated),
+            outcome => Ok(outcome),
+        }
+    }
 }
 
 impl StreamRef {
+    /// Duplicate the stream, the new stream will have the same state as the original
+    /// including the current stream offset, e.g. to fork the scan state for a speculative parsing branch.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+    ///
+    /// let s = db.alloc_scratch().unwrap();
+    /// let st = db.open_stream().unwrap();
+    ///
+    /// let mut matches = vec![];
+    ///
+    /// let mut callback = |_, from, to, _| {
+    ///     matches.push((from, to));
+    ///
+    ///     Matching::Continue
+    /// };
+    ///
+    /// st.scan("foo t", &s, &mut callback).unwrap();
+    ///
+    /// let forked = st.try_clone().unwrap();
+    ///
+    /// st.scan("est", &s, &mut callback).unwrap();
+    /// forked.scan("ext", &s, &mut callback).unwrap();
+    ///
+    /// st.close(&s, &mut callback).unwrap();
+    /// forked.close(&s, &mut callback).unwrap();
+    ///
+    /// assert_eq!(matches, vec![(4, 8)]);
+    /// ```
+    pub fn try_clone(&self) -> Result<Stream> {
+        let s = retry::alloc(|| {
+            let mut s = MaybeUninit::uninit();
+
+            unsafe { ffi::hs_copy_stream(s.as_mut_ptr(), self.as_ptr()).map(|_| Stream::from_ptr(s.assume_init())) }
+        })?;
+
+        #[cfg(feature = "prometheus")]
+        crate::metrics::stream_copied(self.as_ptr(), s.as_ptr());
+
+        Ok(s)
+    }
+
     /// Reset a stream to an initial state.
     ///
     /// Conceptually, this is equivalent to performing `Stream::close` on the given stream,
@@ -57,48 +209,48 @@ impl StreamRef {
     ///
     /// Note: This operation may result in matches being returned (via calls to the match event callback)
     /// for expressions anchored to the end of the original data stream
-    /// (for example, via the use of the `$` meta-character).
+    /// (for example, via the use of the `$` meta-character), which are marked with `MATCH_AT_EOD` in the flags,
+    /// `ScanOutcome::Terminated` is returned if the callback terminated them.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// # use hyperscan::prelude::*;
-    /// let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+    /// # use hyperscan::{prelude::*, MATCH_AT_EOD};
+    /// let db: StreamingDatabase = pattern! {"test$"; SOM_LEFTMOST}.build().unwrap();
     ///
     /// let s = db.alloc_scratch().unwrap();
     /// let st = db.open_stream().unwrap();
     ///
-    /// let data = vec!["foo t", "es", "t bar"];
     /// let mut matches = vec![];
     ///
-    /// let mut callback = |_, from, to, _| {
-    ///     matches.push((from, to));
+    /// let mut callback = |_, from, to, flags| {
+    ///     matches.push((from, to, flags & MATCH_AT_EOD != 0));
     ///
     ///     Matching::Continue
     /// };
     ///
-    /// for d in &data {
-    ///     st.scan(d, &s, &mut callback).unwrap();
-    /// }
-    ///
+    /// // the end of data match of the first connection is reported by the reset
+    /// st.scan("foo t", &s, &mut callback).unwrap();
+    /// st.scan("est", &s, &mut callback).unwrap();
     /// st.reset(&s, &mut callback).unwrap();
     ///
-    /// for d in &data {
-    ///     st.scan(d, &s, &mut callback).unwrap();
-    /// }
-    ///
-    /// st.close(&s, callback).unwrap();
+    /// // the stream is reused for the next connection, whose offsets restart at 0
+    /// st.scan("test", &s, &mut callback).unwrap();
+    /// st.close(&s, &mut callback).unwrap();
     ///
-    /// assert_eq!(matches, vec![(4, 8), (4, 8)]);
+    /// assert_eq!(matches, vec![(4, 8, true), (0, 4, true)]);
     /// ```
-    pub fn reset<F>(&self, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    pub fn reset<F>(&self, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
     where
         F: MatchEventHandler,
     {
         unsafe {
+            let mut on_match_event = AtEod::wrap(&mut on_match_event);
             let (callback, userdata) = on_match_event.split();
 
-            ffi::hs_reset_stream(self.as_ptr(), 0, scratch.as_ptr(), callback, userdata).ok()
+            let code = ffi::hs_reset_stream(self.as_ptr(), 0, scratch.as_ptr(), callback, userdata);
+
+            on_match_event.outcome(code)
         }
     }
 
@@ -139,14 +291,23 @@ impl StreamRef {
     ///
     /// assert_eq!(matches, vec![(0, 4), (4, 8)]);
     /// ```
-    pub fn reset_and_copy_stream<F>(&self, from: &StreamRef, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    pub fn reset_and_copy_stream<F>(
+        &self,
+        from: &StreamRef,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
     where
         F: MatchEventHandler,
     {
         unsafe {
+            let mut on_match_event = AtEod::wrap(&mut on_match_event);
             let (callback, userdata) = on_match_event.split();
 
-            ffi::hs_reset_and_copy_stream(self.as_ptr(), from.as_ptr(), scratch.as_ptr(), callback, userdata).ok()
+            let code =
+                ffi::hs_reset_and_copy_stream(self.as_ptr(), from.as_ptr(), scratch.as_ptr(), callback, userdata);
+
+            on_match_event.outcome(code)
         }
     }
 }
@@ -160,16 +321,59 @@ impl Stream {
     ///
     /// This function must be called for any stream created with `StreamingDatabase::open_stream`,
     /// even if scanning has been terminated by a non-zero return from the match callback function.
-    pub fn close<F>(self, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    ///
+    /// The end of data matches reported by closing the stream are marked with `MATCH_AT_EOD` in the flags,
+    /// `ScanOutcome::Terminated` is returned if the callback terminated the end of data matches.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::{prelude::*, MATCH_AT_EOD};
+    /// let db: StreamingDatabase = pattern! {"test$"; SOM_LEFTMOST}.build().unwrap();
+    /// let s = db.alloc_scratch().unwrap();
+    /// let st = db.open_stream().unwrap();
+    /// let mut matches = vec![];
+    ///
+    /// let mut callback = |_, from, to, flags| {
+    ///     matches.push((from, to, flags & MATCH_AT_EOD != 0));
+    ///     Matching::Continue
+    /// };
+    ///
+    /// st.scan("foo test", &s, &mut callback).unwrap();
+    /// st.close(&s, &mut callback).unwrap();
+    ///
+    /// assert_eq!(matches, vec![(4, 8, true)]);
+    /// ```
+    pub fn close<F>(self, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
     where
         F: MatchEventHandler,
     {
+        let stream = ManuallyDrop::new(self);
+
+        #[cfg(feature = "prometheus")]
+        crate::metrics::stream_closed(stream.as_ptr());
+
         unsafe {
+            let mut on_match_event = AtEod::wrap(&mut on_match_event);
             let (callback, userdata) = on_match_event.split();
 
-            ffi::hs_close_stream(self.as_ptr(), scratch.as_ptr(), callback, userdata).ok()
+            let code = ffi::hs_close_stream(stream.as_ptr(), scratch.as_ptr(), callback, userdata);
+
+            on_match_event.outcome(code)
         }
     }
+
+    /// Close a stream without reporting the end of data matches, so the scratch space is not required.
+    ///
+    /// The stream is also closed this way when it is dropped, but the error is ignored.
+    pub fn free(self) -> Result<()> {
+        let stream = ManuallyDrop::new(self);
+
+        #[cfg(feature = "prometheus")]
+        crate::metrics::stream_closed(stream.as_ptr());
+
+        unsafe { ffi::hs_close_stream(stream.as_ptr(), ptr::null_mut(), None, ptr::null_mut()).ok() }
+    }
 }
 
 impl StreamRef {
@@ -209,6 +413,8 @@ impl StreamRef {
     /// assert_eq!(matches, vec![(4, 8)]);
     /// ```
     pub fn compress(&self, buf: &mut [u8]) -> Result<usize> {
+        Capability::CompressStream.ensure()?;
+
         let mut size = MaybeUninit::uninit();
 
         unsafe {
@@ -218,6 +424,56 @@ impl StreamRef {
         }
     }
 
+    /// Provides the size of the buffer required by `StreamRef::compress` for the current stream state.
+    pub fn compressed_size(&self) -> Result<usize> {
+        Capability::CompressStream.ensure()?;
+
+        let mut size = 0;
+
+        match unsafe { ffi::hs_compress_stream(self.as_ptr(), ptr::null_mut(), 0, &mut size) } {
+            ffi::HS_INSUFFICIENT_SPACE => Ok(size),
+            err => err.ok().map(|_| size),
+        }
+    }
+
+    /// Creates a compressed representation of the provided stream in a new buffer of the required size,
+    /// e.g. to swap the state of an idle flow out of the hot memory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hyperscan::prelude::*;
+    /// let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+    ///
+    /// let s = db.alloc_scratch().unwrap();
+    /// let st = db.open_stream().unwrap();
+    ///
+    /// st.scan("foo te", &s, Matching::Continue).unwrap();
+    ///
+    /// let buf = st.compress_to_vec().unwrap();
+    /// st.free().unwrap();
+    ///
+    /// let st = db.expand_stream(&buf).unwrap();
+    /// let mut matches = vec![];
+    ///
+    /// st.scan("st bar", &s, |_, from, to, _| {
+    ///     matches.push((from, to));
+    ///     Matching::Continue
+    /// })
+    /// .unwrap();
+    /// st.free().unwrap();
+    ///
+    /// assert_eq!(matches, vec![(4, 8)]);
+    /// ```
+    pub fn compress_to_vec(&self) -> Result<Vec<u8>> {
+        let mut buf = vec![0; self.compressed_size()?];
+        let len = self.compress(&mut buf)?;
+
+        buf.truncate(len);
+
+        Ok(buf)
+    }
+
     /// Decompresses a compressed representation created by `StreamRef::compress` on top of the stream.
     /// The stream will first be reset (reporting any EOD matches).
     ///
@@ -257,22 +513,26 @@ impl StreamRef {
     ///
     /// assert_eq!(matches, vec![(4, 8), (4, 8)]);
     /// ```
-    pub fn reset_and_expand<F>(&self, buf: &[u8], scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    pub fn reset_and_expand<F>(&self, buf: &[u8], scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
     where
         F: MatchEventHandler,
     {
+        Capability::CompressStream.ensure()?;
+
         unsafe {
+            let mut on_match_event = AtEod::wrap(&mut on_match_event);
             let (callback, userdata) = on_match_event.split();
 
-            ffi::hs_reset_and_expand_stream(
+            let code = ffi::hs_reset_and_expand_stream(
                 self.as_ptr(),
                 buf.as_ptr() as *const _,
                 buf.len(),
                 scratch.as_ptr(),
                 callback,
                 userdata,
-            )
-            .ok()
+            );
+
+            on_match_event.outcome(code)
         }
     }
 }
@@ -315,12 +575,24 @@ impl DatabaseRef<Streaming> {
     /// assert_eq!(matches, vec![(4, 8)]);
     /// ```
     pub fn expand_stream(&self, buf: &[u8]) -> Result<Stream> {
-        let mut stream = MaybeUninit::uninit();
+        Capability::CompressStream.ensure()?;
 
-        unsafe {
-            ffi::hs_expand_stream(self.as_ptr(), stream.as_mut_ptr(), buf.as_ptr() as *const _, buf.len())
-                .ok()
-                .map(|_| Stream::from_ptr(stream.assume_init()))
-        }
+        #[cfg(feature = "zeroize")]
+        crate::runtime::scrub::install();
+
+        let s = retry::alloc(|| {
+            let mut stream = MaybeUninit::uninit();
+
+            unsafe {
+                ffi::hs_expand_stream(self.as_ptr(), stream.as_mut_ptr(), buf.as_ptr() as *const _, buf.len())
+                    .ok()
+                    .map(|_| Stream::from_ptr(stream.assume_init()))
+            }
+        })?;
+
+        #[cfg(feature = "prometheus")]
+        crate::metrics::stream_opened(self.as_ptr(), s.as_ptr(), || self.stream_size());
+
+        Ok(s)
     }
 }
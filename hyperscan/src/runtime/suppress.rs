@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::runtime::Matching;
+
+/// The window of counting the repeated matches of a pattern, starting from the first match in the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Window {
+    /// The matches which end within the given bytes of the stream.
+    Bytes(u64),
+    /// The matches which are reported within the given time.
+    Time(Duration),
+}
+
+/// The semantics of delivering the repeated matches in a window, like the `threshold` of the IDS engines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Suppression {
+    /// Deliver the first N matches of each window.
+    Limit(u32),
+    /// Deliver every Nth match of each window.
+    Threshold(u32),
+    /// Deliver once per window after N matches.
+    Both(u32),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Start {
+    Offset(u64),
+    Time(Instant),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct State {
+    start: Start,
+    count: u32,
+}
+
+/// Drop the repeated matches of the same pattern in a byte or time window, reducing the callback storms
+/// from the chatty rules.
+///
+/// The state is kept per pattern, a `Suppressor` should be used for one stream,
+/// and reset before scanning a new block or stream.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, Suppression, Suppressor, Window};
+/// let db: BlockDatabase = pattern! {"a"}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut suppressor = Suppressor::new(Suppression::Limit(2), Window::Bytes(10));
+/// let mut matches = vec![];
+///
+/// db.scan("aaaaa-----aaa", &s, suppressor.wrap(|_, _, to, _| {
+///     matches.push(to);
+///     Matching::Continue
+/// })).unwrap();
+///
+/// assert_eq!(matches, vec![1, 2, 11, 12]);
+/// assert_eq!(suppressor.suppressed(), 4);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Suppressor {
+    suppression: Suppression,
+    window: Window,
+    patterns: HashMap<u32, State>,
+    suppressed: u64,
+}
+
+impl Suppressor {
+    /// Constructs a suppressor applying the semantics in the window to all the patterns.
+    pub fn new(suppression: Suppression, window: Window) -> Self {
+        Suppressor {
+            suppression,
+            window,
+            patterns: HashMap::new(),
+            suppressed: 0,
+        }
+    }
+
+    /// Returns the number of the dropped matches.
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+
+    /// Forget the windows of the patterns, should be called before scanning a new block or stream.
+    pub fn reset(&mut self) {
+        self.patterns.clear()
+    }
+
+    /// Returns `true` if the match of the pattern which ends at the offset should be delivered.
+    pub fn check(&mut self, id: u32, to: u64) -> bool {
+        let start = match self.window {
+            Window::Bytes(_) => Start::Offset(to),
+            Window::Time(_) => Start::Time(Instant::now()),
+        };
+        let window = self.window;
+        let state = self.patterns.entry(id).or_insert(State { start, count: 0 });
+
+        let expired = match (state.start, window) {
+            (Start::Offset(off), Window::Bytes(bytes)) => to.saturating_sub(off) >= bytes,
+            (Start::Time(t), Window::Time(time)) => t.elapsed() >= time,
+            _ => true,
+        };
+
+        if expired {
+            *state = State { start, count: 0 };
+        }
+
+        state.count += 1;
+
+        let deliver = match self.suppression {
+            Suppression::Limit(n) => state.count <= n,
+            Suppression::Threshold(n) => n <= 1 || state.count % n == 0,
+            Suppression::Both(n) => state.count == n.max(1),
+        };
+
+        if !deliver {
+            self.suppressed += 1;
+        }
+
+        deliver
+    }
+
+    /// Wrap a match event callback that only receives the matches allowed by the suppression.
+    pub fn wrap<'a, F>(&'a mut self, mut on_match_event: F) -> impl FnMut(u32, u64, u64, u32) -> Matching + 'a
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching + 'a,
+    {
+        move |id, from, to, flags| {
+            if self.check(id, to) {
+                on_match_event(id, from, to, flags)
+            } else {
+                Matching::Continue
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delivered(suppression: Suppression, offsets: &[u64]) -> Vec<u64> {
+        let mut suppressor = Suppressor::new(suppression, Window::Bytes(10));
+
+        offsets.iter().copied().filter(|&to| suppressor.check(0, to)).collect()
+    }
+
+    #[test]
+    fn test_suppression() {
+        let offsets = [1, 2, 3, 4, 5, 11, 12, 13];
+
+        assert_eq!(delivered(Suppression::Limit(2), &offsets), vec![1, 2, 11, 12]);
+        assert_eq!(delivered(Suppression::Threshold(2), &offsets), vec![2, 4, 12]);
+        assert_eq!(delivered(Suppression::Both(3), &offsets), vec![3, 13]);
+    }
+
+    #[test]
+    fn test_time_window() {
+        let mut suppressor = Suppressor::new(Suppression::Limit(1), Window::Time(Duration::from_secs(60)));
+
+        assert!(suppressor.check(0, 1));
+        assert!(!suppressor.check(0, 100));
+        assert!(suppressor.check(1, 100));
+
+        suppressor.reset();
+
+        assert!(suppressor.check(0, 200));
+        assert_eq!(suppressor.suppressed(), 1);
+    }
+}
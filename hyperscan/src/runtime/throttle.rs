@@ -0,0 +1,185 @@
+use std::io::Read;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::common::{Block, DatabaseRef, Streaming, Vectored};
+use crate::errors::Result;
+use crate::runtime::{read_chunk, MatchEventHandler, ScanOutcome, ScratchRef, SCAN_BUF_SIZE};
+
+/// A token bucket limiting the scanning throughput in bytes per second.
+///
+/// A scan larger than the available tokens is allowed to borrow from the future,
+/// the following scans will be delayed until the debt has been paid off.
+#[derive(Clone, Debug)]
+pub struct Throttle {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Throttle {
+    /// Constructs a token bucket with the given bytes per second budget,
+    /// which allows a burst of one second worth of bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_sec` is zero.
+    pub fn new(bytes_per_sec: u64) -> Self {
+        assert!(bytes_per_sec > 0, "throughput budget must be positive");
+
+        let rate = bytes_per_sec as f64;
+
+        Throttle {
+            rate,
+            burst: rate,
+            tokens: rate,
+            last: Instant::now(),
+        }
+    }
+
+    /// Set the maximum number of bytes can be scanned in a burst.
+    pub fn with_burst(mut self, burst: u64) -> Self {
+        self.burst = burst as f64;
+        self.tokens = self.tokens.min(self.burst);
+        self
+    }
+
+    /// Take the tokens for the given bytes, returns how long the caller should wait before scanning.
+    pub fn reserve(&mut self, bytes: usize) -> Duration {
+        let now = Instant::now();
+
+        self.tokens = (self.tokens + now.duration_since(self.last).as_secs_f64() * self.rate).min(self.burst);
+        self.last = now;
+        self.tokens -= bytes as f64;
+
+        if self.tokens >= 0.0 {
+            Duration::default()
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+
+    /// Take the tokens for the given bytes, blocks the current thread until they are available.
+    pub fn acquire(&mut self, bytes: usize) {
+        let delay = self.reserve(bytes);
+
+        if delay > Duration::default() {
+            thread::sleep(delay);
+        }
+    }
+}
+
+/// A database wrapper which limits the scanning throughput with a `Throttle`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, Throttle};
+/// let db: BlockDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut db = db.throttled(Throttle::new(1024 * 1024));
+/// let mut matches = vec![];
+///
+/// db.scan("foo test bar", &s, |_, from, to, _| {
+///     matches.push(from..to);
+///     Matching::Continue
+/// }).unwrap();
+///
+/// assert_eq!(matches, vec![4..8]);
+/// ```
+pub struct Throttled<'a, T> {
+    db: &'a DatabaseRef<T>,
+    throttle: Throttle,
+}
+
+impl<T> DatabaseRef<T> {
+    /// Limit the scanning throughput of the database with a token bucket.
+    pub fn throttled(&self, throttle: Throttle) -> Throttled<'_, T> {
+        Throttled { db: self, throttle }
+    }
+}
+
+impl<T> Throttled<'_, T> {
+    /// Returns the token bucket of the wrapper.
+    pub fn throttle(&mut self) -> &mut Throttle {
+        &mut self.throttle
+    }
+}
+
+impl Throttled<'_, Block> {
+    /// The block regular expression scanner, blocks until the budget allows to scan the data.
+    pub fn scan<T, F>(&mut self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let data = data.as_ref();
+
+        self.throttle.acquire(data.len());
+        self.db.scan(data, scratch, on_match_event)
+    }
+}
+
+impl Throttled<'_, Vectored> {
+    /// The vectored regular expression scanner, blocks until the budget allows to scan the data.
+    pub fn scan<I, T, F>(&mut self, data: I, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let data = data.into_iter().collect::<Vec<_>>();
+
+        self.throttle.acquire(data.iter().map(|buf| buf.as_ref().len()).sum());
+        self.db.scan(&data, scratch, on_match_event)
+    }
+}
+
+impl Throttled<'_, Streaming> {
+    /// Pattern matching takes place for stream-mode pattern databases,
+    /// blocks before each chunk until the budget allows to scan it.
+    pub fn scan<R, F>(&mut self, reader: &mut R, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        R: Read,
+        F: MatchEventHandler,
+    {
+        let stream = self.db.open_stream()?;
+        let mut buf = [0; SCAN_BUF_SIZE];
+
+        let (callback, userdata) = unsafe { on_match_event.split() };
+
+        loop {
+            let len = read_chunk(reader, &mut buf, false)?;
+
+            if len == 0 {
+                break;
+            }
+
+            self.throttle.acquire(len);
+
+            if stream.scan(&buf[..len], scratch, (callback, userdata))?.is_terminated() {
+                return Ok(ScanOutcome::Terminated);
+            }
+        }
+
+        stream.close(scratch, (callback, userdata))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle() {
+        let mut throttle = Throttle::new(1000).with_burst(100);
+
+        assert_eq!(throttle.reserve(100), Duration::default());
+
+        let delay = throttle.reserve(100);
+
+        assert!(delay > Duration::from_millis(50));
+        assert!(delay <= Duration::from_millis(100));
+    }
+}
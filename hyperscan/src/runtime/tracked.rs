@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+
+use crate::common::{DatabaseRef, Streaming};
+use crate::errors::Result;
+use crate::runtime::{Matching, ScanOutcome, ScratchRef, Stream, StreamRef};
+
+/// The activity counters of a stream.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StreamStats {
+    /// The number of bytes written to the stream.
+    pub bytes: u64,
+    /// The number of writes to the stream.
+    pub writes: u64,
+    /// The number of matches reported by the stream.
+    pub matches: u64,
+    /// The time of the last write to the stream.
+    pub last_activity: Option<Instant>,
+}
+
+impl StreamStats {
+    /// Record a write of the given bytes.
+    pub fn record_write(&mut self, len: usize) {
+        self.bytes += len as u64;
+        self.writes += 1;
+        self.last_activity = Some(Instant::now());
+    }
+
+    /// Record a match reported by the stream.
+    pub fn record_match(&mut self) {
+        self.matches += 1;
+    }
+
+    /// Returns how long the stream has been idle since the last write.
+    pub fn idle(&self) -> Option<Duration> {
+        self.last_activity.map(|t| t.elapsed())
+    }
+}
+
+/// A stream which tracks the bytes written, writes, matches and the last activity.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::prelude::*;
+/// let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut st = db.open_tracked_stream().unwrap();
+///
+/// for data in &["foo t", "est b", "ar test"] {
+///     st.scan(data, &s, |_, _, _, _| Matching::Continue).unwrap();
+/// }
+///
+/// let stats = st.stats();
+///
+/// assert_eq!(stats.bytes, 17);
+/// assert_eq!(stats.writes, 3);
+/// assert_eq!(stats.matches, 2);
+/// assert!(stats.last_activity.is_some());
+///
+/// st.close(&s, |_, _, _, _| Matching::Continue).unwrap();
+/// ```
+pub struct TrackedStream {
+    stream: Stream,
+    stats: StreamStats,
+}
+
+impl DatabaseRef<Streaming> {
+    /// Open and initialise a stream which tracks its activity.
+    pub fn open_tracked_stream(&self) -> Result<TrackedStream> {
+        self.open_stream().map(TrackedStream::new)
+    }
+}
+
+impl TrackedStream {
+    /// Track the activity of an opened stream.
+    pub fn new(stream: Stream) -> Self {
+        TrackedStream {
+            stream,
+            stats: StreamStats::default(),
+        }
+    }
+
+    /// Returns the activity counters of the stream.
+    pub fn stats(&self) -> &StreamStats {
+        &self.stats
+    }
+
+    /// Returns the underlying stream.
+    pub fn stream(&self) -> &StreamRef {
+        &self.stream
+    }
+
+    /// Write data to the stream, the matches are counted before delivering them to the callback.
+    pub fn scan<T, F>(&mut self, data: T, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: FnMut(u32, u64, u64, u32) -> Matching,
+    {
+        let data = data.as_ref();
+        let stats = &mut self.stats;
+
+        stats.record_write(data.len());
+
+        self.stream.scan(data, scratch, |id, from, to, flags| {
+            stats.record_match();
+
+            on_match_event(id, from, to, flags)
+        })
+    }
+
+    /// Reset the stream to an initial state, the counters are kept.
+    pub fn reset<F>(&mut self, scratch: &ScratchRef, mut on_match_event: F) -> Result<ScanOutcome>
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching,
+    {
+        let stats = &mut self.stats;
+
+        self.stream.reset(scratch, |id, from, to, flags| {
+            stats.record_match();
+
+            on_match_event(id, from, to, flags)
+        })
+    }
+
+    /// Close the stream, returns the final counters including the end of data matches, and the outcome of closing it.
+    pub fn close<F>(self, scratch: &ScratchRef, mut on_match_event: F) -> Result<(StreamStats, ScanOutcome)>
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching,
+    {
+        let TrackedStream { stream, mut stats } = self;
+
+        let outcome = stream.close(scratch, |id, from, to, flags| {
+            stats.record_match();
+
+            on_match_event(id, from, to, flags)
+        })?;
+
+        Ok((stats, outcome))
+    }
+}
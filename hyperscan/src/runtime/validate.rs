@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::runtime::Matching;
+
+type Validator = Box<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+/// A registry of validators per pattern id, which runs on the matched bytes before the callback sees the event.
+///
+/// The match is dropped if the validator of its pattern rejects the matched bytes,
+/// the patterns without a validator are always delivered.
+/// The patterns should be compiled with `SOM_LEFTMOST` to report the start of the matches.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, Validators};
+/// let db: BlockDatabase = pattern! {r"\b\d{16}\b"; SOM_LEFTMOST}.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let mut validators = Validators::new();
+/// validators.register(0, Validators::luhn);
+///
+/// let data = "4111111111111111 4111111111111112";
+/// let mut matches = vec![];
+///
+/// db.scan(data, &s, validators.wrap(data.as_bytes(), |_, from, to, _| {
+///     matches.push(&data[from as usize..to as usize]);
+///     Matching::Continue
+/// })).unwrap();
+///
+/// assert_eq!(matches, vec!["4111111111111111"]);
+/// ```
+#[derive(Default)]
+pub struct Validators {
+    validators: HashMap<u32, Validator>,
+}
+
+impl fmt::Debug for Validators {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.validators.keys()).finish()
+    }
+}
+
+impl Validators {
+    /// Constructs a new, empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a registry with the validators of the `patterns::pii` collection.
+    #[cfg(feature = "compile")]
+    pub fn pii() -> Self {
+        use crate::patterns::pii;
+
+        let mut validators = Self::new();
+
+        for rule in &[pii::VISA, pii::MASTERCARD, pii::DISCOVER, pii::AMEX] {
+            validators.register(rule.id as u32, Self::luhn);
+        }
+
+        validators.register(pii::US_SSN.id as u32, Self::us_ssn);
+        validators
+    }
+
+    /// Register the validator of the pattern, replaces the previous one.
+    pub fn register<F>(&mut self, id: u32, validator: F) -> &mut Self
+    where
+        F: Fn(&[u8]) -> bool + Send + Sync + 'static,
+    {
+        self.validators.insert(id, Box::new(validator));
+        self
+    }
+
+    /// Remove the validator of the pattern.
+    pub fn unregister(&mut self, id: u32) -> &mut Self {
+        self.validators.remove(&id);
+        self
+    }
+
+    /// Returns `true` if the matched bytes of the pattern are valid.
+    pub fn validate(&self, id: u32, matched: &[u8]) -> bool {
+        self.validators.get(&id).map_or(true, |validator| validator(matched))
+    }
+
+    /// Wrap a match event callback that only receives the validated matches of the scanned data.
+    pub fn wrap<'a, F>(
+        &'a self,
+        data: &'a [u8],
+        mut on_match_event: F,
+    ) -> impl FnMut(u32, u64, u64, u32) -> Matching + 'a
+    where
+        F: FnMut(u32, u64, u64, u32) -> Matching + 'a,
+    {
+        move |id, from, to, flags| {
+            let matched = data.get(from as usize..to as usize).unwrap_or_default();
+
+            if self.validate(id, matched) {
+                on_match_event(id, from, to, flags)
+            } else {
+                Matching::Continue
+            }
+        }
+    }
+
+    /// Validate the card number with the Luhn checksum, the spaces and dashes are ignored.
+    pub fn luhn(matched: &[u8]) -> bool {
+        let mut sum = 0;
+        let mut digits = 0;
+
+        for &b in matched.iter().rev() {
+            match b {
+                b'0'..=b'9' => {
+                    let mut d = u32::from(b - b'0');
+
+                    if digits % 2 == 1 {
+                        d *= 2;
+
+                        if d > 9 {
+                            d -= 9;
+                        }
+                    }
+
+                    sum += d;
+                    digits += 1;
+                }
+                b' ' | b'-' => {}
+                _ => return false,
+            }
+        }
+
+        digits > 1 && sum % 10 == 0
+    }
+
+    /// Validate the US social security number, rejects the area, group or serial numbers never assigned.
+    pub fn us_ssn(matched: &[u8]) -> bool {
+        let digits = matched
+            .iter()
+            .filter(|b| b.is_ascii_digit())
+            .map(|b| u32::from(b - b'0'))
+            .collect::<Vec<_>>();
+
+        if digits.len() != 9 {
+            return false;
+        }
+
+        let area = digits[0] * 100 + digits[1] * 10 + digits[2];
+        let group = digits[3] * 10 + digits[4];
+        let serial = digits[5] * 1000 + digits[6] * 100 + digits[7] * 10 + digits[8];
+
+        area != 0 && area != 666 && area < 900 && group != 0 && serial != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luhn() {
+        assert!(Validators::luhn(b"4111 1111 1111 1111"));
+        assert!(Validators::luhn(b"3782-822463-10005"));
+        assert!(!Validators::luhn(b"4111111111111112"));
+        assert!(!Validators::luhn(b"4111x111"));
+    }
+
+    #[test]
+    fn test_us_ssn() {
+        assert!(Validators::us_ssn(b"123-45-6789"));
+        assert!(!Validators::us_ssn(b"000-45-6789"));
+        assert!(!Validators::us_ssn(b"666-45-6789"));
+        assert!(!Validators::us_ssn(b"123-00-6789"));
+        assert!(!Validators::us_ssn(b"123-45-0000"));
+    }
+}
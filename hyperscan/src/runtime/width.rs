@@ -0,0 +1,91 @@
+use crate::common::{Block, DatabaseRef, Vectored};
+use crate::errors::Result;
+use crate::runtime::{MatchEventHandler, ScanOutcome, ScratchRef};
+
+/// A database wrapper which skips scanning the input shorter than the minimum width of the patterns.
+///
+/// Since the input shorter than the minimum possible match can't match anything,
+/// the call into Hyperscan is avoided entirely, which matters when scanning a lot of tiny payloads.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*};
+/// let patterns = patterns!("foobar", "hello");
+/// let db: BlockDatabase = patterns.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+/// let db = db.with_min_width(patterns.min_width().unwrap());
+///
+/// assert!(db.is_too_short(b"foo"));
+///
+/// let mut matches = vec![];
+///
+/// for data in &["foo", "hello"] {
+///     db.scan(data, &s, |id, _, _, _| {
+///         matches.push(id);
+///         Matching::Continue
+///     }).unwrap();
+/// }
+///
+/// assert_eq!(matches, vec![1]);
+/// ```
+pub struct MinWidth<'a, T> {
+    db: &'a DatabaseRef<T>,
+    min_width: usize,
+}
+
+impl<T> DatabaseRef<T> {
+    /// Skip scanning the input shorter than the minimum width of the patterns in the database.
+    ///
+    /// The minimum width should be calculated from the patterns with `Patterns::min_width()`.
+    pub fn with_min_width(&self, min_width: usize) -> MinWidth<'_, T> {
+        MinWidth { db: self, min_width }
+    }
+}
+
+impl<T> MinWidth<'_, T> {
+    /// Returns the minimum width of the patterns.
+    pub fn min_width(&self) -> usize {
+        self.min_width
+    }
+}
+
+impl MinWidth<'_, Block> {
+    /// Returns `true` if the data is too short to match any pattern.
+    pub fn is_too_short<T: AsRef<[u8]>>(&self, data: T) -> bool {
+        data.as_ref().len() < self.min_width
+    }
+
+    /// The block regular expression scanner, skips the data shorter than the minimum width.
+    pub fn scan<T, F>(&self, data: T, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let data = data.as_ref();
+
+        if self.is_too_short(data) {
+            Ok(ScanOutcome::Completed)
+        } else {
+            self.db.scan(data, scratch, on_match_event)
+        }
+    }
+}
+
+impl MinWidth<'_, Vectored> {
+    /// The vectored regular expression scanner, skips the data shorter than the minimum width in total.
+    pub fn scan<I, T, F>(&self, data: I, scratch: &ScratchRef, on_match_event: F) -> Result<ScanOutcome>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+        F: MatchEventHandler,
+    {
+        let data = data.into_iter().collect::<Vec<_>>();
+
+        if data.iter().map(|buf| buf.as_ref().len()).sum::<usize>() < self.min_width {
+            Ok(ScanOutcome::Completed)
+        } else {
+            self.db.scan(&data, scratch, on_match_event)
+        }
+    }
+}
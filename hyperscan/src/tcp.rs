@@ -0,0 +1,537 @@
+//! Lightweight TCP flow reassembly for stream scanning.
+//!
+//! The `Reassembler` reorders the TCP segments of each flow by the sequence number,
+//! and feeds the in-order payload to a Hyperscan stream per flow,
+//! so the matches across the segment boundaries are reported correctly.
+//!
+//! The packets should be decoded by the caller, e.g. with `pcap` and `pnet`,
+//! only the sequence number, the `SYN`, `FIN` and `RST` flags and the payload of a segment are required.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use hyperscan::{prelude::*, tcp::{Reassembler, Segment}};
+//! let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+//! let s = db.alloc_scratch().unwrap();
+//! let mut reassembler = Reassembler::new(&db);
+//! let mut matches = vec![];
+//!
+//! // the segments arrive out of order
+//! for segment in &[
+//!     Segment::new(1000, b"").syn(),
+//!     Segment::new(1008, b"st data"),
+//!     Segment::new(1001, b"some te"),
+//!     Segment::new(1015, b"").fin(),
+//! ] {
+//!     reassembler
+//!         .segment("flow", segment, &s, |flow: &&str, _id, from, to| {
+//!             matches.push((flow.to_string(), from, to));
+//!             Matching::Continue
+//!         })
+//!         .unwrap();
+//! }
+//!
+//! assert_eq!(matches, vec![("flow".to_string(), 5, 9)]);
+//! assert!(reassembler.is_empty());
+//! ```
+use std::cell::Cell;
+use std::collections::hash_map::{Entry, HashMap};
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+use anyhow::Result;
+
+use crate::common::StreamingDatabase;
+use crate::ffi;
+use crate::runtime::{MatchEventHandler, Matching, ScanOutcome, ScratchRef, Stream, StreamStats};
+
+type Handler = (ffi::match_event_handler, *mut libc::c_void);
+
+const DEFAULT_MAX_BUFFERED: usize = 64 * 1024;
+
+/// The policy of handling the missing data of a flow.
+///
+/// It is applied when the out-of-order data buffered for a flow exceeds the limit,
+/// or the flow is finished before the missing data arrives.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GapPolicy {
+    /// Skip the missing data and continue scanning the stream,
+    /// the matches across the gap may be reported.
+    Continue,
+    /// Skip the missing data and reset the stream,
+    /// the offsets of the following matches are relative to the data after the gap.
+    Reset,
+    /// Stop scanning the flow until it is finished.
+    Abandon,
+}
+
+impl Default for GapPolicy {
+    fn default() -> Self {
+        GapPolicy::Reset
+    }
+}
+
+/// A decoded TCP segment.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Segment<'a> {
+    /// The sequence number.
+    pub seq: u32,
+    /// The `SYN` flag, the payload starts from the next sequence number.
+    pub syn: bool,
+    /// The `FIN` flag, the flow is finished after the segment.
+    pub fin: bool,
+    /// The `RST` flag, the flow is aborted.
+    pub rst: bool,
+    /// The payload of the segment.
+    pub payload: &'a [u8],
+}
+
+impl<'a> Segment<'a> {
+    /// Constructs a segment with the sequence number and payload.
+    pub fn new(seq: u32, payload: &'a [u8]) -> Self {
+        Segment {
+            seq,
+            payload,
+            ..Default::default()
+        }
+    }
+
+    /// Set the `SYN` flag.
+    pub fn syn(mut self) -> Self {
+        self.syn = true;
+        self
+    }
+
+    /// Set the `FIN` flag.
+    pub fn fin(mut self) -> Self {
+        self.fin = true;
+        self
+    }
+
+    /// Set the `RST` flag.
+    pub fn rst(mut self) -> Self {
+        self.rst = true;
+        self
+    }
+}
+
+struct Flow {
+    stream: Stream,
+    next_seq: u32,
+    offset: u64,
+    pending: BTreeMap<u64, Vec<u8>>,
+    buffered: usize,
+    abandoned: bool,
+    terminated: bool,
+    stats: StreamStats,
+}
+
+impl Flow {
+    fn scan(&mut self, data: &[u8], scratch: &ScratchRef, handler: Handler) -> Result<ScanOutcome> {
+        let outcome = self.stream.scan(data, scratch, handler)?;
+        self.stats.record_write(data.len());
+        self.offset += data.len() as u64;
+        self.next_seq = self.next_seq.wrapping_add(data.len() as u32);
+        self.terminate_if(outcome);
+        Ok(outcome)
+    }
+
+    /// Stop scanning the flow until it is finished if the callback terminated the scan.
+    fn terminate_if(&mut self, outcome: ScanOutcome) {
+        if outcome.is_terminated() {
+            self.terminated = true;
+            self.pending.clear();
+            self.buffered = 0;
+        }
+    }
+
+    fn deliver(&mut self, scratch: &ScratchRef, handler: Handler) -> Result<ScanOutcome> {
+        while let Some(&start) = self.pending.keys().next() {
+            if start > self.offset {
+                break;
+            }
+
+            let data = self.pending.remove(&start).unwrap_or_default();
+            let skip = (self.offset - start) as usize;
+
+            self.buffered -= data.len();
+
+            if skip < data.len() && self.scan(&data[skip..], scratch, handler)?.is_terminated() {
+                return Ok(ScanOutcome::Terminated);
+            }
+        }
+
+        Ok(ScanOutcome::Completed)
+    }
+
+    fn skip_gap(&mut self, policy: GapPolicy, scratch: &ScratchRef, handler: Handler) -> Result<ScanOutcome> {
+        if policy == GapPolicy::Abandon {
+            self.abandoned = true;
+            self.pending.clear();
+            self.buffered = 0;
+        } else if let Some(&start) = self.pending.keys().next() {
+            if policy == GapPolicy::Reset {
+                let outcome = self.stream.reset(scratch, handler)?;
+
+                self.terminate_if(outcome);
+
+                if outcome.is_terminated() {
+                    return Ok(outcome);
+                }
+            }
+
+            self.next_seq = self.next_seq.wrapping_add((start - self.offset) as u32);
+            self.offset = start;
+
+            return self.deliver(scratch, handler);
+        }
+
+        Ok(ScanOutcome::Completed)
+    }
+
+    fn segment(
+        &mut self,
+        segment: &Segment<'_>,
+        policy: GapPolicy,
+        max_buffered: usize,
+        scratch: &ScratchRef,
+        handler: Handler,
+    ) -> Result<ScanOutcome> {
+        if self.terminated {
+            return Ok(ScanOutcome::Terminated);
+        }
+        if self.abandoned {
+            return Ok(ScanOutcome::Completed);
+        }
+
+        let seq = if segment.syn {
+            segment.seq.wrapping_add(1)
+        } else {
+            segment.seq
+        };
+        let rel = seq.wrapping_sub(self.next_seq) as i32;
+        let (start, payload) = if rel < 0 {
+            // the retransmitted data was scanned
+            let n = -(rel as i64) as usize;
+
+            (self.offset, segment.payload.get(n..).unwrap_or_default())
+        } else {
+            (self.offset + rel as u64, segment.payload)
+        };
+
+        if payload.is_empty() {
+            Ok(ScanOutcome::Completed)
+        } else if start == self.offset {
+            if self.scan(payload, scratch, handler)?.is_terminated() {
+                return Ok(ScanOutcome::Terminated);
+            }
+
+            self.deliver(scratch, handler)
+        } else {
+            self.buffered += payload.len();
+
+            if let Some(prev) = self.pending.insert(start, payload.to_vec()) {
+                self.buffered -= prev.len();
+            }
+
+            if self.buffered > max_buffered {
+                self.skip_gap(policy, scratch, handler)
+            } else {
+                Ok(ScanOutcome::Completed)
+            }
+        }
+    }
+
+    fn close(mut self, policy: GapPolicy, scratch: &ScratchRef, handler: Handler) -> Result<ScanOutcome> {
+        while !self.pending.is_empty() && !self.abandoned && !self.terminated {
+            self.skip_gap(policy, scratch, handler)?;
+        }
+
+        self.finish(scratch, handler)
+    }
+
+    fn finish(self, scratch: &ScratchRef, handler: Handler) -> Result<ScanOutcome> {
+        if self.terminated {
+            // the callback has terminated the flow, don't report the end of data matches.
+            self.stream.free()?;
+
+            Ok(ScanOutcome::Terminated)
+        } else {
+            Ok(self.stream.close(scratch, handler)?)
+        }
+    }
+}
+
+/// The TCP flow reassembler feeding the payload to a Hyperscan stream per flow.
+///
+/// The flows are identified by the key, e.g. the addresses and ports of the connection.
+/// A flow is started by its first segment, and closed by the `FIN` or `RST` flag,
+/// the end of data matches are reported when the flow is closed.
+pub struct Reassembler<'a, K> {
+    db: &'a StreamingDatabase,
+    flows: HashMap<K, Flow>,
+    policy: GapPolicy,
+    max_buffered: usize,
+}
+
+impl<'a, K: Hash + Eq + Clone> Reassembler<'a, K> {
+    /// Constructs a reassembler opening the streams of the database.
+    pub fn new(db: &'a StreamingDatabase) -> Self {
+        Reassembler {
+            db,
+            flows: HashMap::new(),
+            policy: GapPolicy::default(),
+            max_buffered: DEFAULT_MAX_BUFFERED,
+        }
+    }
+
+    /// Set the policy of handling the missing data, `GapPolicy::Reset` by default.
+    pub fn gap_policy(mut self, policy: GapPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the maximum bytes of the out-of-order data buffered per flow, 64KB by default.
+    pub fn max_buffered(mut self, max_buffered: usize) -> Self {
+        self.max_buffered = max_buffered;
+        self
+    }
+
+    /// Returns the number of the active flows.
+    pub fn len(&self) -> usize {
+        self.flows.len()
+    }
+
+    /// Returns `true` if there is no active flow.
+    pub fn is_empty(&self) -> bool {
+        self.flows.is_empty()
+    }
+
+    /// Returns the activity counters of an active flow.
+    pub fn stats(&self, key: &K) -> Option<&StreamStats> {
+        self.flows.get(key).map(|flow| &flow.stats)
+    }
+
+    /// Feed a segment of the flow, the matches are passed to the callback with the flow key.
+    ///
+    /// Returns `ScanOutcome::Terminated` if the callback terminates the scan of the flow,
+    /// the following segments of the flow are ignored and return `ScanOutcome::Terminated` until it is finished.
+    pub fn segment<F>(
+        &mut self,
+        key: K,
+        segment: &Segment<'_>,
+        scratch: &ScratchRef,
+        mut on_match_event: F,
+    ) -> Result<ScanOutcome>
+    where
+        F: FnMut(&K, u32, u64, u64) -> Matching,
+    {
+        let matches = Cell::new(0);
+        let mut callback = |id, from, to, _flags: u32| {
+            matches.set(matches.get() + 1);
+            on_match_event(&key, id, from, to)
+        };
+        let handler = unsafe { callback.split() };
+
+        if segment.rst {
+            return match self.flows.remove(&key) {
+                Some(flow) => flow.finish(scratch, handler),
+                None => Ok(ScanOutcome::Completed),
+            };
+        }
+
+        let flow = match self.flows.entry(key.clone()) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(Flow {
+                stream: self.db.open_stream()?,
+                next_seq: if segment.syn {
+                    segment.seq.wrapping_add(1)
+                } else {
+                    segment.seq
+                },
+                offset: 0,
+                pending: BTreeMap::new(),
+                buffered: 0,
+                abandoned: false,
+                terminated: false,
+                stats: StreamStats::default(),
+            }),
+        };
+
+        let outcome = flow.segment(segment, self.policy, self.max_buffered, scratch, handler)?;
+        flow.stats.matches += matches.get();
+
+        if segment.fin {
+            if let Some(flow) = self.flows.remove(&key) {
+                return flow.close(self.policy, scratch, handler);
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Close all the active flows, e.g. at the end of a capture.
+    ///
+    /// Terminating the end of data matches of a flow doesn't stop closing the other flows.
+    pub fn flush<F>(&mut self, scratch: &ScratchRef, mut on_match_event: F) -> Result<()>
+    where
+        F: FnMut(&K, u32, u64, u64) -> Matching,
+    {
+        let policy = self.policy;
+
+        self.flows.drain().try_for_each(|(key, flow)| {
+            let mut callback = |id, from, to, _flags: u32| on_match_event(&key, id, from, to);
+            let handler = unsafe { callback.split() };
+
+            flow.close(policy, scratch, handler).map(|_| ())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    use super::*;
+
+    fn feed<'a>(
+        reassembler: &mut Reassembler<'_, u32>,
+        segments: &[Segment<'_>],
+        scratch: &ScratchRef,
+        matches: &'a mut Vec<(u64, u64)>,
+    ) -> &'a [(u64, u64)] {
+        for segment in segments {
+            let outcome = reassembler
+                .segment(1, segment, scratch, |_, _, from, to| {
+                    matches.push((from, to));
+                    Matching::Continue
+                })
+                .unwrap();
+
+            assert_eq!(outcome, ScanOutcome::Completed);
+        }
+
+        matches
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let mut reassembler = Reassembler::new(&db);
+        let mut matches = vec![];
+
+        let segments = [
+            Segment::new(1000, b"").syn(),
+            Segment::new(1015, b" test"),
+            Segment::new(1008, b"st data"),
+        ];
+
+        assert!(feed(&mut reassembler, &segments, &s, &mut matches).is_empty());
+        assert_eq!(reassembler.stats(&1).unwrap().bytes, 0);
+
+        let segments = [Segment::new(1001, b"some te")];
+
+        assert_eq!(feed(&mut reassembler, &segments, &s, &mut matches), &[(5, 9), (15, 19)]);
+        assert_eq!(reassembler.stats(&1).unwrap().bytes, 19);
+        assert_eq!(reassembler.stats(&1).unwrap().matches, 2);
+    }
+
+    #[test]
+    fn test_retransmit() {
+        let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let mut reassembler = Reassembler::new(&db);
+        let mut matches = vec![];
+
+        let segments = [
+            Segment::new(1000, b"").syn(),
+            Segment::new(1001, b"some te"),
+            Segment::new(1001, b"some te"),
+            Segment::new(1006, b"test"),
+            Segment::new(1008, b"st"),
+        ];
+
+        assert_eq!(feed(&mut reassembler, &segments, &s, &mut matches), &[(5, 9)]);
+        assert_eq!(reassembler.stats(&1).unwrap().bytes, 9);
+    }
+
+    #[test]
+    fn test_fin_eod() {
+        let db: StreamingDatabase = pattern! {"test$"; SOM_LEFTMOST}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let mut reassembler = Reassembler::new(&db);
+        let mut matches = vec![];
+
+        let segments = [Segment::new(1000, b"").syn(), Segment::new(1001, b"foo test")];
+
+        assert!(feed(&mut reassembler, &segments, &s, &mut matches).is_empty());
+        assert_eq!(reassembler.len(), 1);
+
+        let segments = [Segment::new(1009, b"").fin()];
+
+        assert_eq!(feed(&mut reassembler, &segments, &s, &mut matches), &[(4, 8)]);
+        assert!(reassembler.is_empty());
+
+        let mut matches = vec![];
+        let segments = [Segment::new(2000, b"foo test"), Segment::new(2008, b"").rst()];
+
+        assert_eq!(feed(&mut reassembler, &segments, &s, &mut matches), &[(4, 8)]);
+        assert!(reassembler.is_empty());
+    }
+
+    #[test]
+    fn test_gap_policy() {
+        let db: StreamingDatabase = pattern! {"test"; SOM_LEFTMOST}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let segments = [Segment::new(1000, b"foo"), Segment::new(1010, b"test")];
+
+        let mut reassembler = Reassembler::new(&db).max_buffered(2);
+        let mut matches = vec![];
+
+        assert_eq!(feed(&mut reassembler, &segments, &s, &mut matches), &[(0, 4)]);
+
+        let mut reassembler = Reassembler::new(&db).max_buffered(2).gap_policy(GapPolicy::Continue);
+        let mut matches = vec![];
+
+        assert_eq!(feed(&mut reassembler, &segments, &s, &mut matches), &[(3, 7)]);
+
+        let mut reassembler = Reassembler::new(&db).max_buffered(2).gap_policy(GapPolicy::Abandon);
+        let mut matches = vec![];
+
+        assert!(feed(&mut reassembler, &segments, &s, &mut matches).is_empty());
+        assert_eq!(reassembler.stats(&1).unwrap().bytes, 3);
+    }
+
+    #[test]
+    fn test_terminated() {
+        let db: StreamingDatabase = pattern! {"test"}.build().unwrap();
+        let s = db.alloc_scratch().unwrap();
+        let mut reassembler = Reassembler::new(&db);
+        let mut matches = 0;
+        let mut on_match = |_: &u32, _: u32, _: u64, _: u64| {
+            matches += 1;
+            Matching::Terminate
+        };
+
+        let outcome = reassembler
+            .segment(1, &Segment::new(1000, b"test test"), &s, &mut on_match)
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Terminated);
+
+        let outcome = reassembler
+            .segment(1, &Segment::new(1009, b" test"), &s, &mut on_match)
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Terminated);
+
+        let outcome = reassembler
+            .segment(1, &Segment::new(1014, b"").fin(), &s, &mut on_match)
+            .unwrap();
+
+        assert_eq!(outcome, ScanOutcome::Terminated);
+        assert!(reassembler.is_empty());
+        assert_eq!(matches, 1);
+    }
+}
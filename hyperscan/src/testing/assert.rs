@@ -0,0 +1,150 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use anyhow::Result;
+
+use crate::common::BlockDatabase;
+use crate::runtime::{Matching, ScratchRef};
+
+/// The difference between the expected and actual matches as `(id, from, to)`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatchDiff {
+    /// The data scanned, which is shown in the message.
+    pub data: Vec<u8>,
+    /// The expected matches which were not found.
+    pub missing: Vec<(u32, u64, u64)>,
+    /// The matches which were found but not expected.
+    pub unexpected: Vec<(u32, u64, u64)>,
+}
+
+impl MatchDiff {
+    /// Returns `true` if the actual matches are the expected ones.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+impl fmt::Display for MatchDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "matches differ when scanning {:?}",
+            String::from_utf8_lossy(&self.data)
+        )?;
+
+        for (id, from, to) in &self.missing {
+            writeln!(f, "- ({}, {}, {})", id, from, to)?;
+        }
+
+        for (id, from, to) in &self.unexpected {
+            writeln!(f, "+ ({}, {}, {})", id, from, to)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for MatchDiff {}
+
+/// Scan the data and collect the matches as `(id, from, to)`.
+pub fn scan_matches<T: AsRef<[u8]>>(
+    db: &BlockDatabase,
+    scratch: &ScratchRef,
+    data: T,
+) -> Result<BTreeSet<(u32, u64, u64)>> {
+    let mut matches = BTreeSet::new();
+
+    let _ = db.scan(data, scratch, |id, from, to, _| {
+        matches.insert((id, from, to));
+        Matching::Continue
+    })?;
+
+    Ok(matches)
+}
+
+/// Scan the data and compare the matches with the expected ones in any order.
+pub fn diff_matches<T, I>(db: &BlockDatabase, scratch: &ScratchRef, data: T, expected: I) -> Result<MatchDiff>
+where
+    T: AsRef<[u8]>,
+    I: IntoIterator<Item = (u32, u64, u64)>,
+{
+    let data = data.as_ref();
+    let actual = scan_matches(db, scratch, data)?;
+    let expected = expected.into_iter().collect::<BTreeSet<_>>();
+
+    Ok(MatchDiff {
+        data: data.to_vec(),
+        missing: expected.difference(&actual).copied().collect(),
+        unexpected: actual.difference(&expected).copied().collect(),
+    })
+}
+
+/// Scan the data and returns a `MatchDiff` error if the matches are not the expected ones.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, testing::{check_matches, MatchDiff}};
+/// let db: BlockDatabase = patterns!("foo", "bar").build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+///
+/// check_matches(&db, &s, "foo bar", vec![(0, 0, 3), (1, 0, 7)]).unwrap();
+///
+/// let err = check_matches(&db, &s, "foo", vec![(1, 0, 7)]).unwrap_err();
+///
+/// assert_eq!(
+///     err.downcast_ref::<MatchDiff>().unwrap().to_string(),
+///     "matches differ when scanning \"foo\"\n- (1, 0, 7)\n+ (0, 0, 3)\n"
+/// );
+/// ```
+pub fn check_matches<T, I>(db: &BlockDatabase, scratch: &ScratchRef, data: T, expected: I) -> Result<()>
+where
+    T: AsRef<[u8]>,
+    I: IntoIterator<Item = (u32, u64, u64)>,
+{
+    let diff = diff_matches(db, scratch, data, expected)?;
+
+    if diff.is_empty() {
+        Ok(())
+    } else {
+        Err(diff.into())
+    }
+}
+
+/// Scan the data and returns a `MatchDiff` error if anything matches.
+pub fn check_no_match<T: AsRef<[u8]>>(db: &BlockDatabase, scratch: &ScratchRef, data: T) -> Result<()> {
+    check_matches(db, scratch, data, None)
+}
+
+/// Asserts the block database matches the data with the expected `(id, from, to)` in any order.
+///
+/// The matches are compared with `testing::check_matches`, and the difference is shown on failure.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{assert_matches, assert_no_match, patterns, prelude::*};
+/// let db: BlockDatabase = patterns!("foo", "bar").build().unwrap();
+///
+/// assert_matches!(db, "foo bar", [(0, 0, 3), (1, 0, 7)]);
+/// assert_no_match!(db, "baz");
+/// ```
+#[macro_export]
+macro_rules! assert_matches {
+    ($db:expr, $data:expr, [$($expected:expr),* $(,)?]) => {{
+        let db: &$crate::BlockDatabase = &$db;
+        let scratch = db.alloc_scratch().expect("alloc scratch");
+
+        if let Err(err) = $crate::testing::check_matches(db, &scratch, $data, vec![$($expected),*]) {
+            panic!("{}", err);
+        }
+    }};
+}
+
+/// Asserts the block database doesn't match the data.
+#[macro_export]
+macro_rules! assert_no_match {
+    ($db:expr, $data:expr) => {
+        $crate::assert_matches!($db, $data, [])
+    };
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::common::{BlockDatabase, StreamingDatabase};
+use crate::ffi;
+use crate::runtime::{Matching, ScratchRef};
+use crate::testing::{scan_matches, Corpus, MatchDiff};
+
+const DEFAULT_MAX_CHUNKS: usize = 8;
+
+/// A checker which scans the same data in block mode and as a stream split at the random boundaries,
+/// and verifies the matches agree, for catching the chunking bugs in the scanning pipelines.
+///
+/// Both databases should be compiled from the same patterns, and the scratch space must be allocated for both.
+/// The start of a stream match beyond the SOM horizon is reported as `HS_OFFSET_PAST_HORIZON`,
+/// such matches are compared with the block matches by the id and the end offset only.
+///
+/// The matches are compared with `MatchDiff`, the missing matches were found in block mode only,
+/// and the unexpected matches were found in stream mode only.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{prelude::*, testing::ConsistencyChecker};
+/// let block: BlockDatabase = pattern! {r"foo\d+bar"; SOM_LEFTMOST}.build().unwrap();
+/// let streaming: StreamingDatabase = pattern! {r"foo\d+bar"; SOM_LEFTMOST}.build().unwrap();
+/// let mut s = block.alloc_scratch().unwrap();
+/// streaming.realloc_scratch(&mut s).unwrap();
+///
+/// let mut checker = ConsistencyChecker::with_seed(42);
+///
+/// for _ in 0..16 {
+///     checker.check(&block, &streaming, &s, "foo123bar and foo4bar").unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConsistencyChecker {
+    rng: StdRng,
+    max_chunks: usize,
+}
+
+impl Default for ConsistencyChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConsistencyChecker {
+    /// Constructs a checker seeded from the system entropy.
+    pub fn new() -> Self {
+        Self::from_rng(StdRng::from_entropy())
+    }
+
+    /// Constructs a checker with the seed, so the splits are reproducible.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: StdRng) -> Self {
+        ConsistencyChecker {
+            rng,
+            max_chunks: DEFAULT_MAX_CHUNKS,
+        }
+    }
+
+    /// Set the maximum number of the chunks the data is split into, 8 by default.
+    pub fn max_chunks(mut self, max_chunks: usize) -> Self {
+        self.max_chunks = max_chunks.max(1);
+        self
+    }
+
+    /// Split the data into the chunks at the random boundaries, the chunks may be empty.
+    pub fn split<'d>(&mut self, data: &'d [u8]) -> Vec<&'d [u8]> {
+        let n = self.rng.gen_range(0..self.max_chunks);
+        let mut boundaries = (0..n).map(|_| self.rng.gen_range(0..=data.len())).collect::<Vec<_>>();
+
+        boundaries.sort_unstable();
+
+        let mut chunks = Vec::with_capacity(n + 1);
+        let mut start = 0;
+
+        for end in boundaries {
+            chunks.push(&data[start..end]);
+            start = end;
+        }
+
+        chunks.push(&data[start..]);
+        chunks
+    }
+
+    /// Scan the chunks as a stream and collect the matches as `(id, from, to)`.
+    pub fn scan_chunks(
+        &self,
+        db: &StreamingDatabase,
+        scratch: &ScratchRef,
+        chunks: &[&[u8]],
+    ) -> Result<BTreeSet<(u32, u64, u64)>> {
+        let mut matches = BTreeSet::new();
+        let mut callback = |id, from, to, _flags: u32| {
+            matches.insert((id, from, to));
+            Matching::Continue
+        };
+        let stream = db.open_stream()?;
+
+        for chunk in chunks {
+            let _ = stream.scan(chunk, scratch, &mut callback)?;
+        }
+
+        let _ = stream.close(scratch, callback)?;
+
+        Ok(matches)
+    }
+
+    /// Scan the data in block mode and as a randomly split stream, and compare the matches.
+    pub fn diff<T: AsRef<[u8]>>(
+        &mut self,
+        block: &BlockDatabase,
+        streaming: &StreamingDatabase,
+        scratch: &ScratchRef,
+        data: T,
+    ) -> Result<MatchDiff> {
+        let data = data.as_ref();
+        let chunks = self.split(data);
+
+        self.diff_chunks(block, streaming, scratch, data, &chunks)
+    }
+
+    /// Scan the data in block mode and as a randomly split stream,
+    /// returns a `MatchDiff` error with the split offsets if the matches disagree.
+    pub fn check<T: AsRef<[u8]>>(
+        &mut self,
+        block: &BlockDatabase,
+        streaming: &StreamingDatabase,
+        scratch: &ScratchRef,
+        data: T,
+    ) -> Result<()> {
+        let data = data.as_ref();
+        let chunks = self.split(data);
+        let diff = self.diff_chunks(block, streaming, scratch, data, &chunks)?;
+
+        if diff.is_empty() {
+            return Ok(());
+        }
+
+        let offsets = chunks[..chunks.len() - 1]
+            .iter()
+            .scan(0, |offset, chunk| {
+                *offset += chunk.len();
+                Some(*offset)
+            })
+            .collect::<Vec<_>>();
+
+        Err(diff).with_context(|| format!("stream split at {:?}", offsets))
+    }
+
+    /// Check each sample of the corpus with `rounds` random splits.
+    pub fn check_corpus(
+        &mut self,
+        block: &BlockDatabase,
+        streaming: &StreamingDatabase,
+        scratch: &ScratchRef,
+        corpus: &Corpus,
+        rounds: usize,
+    ) -> Result<()> {
+        let samples = corpus
+            .positives
+            .iter()
+            .map(|(_, sample)| sample)
+            .chain(corpus.negatives.iter());
+
+        for sample in samples {
+            for _ in 0..rounds {
+                self.check(block, streaming, scratch, sample)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn diff_chunks(
+        &self,
+        block: &BlockDatabase,
+        streaming: &StreamingDatabase,
+        scratch: &ScratchRef,
+        data: &[u8],
+        chunks: &[&[u8]],
+    ) -> Result<MatchDiff> {
+        let mut expected = scan_matches(block, scratch, data)?;
+        let mut unexpected = vec![];
+
+        for (id, from, to) in self.scan_chunks(streaming, scratch, chunks)? {
+            let found = if from == ffi::HS_OFFSET_PAST_HORIZON as u64 {
+                expected.iter().find(|&&(i, _, t)| i == id && t == to).copied()
+            } else {
+                Some((id, from, to)).filter(|m| expected.contains(m))
+            };
+
+            match found {
+                Some(m) => {
+                    expected.remove(&m);
+                }
+                None => unexpected.push((id, from, to)),
+            }
+        }
+
+        Ok(MatchDiff {
+            data: data.to_vec(),
+            missing: expected.into_iter().collect(),
+            unexpected,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let mut checker = ConsistencyChecker::with_seed(1).max_chunks(4);
+        let data = b"hello world";
+
+        for _ in 0..32 {
+            let chunks = checker.split(data);
+
+            assert!(!chunks.is_empty() && chunks.len() <= 4);
+            assert_eq!(chunks.concat(), data);
+        }
+
+        assert!(checker.split(b"").iter().all(|chunk| chunk.is_empty()));
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::Result;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use regex_syntax::hir::{Class, Hir, HirKind};
+use regex_syntax::ParserBuilder;
+
+use crate::common::BlockDatabase;
+use crate::compile::{Flags, Pattern, Patterns};
+use crate::runtime::{Matching, ScratchRef};
+
+const DEFAULT_MAX_REPEAT: u32 = 3;
+const DEFAULT_SAMPLE_LEN: usize = 32;
+const MAX_ATTEMPTS: usize = 16;
+
+/// A corpus of the samples generated from a pattern set.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Corpus {
+    /// The samples which should match the pattern with the id.
+    pub positives: Vec<(u32, Vec<u8>)>,
+    /// The samples which don't match any pattern.
+    pub negatives: Vec<Vec<u8>>,
+}
+
+/// A generator of the positive and negative samples of the patterns, for smoke-testing the compiled databases.
+///
+/// The positive samples are generated from the syntax tree of the expressions parsed by `regex-syntax`,
+/// the patterns with the syntax it doesn't support are skipped, and the look-around assertions,
+/// e.g. `\b` or `$`, are ignored, so a few samples may not match. The negative samples are the random
+/// strings which are verified not to match the database.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hyperscan::{patterns, prelude::*, testing::CorpusGenerator};
+/// let patterns = patterns!(r"foo\d+", r"/ba[rz]{2}/i");
+/// let db: BlockDatabase = patterns.build().unwrap();
+/// let s = db.alloc_scratch().unwrap();
+///
+/// let corpus = CorpusGenerator::with_seed(42).generate(&patterns, &db, &s, 4).unwrap();
+///
+/// assert_eq!(corpus.positives.len(), 8);
+/// assert_eq!(corpus.negatives.len(), 4);
+///
+/// for (id, sample) in &corpus.positives {
+///     let mut matched = vec![];
+///     db.scan(sample, &s, |id, _, _, _| {
+///         matched.push(id);
+///         Matching::Continue
+///     }).unwrap();
+///
+///     assert!(matched.contains(id));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CorpusGenerator {
+    rng: StdRng,
+    max_repeat: u32,
+    sample_len: usize,
+}
+
+impl Default for CorpusGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CorpusGenerator {
+    /// Constructs a generator seeded from the system entropy.
+    pub fn new() -> Self {
+        Self::from_rng(StdRng::from_entropy())
+    }
+
+    /// Constructs a generator with the seed, so the corpus is reproducible.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: StdRng) -> Self {
+        CorpusGenerator {
+            rng,
+            max_repeat: DEFAULT_MAX_REPEAT,
+            sample_len: DEFAULT_SAMPLE_LEN,
+        }
+    }
+
+    /// Set the maximum repetitions of the unbounded repeats, e.g. `a*` or `a{2,}`, 3 by default.
+    pub fn max_repeat(mut self, max_repeat: u32) -> Self {
+        self.max_repeat = max_repeat;
+        self
+    }
+
+    /// Set the length of the negative samples, 32 bytes by default.
+    pub fn sample_len(mut self, sample_len: usize) -> Self {
+        self.sample_len = sample_len;
+        self
+    }
+
+    /// Generate a sample which should match the pattern, returns `None` if the expression is not supported.
+    pub fn positive(&mut self, pattern: &Pattern) -> Option<Vec<u8>> {
+        let utf8 = pattern.flags.contains(Flags::UTF8);
+        let hir = ParserBuilder::new()
+            .case_insensitive(pattern.flags.contains(Flags::CASELESS))
+            .multi_line(pattern.flags.contains(Flags::MULTILINE))
+            .dot_matches_new_line(pattern.flags.contains(Flags::DOTALL))
+            .unicode(utf8)
+            .utf8(utf8)
+            .build()
+            .parse(&pattern.expression)
+            .ok()?;
+        let mut sample = vec![];
+
+        self.generate_hir(&hir, &mut sample);
+
+        Some(sample)
+    }
+
+    /// Generate the positive samples for each pattern, with the pattern id as the build does.
+    pub fn positives(&mut self, patterns: &Patterns, n: usize) -> Vec<(u32, Vec<u8>)> {
+        let mut samples = vec![];
+
+        for (i, pattern) in patterns.iter().enumerate() {
+            let id = pattern.id.unwrap_or(i) as u32;
+
+            for _ in 0..n {
+                if let Some(sample) = self.positive(pattern) {
+                    samples.push((id, sample));
+                }
+            }
+        }
+
+        samples
+    }
+
+    /// Generate the random printable samples which don't match the database.
+    pub fn negatives(&mut self, db: &BlockDatabase, scratch: &ScratchRef, n: usize) -> Result<Vec<Vec<u8>>> {
+        let mut samples = vec![];
+
+        for _ in 0..n * MAX_ATTEMPTS {
+            if samples.len() == n {
+                break;
+            }
+
+            let sample = (0..self.sample_len)
+                .map(|_| self.rng.gen_range(b' '..=b'~'))
+                .collect::<Vec<_>>();
+            let mut matched = false;
+
+            let _ = db.scan(&sample, scratch, |_, _, _, _| {
+                matched = true;
+                Matching::Terminate
+            })?;
+
+            if !matched {
+                samples.push(sample);
+            }
+        }
+
+        Ok(samples)
+    }
+
+    /// Generate a corpus with `n` positive samples for each pattern and `n` negative samples.
+    pub fn generate(
+        &mut self,
+        patterns: &Patterns,
+        db: &BlockDatabase,
+        scratch: &ScratchRef,
+        n: usize,
+    ) -> Result<Corpus> {
+        Ok(Corpus {
+            positives: self.positives(patterns, n),
+            negatives: self.negatives(db, scratch, n)?,
+        })
+    }
+
+    fn generate_hir(&mut self, hir: &Hir, buf: &mut Vec<u8>) {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => {}
+            HirKind::Literal(lit) => buf.extend_from_slice(&lit.0),
+            HirKind::Class(Class::Unicode(cls)) => {
+                if let Some(range) = pick(&mut self.rng, cls.ranges()) {
+                    let c = self.rng.gen_range(u32::from(range.start())..=u32::from(range.end()));
+                    let c = std::char::from_u32(c).unwrap_or_else(|| range.start());
+
+                    buf.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+            }
+            HirKind::Class(Class::Bytes(cls)) => {
+                if let Some(range) = pick(&mut self.rng, cls.ranges()) {
+                    buf.push(self.rng.gen_range(range.start()..=range.end()));
+                }
+            }
+            HirKind::Repetition(rep) => {
+                let max = rep.max.unwrap_or(rep.min + self.max_repeat).max(rep.min);
+                let n = self.rng.gen_range(rep.min..=max);
+
+                for _ in 0..n {
+                    self.generate_hir(&rep.sub, buf);
+                }
+            }
+            HirKind::Capture(cap) => self.generate_hir(&cap.sub, buf),
+            HirKind::Concat(hirs) => {
+                for hir in hirs {
+                    self.generate_hir(hir, buf);
+                }
+            }
+            HirKind::Alternation(hirs) => {
+                if let Some(hir) = pick(&mut self.rng, hirs) {
+                    self.generate_hir(hir, buf);
+                }
+            }
+        }
+    }
+}
+
+fn pick<'a, T>(rng: &mut StdRng, items: &'a [T]) -> Option<&'a T> {
+    if items.is_empty() {
+        None
+    } else {
+        Some(&items[rng.gen_range(0..items.len())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positive() {
+        let mut gen = CorpusGenerator::with_seed(1);
+
+        for _ in 0..10 {
+            let sample = gen.positive(&"ab{2}[0-9]c?".parse().unwrap()).unwrap();
+
+            assert!(sample.starts_with(b"abb"));
+            assert!(sample[3].is_ascii_digit());
+            assert!(sample.len() == 4 || sample.ends_with(b"c"));
+        }
+
+        assert_eq!(gen.positive(&"(?<=a)b".parse().unwrap()), None);
+    }
+}
@@ -0,0 +1,11 @@
+//! Utilities for testing the pattern sets and the scanning pipelines.
+//!
+//! The `testing` feature is intended for the dev-dependencies of the downstream crates,
+//! e.g. smoke-testing the databases after compiling the rules.
+mod assert;
+mod consistency;
+mod corpus;
+
+pub use self::assert::{check_matches, check_no_match, diff_matches, scan_matches, MatchDiff};
+pub use self::consistency::ConsistencyChecker;
+pub use self::corpus::{Corpus, CorpusGenerator};
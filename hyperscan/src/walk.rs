@@ -0,0 +1,155 @@
+//! Recursive directory scanner.
+//!
+//! The `Walker` walks the directories like [ripgrep](https://github.com/BurntSushi/ripgrep),
+//! respects the `.gitignore` and `.ignore` files, dispatches the files to a pool of workers
+//! each with its own scratch space, and sends the matches to a `MatchSink`.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! # use std::path::Path;
+//! # use hyperscan::{prelude::*, walk::Walker};
+//! let pattern = pattern! {"TODO"};
+//! let block: BlockDatabase = pattern.build().unwrap();
+//! let streaming: StreamingDatabase = pattern.build().unwrap();
+//!
+//! Walker::new("src", &block, &streaming)
+//!     .threads(4)
+//!     .run(&|path: &Path, _id: u32, _from: u64, to: u64| {
+//!         println!("{}: {}", path.display(), to);
+//!         Matching::Continue
+//!     })
+//!     .unwrap();
+//! ```
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+use anyhow::Result;
+use ignore::{WalkBuilder, WalkState};
+
+use crate::common::{BlockDatabase, StreamingDatabase};
+use crate::runtime::{MatchSink, ScanOutcome, ScratchRef};
+
+/// The files larger than it are scanned in streaming mode by default.
+pub const DEFAULT_MAX_BLOCK_SIZE: u64 = 1 << 20;
+
+/// A parallel recursive directory scanner.
+///
+/// The small files are read into memory and scanned with the block mode database,
+/// the large files are scanned with the streaming mode database chunk by chunk.
+pub struct Walker<'a> {
+    block: &'a BlockDatabase,
+    streaming: &'a StreamingDatabase,
+    builder: WalkBuilder,
+    max_block_size: u64,
+}
+
+impl<'a> Walker<'a> {
+    /// Constructs a walker of the path with the block and streaming mode databases compiled from the same patterns.
+    pub fn new<P: AsRef<Path>>(path: P, block: &'a BlockDatabase, streaming: &'a StreamingDatabase) -> Self {
+        Walker {
+            block,
+            streaming,
+            builder: WalkBuilder::new(path),
+            max_block_size: DEFAULT_MAX_BLOCK_SIZE,
+        }
+    }
+
+    /// Add a file path to walk.
+    pub fn add<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.builder.add(path);
+        self
+    }
+
+    /// The number of worker threads, `0` to choose automatically.
+    pub fn threads(mut self, n: usize) -> Self {
+        self.builder.threads(n);
+        self
+    }
+
+    /// The files larger than the size in bytes are scanned in streaming mode.
+    pub fn max_block_size(mut self, size: u64) -> Self {
+        self.max_block_size = size;
+        self
+    }
+
+    /// Returns the underlying builder to configure the ignore rules, hidden files, symbolic links etc.
+    pub fn walk_builder(&mut self) -> &mut WalkBuilder {
+        &mut self.builder
+    }
+
+    /// Walk the directories and scan the files, the matches and errors are sent to the sink.
+    pub fn run<S: MatchSink>(self, sink: &S) -> Result<()> {
+        let block_scratch = self.block.alloc_scratch()?;
+        let stream_scratch = self.streaming.alloc_scratch()?;
+        let Walker {
+            block,
+            streaming,
+            builder,
+            max_block_size,
+        } = self;
+
+        builder.build_parallel().run(|| {
+            let block_scratch = block_scratch.clone();
+            let stream_scratch = stream_scratch.clone();
+
+            Box::new(move |entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(err) => {
+                        let kind = err.io_error().map_or(io::ErrorKind::Other, io::Error::kind);
+
+                        sink.on_error(None, &io::Error::new(kind, err).into());
+                        return WalkState::Continue;
+                    }
+                };
+
+                if !entry.file_type().map_or(false, |ty| ty.is_file()) {
+                    return WalkState::Continue;
+                }
+
+                let path = entry.path();
+                let res = entry.metadata().map_err(crate::Error::from).and_then(|md| {
+                    if md.len() <= max_block_size {
+                        scan_block(block, &block_scratch, path, sink)
+                    } else {
+                        scan_streaming(streaming, &stream_scratch, path, sink)
+                    }
+                });
+
+                if let Err(err) = res {
+                    sink.on_error(Some(path), &err);
+                }
+
+                WalkState::Continue
+            })
+        });
+
+        Ok(())
+    }
+}
+
+fn scan_block<S: MatchSink>(
+    db: &BlockDatabase,
+    scratch: &ScratchRef,
+    path: &Path,
+    sink: &S,
+) -> crate::Result<ScanOutcome> {
+    let data = fs::read(path)?;
+
+    db.scan(&data, scratch, |id, from, to, _flags| sink.on_match(path, id, from, to))
+}
+
+fn scan_streaming<S: MatchSink>(
+    db: &StreamingDatabase,
+    scratch: &ScratchRef,
+    path: &Path,
+    sink: &S,
+) -> crate::Result<ScanOutcome> {
+    let mut f = File::open(path)?;
+
+    db.scan(&mut f, scratch, |id, from, to, _flags| {
+        sink.on_match(path, id, from, to)
+    })
+}
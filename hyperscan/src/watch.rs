@@ -0,0 +1,150 @@
+//! Filesystem watching service.
+//!
+//! The `WatchService` watches the directories with [notify](https://docs.rs/notify),
+//! scans the created or modified files against a hot-reloadable database,
+//! and sends the matches to a `MatchSink`.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! # use std::path::Path;
+//! # use std::sync::Arc;
+//! # use hyperscan::{prelude::*, watch::WatchService, DatabaseHandle};
+//! let db: BlockDatabase = pattern! {"password"}.build().unwrap();
+//! let handle = Arc::new(DatabaseHandle::new(db));
+//! let mut service = WatchService::new(handle.clone()).unwrap();
+//!
+//! service.watch("/var/spool/uploads", true).unwrap();
+//!
+//! // reload the patterns from another thread
+//! handle.store(pattern! {"secret"}.build().unwrap());
+//!
+//! service
+//!     .run(&|path: &Path, id: u32, _from: u64, to: u64| {
+//!         println!("{}: pattern {} @ {}", path.display(), id, to);
+//!         Matching::Continue
+//!     })
+//!     .unwrap();
+//! ```
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::common::{Block, DatabaseHandle};
+use crate::runtime::{MatchSink, Scratch};
+
+/// A service scanning the new or modified files in the watched directories.
+pub struct WatchService {
+    db: Arc<DatabaseHandle<Block>>,
+    generation: usize,
+    scratch: Scratch,
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+impl WatchService {
+    /// Constructs a service scanning the files with the database of the handle.
+    ///
+    /// The database can be replaced with `DatabaseHandle::store()`,
+    /// the following files will be scanned with the new database.
+    pub fn new(db: Arc<DatabaseHandle<Block>>) -> Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(tx)?;
+        let (cur, generation) = db.snapshot();
+        let scratch = cur.alloc_scratch()?;
+
+        Ok(WatchService {
+            db,
+            generation,
+            scratch,
+            watcher,
+            events,
+        })
+    }
+
+    /// Watch the file or directory, and optionally its subdirectories.
+    pub fn watch<P: AsRef<Path>>(&mut self, path: P, recursive: bool) -> Result<()> {
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+
+        self.watcher.watch(path.as_ref(), mode).map_err(anyhow::Error::from)
+    }
+
+    /// Stop watching the file or directory.
+    pub fn unwatch<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.watcher.unwatch(path.as_ref()).map_err(anyhow::Error::from)
+    }
+
+    /// Keep scanning the changed files until the watcher is stopped.
+    pub fn run<S: MatchSink>(&mut self, sink: &S) -> Result<()> {
+        loop {
+            self.poll(Duration::from_secs(1), sink)?;
+        }
+    }
+
+    /// Wait for the filesystem events until the timeout, scan the changed files and returns the number of them.
+    ///
+    /// The failures of accessing or scanning the files are sent to the sink.
+    pub fn poll<S: MatchSink>(&mut self, timeout: Duration, sink: &S) -> Result<usize> {
+        let deadline = Instant::now() + timeout;
+        let mut changed = BTreeSet::new();
+
+        loop {
+            let event = match self
+                .events
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Err(anyhow!("watcher stopped")),
+            };
+
+            match event {
+                Ok(event) if is_changed(&event.kind) => changed.extend(event.paths),
+                Ok(_) => {}
+                Err(err) => sink.on_error(None, &io::Error::new(io::ErrorKind::Other, err).into()),
+            }
+        }
+
+        let db = self.db.refresh(&mut self.generation, &mut self.scratch)?;
+
+        let mut scanned = 0;
+
+        for path in changed.iter().filter(|path| path.is_file()) {
+            let res = fs::read(path).map_err(crate::Error::from).and_then(|data| {
+                db.scan(&data, &self.scratch, |id, from, to, _| {
+                    sink.on_match(path, id, from, to)
+                })
+            });
+
+            match res {
+                Ok(_) => scanned += 1,
+                Err(err) => sink.on_error(Some(path.as_path()), &err),
+            }
+        }
+
+        Ok(scanned)
+    }
+}
+
+fn is_changed(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_)
+            | EventKind::Modify(ModifyKind::Any)
+            | EventKind::Modify(ModifyKind::Data(_))
+            | EventKind::Modify(ModifyKind::Name(RenameMode::To))
+            | EventKind::Modify(ModifyKind::Name(RenameMode::Both))
+    )
+}